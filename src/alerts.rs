@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::queue::{publish_message, SharedQueueRegistry};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertKind {
+    Failing,
+    Recovered,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleAlert {
+    pub kind: AlertKind,
+    pub service: String,
+    pub index: usize,
+    pub method: String,
+    pub endpoint: String,
+    pub queue: Option<String>,
+    pub failures: u32,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::Failing => "schedule_failing",
+            AlertKind::Recovered => "schedule_recovered",
+        }
+    }
+}
+
+impl ScheduleAlert {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "event": self.kind.as_str(),
+            "service": self.service,
+            "schedule": self.index,
+            "method": self.queue.is_none().then_some(&self.method),
+            "endpoint": self.queue.is_none().then(|| format!("/{}", self.endpoint)),
+            "queue": self.queue,
+            "consecutive_failures": self.failures,
+            "last_status": self.status,
+            "last_error": self.error,
+            "at": self.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        })
+    }
+}
+
+// Alerts are always logged; the HTTP callback and the internal queue are
+// both optional and get the same JSON event.
+#[derive(Clone, Default)]
+pub struct AlertSink {
+    webhook_url: Option<String>,
+    queue: Option<(String, SharedQueueRegistry)>,
+    request_timeout: Duration,
+}
+
+impl AlertSink {
+    pub fn new(
+        webhook_url: Option<String>,
+        queue: Option<String>,
+        queues: &SharedQueueRegistry,
+        request_timeout: Duration,
+    ) -> Self {
+        Self {
+            webhook_url,
+            queue: queue.map(|queue| (queue, Arc::clone(queues))),
+            request_timeout,
+        }
+    }
+
+    pub fn send(&self, alert: &ScheduleAlert) {
+        let label = match &alert.queue {
+            Some(queue) => format!("'queue {queue}' for service '{}'", alert.service),
+            None => format!(
+                "'{} /{}' for service '{}'",
+                alert.method, alert.endpoint, alert.service
+            ),
+        };
+        match alert.kind {
+            AlertKind::Failing => eprintln!(
+                "Scheduled webhook {label} is failing: {} runs in a row failed",
+                alert.failures
+            ),
+            AlertKind::Recovered => eprintln!("Scheduled webhook {label} has recovered"),
+        }
+
+        let payload = alert.to_json().to_string();
+        if let Some(url) = &self.webhook_url {
+            let result = ureq::post(url)
+                .timeout(self.request_timeout)
+                .set("Content-Type", "application/json")
+                .send_string(&payload);
+            if let Err(error) = result {
+                eprintln!("Failed to send schedule alert for {label} to {url}: {error}");
+            }
+        }
+        if let Some((queue, registry)) = &self.queue {
+            if let Err(error) =
+                publish_message(registry, queue, "application/json", payload.as_bytes())
+            {
+                eprintln!(
+                    "Failed to publish schedule alert for {label} to queue '{queue}': {error:#}"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::{initialize_queue_registry, with_queue_registry};
+
+    #[test]
+    fn publishes_the_alert_event_to_the_configured_queue() {
+        let alert = ScheduleAlert {
+            kind: AlertKind::Failing,
+            service: "svc".into(),
+            index: 1,
+            method: "POST".into(),
+            endpoint: "sync".into(),
+            queue: None,
+            failures: 3,
+            status: Some(503),
+            error: None,
+            at: DateTime::from_timestamp(0, 0).unwrap(),
+        };
+        let event = alert.to_json();
+        assert_eq!(event["event"], "schedule_failing");
+        assert_eq!(event["endpoint"], "/sync");
+        assert_eq!(event["last_status"], 503);
+        assert_eq!(event["at"], "1970-01-01T00:00:00Z");
+
+        let queues = initialize_queue_registry(&[]);
+        let sink = AlertSink::new(
+            None,
+            Some("ops.alerts".into()),
+            &queues,
+            Duration::from_secs(1),
+        );
+        sink.send(&alert);
+        AlertSink::default().send(&alert);
+        let snapshot = with_queue_registry(&queues, |registry| registry.snapshot()).unwrap();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].name, "ops.alerts");
+        assert_eq!(snapshot[0].message_count, 1);
+    }
+}
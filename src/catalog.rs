@@ -0,0 +1,314 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{bail, Result};
+
+use crate::config::Service;
+
+pub type SharedServiceCatalog = Arc<CatalogSlot>;
+
+#[derive(Debug)]
+pub struct CatalogSlot {
+    current: Mutex<Arc<ServiceCatalog>>,
+    // Held by whoever derives the next catalog from the current one, from the
+    // snapshot it starts from until its changes are applied.
+    writer: Mutex<()>,
+}
+
+pub struct CatalogWrite<'a> {
+    slot: &'a CatalogSlot,
+    snapshot: Arc<ServiceCatalog>,
+    _writer: MutexGuard<'a, ()>,
+}
+
+#[derive(Debug)]
+pub struct ServiceCatalog {
+    services: Vec<Service>,
+    // Indices into `services` with the prefix split into segments, longest first.
+    routes: Vec<(Vec<String>, usize)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PrefixConflict {
+    Duplicate {
+        prefix: String,
+        services: Vec<String>,
+    },
+    Shadowing {
+        prefix: String,
+        service: String,
+        nested_prefix: String,
+        nested_service: String,
+    },
+}
+
+impl fmt::Display for PrefixConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrefixConflict::Duplicate { prefix, services } => write!(
+                f,
+                "prefix '{prefix}' is declared by several services: {}",
+                services.join(", ")
+            ),
+            PrefixConflict::Shadowing {
+                prefix,
+                service,
+                nested_prefix,
+                nested_service,
+            } => write!(
+                f,
+                "prefix '{prefix}' of service '{service}' shadows prefix '{nested_prefix}' of service '{nested_service}'"
+            ),
+        }
+    }
+}
+
+impl ServiceCatalog {
+    pub fn build(services: Vec<Service>) -> Result<Self> {
+        let conflicts = find_prefix_conflicts(&services);
+        if !conflicts.is_empty() {
+            let messages: Vec<String> = conflicts.iter().map(ToString::to_string).collect();
+            bail!("{}", messages.join("; "));
+        }
+
+        let mut routes: Vec<(Vec<String>, usize)> = services
+            .iter()
+            .enumerate()
+            .map(|(index, service)| (prefix_segments(&service.prefix), index))
+            .collect();
+        routes.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        Ok(Self { services, routes })
+    }
+
+    pub fn services(&self) -> &[Service] {
+        &self.services
+    }
+
+    // Prefixes match whole segments, so `sap` never captures `sap-adapter/...`,
+    // and the longest matching prefix wins.
+    pub fn resolve_route(&self, path: &str) -> Option<(&Service, String)> {
+        self.routes.iter().find_map(|(prefix, index)| {
+            let rest = strip_prefix_segments(path, prefix)?;
+            let endpoint_path = rest.trim_start_matches('/');
+            (!endpoint_path.is_empty()).then(|| (&self.services[*index], endpoint_path.to_string()))
+        })
+    }
+}
+
+fn strip_prefix_segments<'p>(path: &'p str, prefix: &[String]) -> Option<&'p str> {
+    let mut rest = path;
+    for segment in prefix {
+        rest = rest
+            .trim_start_matches('/')
+            .strip_prefix(segment.as_str())?;
+        if !(rest.is_empty() || rest.starts_with('/')) {
+            return None;
+        }
+    }
+    Some(rest)
+}
+
+fn prefix_segments(prefix: &str) -> Vec<String> {
+    prefix
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn find_prefix_conflicts(services: &[Service]) -> Vec<PrefixConflict> {
+    let mut conflicts = Vec::new();
+    let prefixes: Vec<(Vec<String>, &Service)> = services
+        .iter()
+        .map(|service| (prefix_segments(&service.prefix), service))
+        .collect();
+
+    let mut reported: Vec<&[String]> = Vec::new();
+    for (segments, _) in &prefixes {
+        if reported.contains(&segments.as_slice()) {
+            continue;
+        }
+        let owners: Vec<String> = prefixes
+            .iter()
+            .filter(|(other, _)| other == segments)
+            .map(|(_, service)| service.name.clone())
+            .collect();
+        if owners.len() > 1 {
+            conflicts.push(PrefixConflict::Duplicate {
+                prefix: segments.join("/"),
+                services: owners,
+            });
+        }
+        reported.push(segments);
+    }
+
+    for (segments, service) in &prefixes {
+        for (nested, nested_service) in &prefixes {
+            if nested.len() > segments.len() && nested.starts_with(segments) {
+                conflicts.push(PrefixConflict::Shadowing {
+                    prefix: segments.join("/"),
+                    service: service.name.clone(),
+                    nested_prefix: nested.join("/"),
+                    nested_service: nested_service.name.clone(),
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+pub fn initialize_service_catalog(catalog: ServiceCatalog) -> SharedServiceCatalog {
+    Arc::new(CatalogSlot {
+        current: Mutex::new(Arc::new(catalog)),
+        writer: Mutex::new(()),
+    })
+}
+
+// Readers keep the snapshot they took for the whole request, so a reload never
+// changes the service set halfway through handling it.
+pub fn current_catalog(catalog: &SharedServiceCatalog) -> Arc<ServiceCatalog> {
+    match catalog.current.lock() {
+        Ok(guard) => Arc::clone(&guard),
+        Err(poisoned) => Arc::clone(&poisoned.into_inner()),
+    }
+}
+
+// Writers queue up here, so a reload and a schedule edit never start from the
+// same snapshot and drop each other's changes. Readers are not blocked.
+pub fn begin_catalog_write(catalog: &SharedServiceCatalog) -> CatalogWrite<'_> {
+    let writer = match catalog.writer.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    CatalogWrite {
+        slot: catalog,
+        snapshot: current_catalog(catalog),
+        _writer: writer,
+    }
+}
+
+impl CatalogWrite<'_> {
+    pub fn snapshot(&self) -> &Arc<ServiceCatalog> {
+        &self.snapshot
+    }
+
+    pub fn replace(&mut self, next: ServiceCatalog) {
+        self.snapshot = Arc::new(next);
+        match self.slot.current.lock() {
+            Ok(mut guard) => *guard = Arc::clone(&self.snapshot),
+            Err(poisoned) => *poisoned.into_inner() = Arc::clone(&self.snapshot),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServiceKind;
+
+    fn service(name: &str, prefix: &str) -> Service {
+        Service {
+            kind: ServiceKind::Adapter,
+            prefix: prefix.into(),
+            ..Service::for_test(name)
+        }
+    }
+
+    #[test]
+    fn routes_on_whole_segments() {
+        let catalog = ServiceCatalog::build(vec![
+            service("sap", "sap"),
+            service("sap_adapter", "sap-adapter"),
+            service("billing", "billing/v2"),
+        ])
+        .expect("catalog");
+
+        let route = |path: &str| {
+            catalog
+                .resolve_route(path)
+                .map(|(service, rest)| (service.name.clone(), rest))
+        };
+
+        assert_eq!(
+            route("sap-adapter/invoices"),
+            Some(("sap_adapter".into(), "invoices".into()))
+        );
+        assert_eq!(
+            route("sap/invoices/1"),
+            Some(("sap".into(), "invoices/1".into()))
+        );
+        assert_eq!(
+            route("billing/v2/items"),
+            Some(("billing".into(), "items".into()))
+        );
+        assert_eq!(route("billing/items"), None);
+        assert_eq!(route("sap"), None);
+        assert_eq!(route("sapx/invoices"), None);
+    }
+
+    #[test]
+    fn rejects_duplicate_and_shadowing_prefixes() {
+        let services = vec![
+            service("a", "shared"),
+            service("b", "/shared/"),
+            service("c", "sap"),
+            service("d", "sap/v2"),
+        ];
+
+        assert_eq!(
+            find_prefix_conflicts(&services),
+            vec![
+                PrefixConflict::Duplicate {
+                    prefix: "shared".into(),
+                    services: vec!["a".into(), "b".into()],
+                },
+                PrefixConflict::Shadowing {
+                    prefix: "sap".into(),
+                    service: "c".into(),
+                    nested_prefix: "sap/v2".into(),
+                    nested_service: "d".into(),
+                },
+            ]
+        );
+
+        let error = ServiceCatalog::build(services).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("prefix 'sap' of service 'c' shadows prefix 'sap/v2' of service 'd'"));
+    }
+
+    #[test]
+    fn writers_start_from_the_previous_writers_result() {
+        let shared =
+            initialize_service_catalog(ServiceCatalog::build(vec![service("a", "a")]).unwrap());
+
+        let mut first = begin_catalog_write(&shared);
+        let second = std::thread::spawn({
+            let shared = Arc::clone(&shared);
+            move || {
+                let mut write = begin_catalog_write(&shared);
+                let mut services = write.snapshot().services().to_vec();
+                services.push(service("c", "c"));
+                write.replace(ServiceCatalog::build(services).unwrap());
+            }
+        });
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let mut services = first.snapshot().services().to_vec();
+        services.push(service("b", "b"));
+        first.replace(ServiceCatalog::build(services).unwrap());
+        assert_eq!(current_catalog(&shared).services().len(), 2);
+        drop(first);
+
+        second.join().unwrap();
+        let names: Vec<String> = current_catalog(&shared)
+            .services()
+            .iter()
+            .map(|service| service.name.clone())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+}
@@ -1,6 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{anyhow, bail, Context, Result};
 use serde::de::{self, Deserializer};
@@ -9,7 +10,19 @@ use serde_json::Value;
 use tiny_http::Method;
 use url::Url;
 
+use crate::cron::ScheduleTrigger;
+use crate::openapi::{OperationLookup, OperationTable};
+use crate::webhook::{check_placeholders, schedule_contract_violations, BodyTemplate};
+
 const MAX_MEMORY_LIMIT_MB: u64 = (u32::MAX as u64) / 16;
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+const DEFAULT_RETRY_BACKOFF_SECS: u64 = 5;
+const DEFAULT_RETRY_MAX_BACKOFF_SECS: u64 = 300;
+pub const DEFAULT_ALERT_AFTER: u32 = 3;
+const DEFAULT_MAX_RESTARTS: u32 = 5;
+const DEFAULT_RESTART_WINDOW_SECS: u64 = 300;
+const DEFAULT_RESTART_BACKOFF_SECS: u64 = 1;
+const DEFAULT_RESTART_MAX_BACKOFF_SECS: u64 = 60;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Service {
@@ -19,22 +32,299 @@ pub struct Service {
     pub prefix: String,
     pub base_url: String,
     pub runner_urls: Vec<String>,
-    pub allowed_get_endpoints: HashSet<String>,
+    pub auto_ports: bool,
+    pub operations: OperationTable,
     pub queue_listeners: Vec<ServiceQueueListener>,
+    pub publishes: Vec<String>,
+    pub depends_on: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub secrets: BTreeMap<String, String>,
     pub schedules: Vec<ServiceSchedule>,
     pub memory_limit_mb: Option<u64>,
     pub runner_instances: usize,
+    pub validate_requests: bool,
+    pub monitor_responses: bool,
+    pub restart: RestartPolicy,
+    pub runtime: RuntimeConfig,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ServiceSchedule {
+    // Empty for queue schedules.
     pub endpoint: String,
-    pub interval_secs: u64,
+    // Set when the schedule publishes to an internal queue instead of calling
+    // the module; `body` is then the message template.
+    pub queue: Option<String>,
+    pub trigger: ScheduleTrigger,
+    pub method: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<BodyTemplate>,
+    pub concurrency: ConcurrencyPolicy,
+    pub catch_up: CatchUpPolicy,
+    pub jitter_secs: u64,
+    pub target: ScheduleTarget,
+    pub retry: RetryPolicy,
+    // Failed runs in a row after which the schedule counts as failing; 0 never alerts.
+    pub alert_after: u32,
+    pub origin: ScheduleOrigin,
+}
+
+// Schedules created or changed through the runtime API are kept apart from
+// the ones read from service.json until they are written back.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScheduleOrigin {
+    #[default]
+    Config,
+    Added,
+    Edited,
+}
+
+// What happens to a fire, periodic or manual, while a run is still in flight.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConcurrencyPolicy {
+    #[default]
+    Skip,
+    Queue,
+    Allow,
+}
+
+// What happens to the fire times missed while paused or while the runner was down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    #[default]
+    None,
+    Once,
+    All,
+}
+
+// Which runner instances receive each scheduled call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScheduleTarget {
+    #[default]
+    RoundRobin,
+    AnyHealthy,
+    Broadcast,
+}
+
+// How a call that fails (no response or a 5xx) is retried before the run
+// counts as failed. The wait doubles after each attempt, up to the cap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub backoff_secs: u64,
+    pub max_backoff_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_secs: DEFAULT_RETRY_BACKOFF_SECS,
+            max_backoff_secs: DEFAULT_RETRY_MAX_BACKOFF_SECS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    // The wait before attempt `attempt + 1`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(32);
+        Duration::from_secs(
+            self.backoff_secs
+                .saturating_mul(factor)
+                .min(self.max_backoff_secs),
+        )
+    }
+}
+
+// When a module instance that exits on its own is started again.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RestartMode {
+    Always,
+    #[default]
+    OnFailure,
+    Never,
+}
+
+// Restarts wait `backoff_secs`, doubling for each restart still inside the
+// window; past `max_restarts` in the window the instance is left stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    pub max_restarts: u32,
+    pub window_secs: u64,
+    pub backoff_secs: u64,
+    pub max_backoff_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::default(),
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            window_secs: DEFAULT_RESTART_WINDOW_SECS,
+            backoff_secs: DEFAULT_RESTART_BACKOFF_SECS,
+            max_backoff_secs: DEFAULT_RESTART_MAX_BACKOFF_SECS,
+        }
+    }
+}
+
+impl RestartPolicy {
+    // The wait before the next restart, given how many happened in the window.
+    pub fn backoff(&self, recent_restarts: u32) -> Duration {
+        let factor = 1u64 << recent_restarts.min(32);
+        Duration::from_secs(
+            self.backoff_secs
+                .saturating_mul(factor)
+                .min(self.max_backoff_secs),
+        )
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+}
+
+impl RestartMode {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "always" => Some(RestartMode::Always),
+            "on-failure" => Some(RestartMode::OnFailure),
+            "never" => Some(RestartMode::Never),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestartMode::Always => "always",
+            RestartMode::OnFailure => "on-failure",
+            RestartMode::Never => "never",
+        }
+    }
+}
+
+// The program that runs the service's module (see `runtime.rs`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RuntimeConfig {
+    #[default]
+    WasmEdge,
+    // Any WASI CLI. `capability_args` let the guest serve HTTP and
+    // `memory_args` cap its memory, with `{bytes}` and `{pages}` filled in;
+    // both default to wasmtime's flags when the command is wasmtime. `args`
+    // go right before the module path.
+    Wasi {
+        command: String,
+        args: Vec<String>,
+        capability_args: Vec<String>,
+        memory_args: Vec<String>,
+    },
+    // A host binary. A `command` with a path separator is relative to the
+    // service directory; a bare name is looked up in `PATH`.
+    Native {
+        command: String,
+        args: Vec<String>,
+    },
+}
+
+impl RuntimeConfig {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuntimeConfig::WasmEdge => "wasmedge",
+            RuntimeConfig::Wasi { .. } => "wasi",
+            RuntimeConfig::Native { .. } => "native",
+        }
+    }
+
+    pub fn enforces_memory_limit(&self) -> bool {
+        match self {
+            RuntimeConfig::WasmEdge => true,
+            RuntimeConfig::Wasi { memory_args, .. } => !memory_args.is_empty(),
+            RuntimeConfig::Native { .. } => false,
+        }
+    }
+}
+
+impl ServiceSchedule {
+    // How logs and alerts name the schedule.
+    pub fn label(&self) -> String {
+        match &self.queue {
+            Some(queue) => format!("queue {queue}"),
+            None => format!("{} /{}", self.method, self.endpoint),
+        }
+    }
+}
+
+impl ScheduleOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleOrigin::Config => "config",
+            ScheduleOrigin::Added => "added",
+            ScheduleOrigin::Edited => "edited",
+        }
+    }
+}
+
+impl ConcurrencyPolicy {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "skip" => Some(ConcurrencyPolicy::Skip),
+            "queue" => Some(ConcurrencyPolicy::Queue),
+            "allow" => Some(ConcurrencyPolicy::Allow),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConcurrencyPolicy::Skip => "skip",
+            ConcurrencyPolicy::Queue => "queue",
+            ConcurrencyPolicy::Allow => "allow",
+        }
+    }
+}
+
+impl CatchUpPolicy {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "none" => Some(CatchUpPolicy::None),
+            "once" => Some(CatchUpPolicy::Once),
+            "all" => Some(CatchUpPolicy::All),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatchUpPolicy::None => "none",
+            CatchUpPolicy::Once => "once",
+            CatchUpPolicy::All => "all",
+        }
+    }
+}
+
+impl ScheduleTarget {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "round_robin" => Some(ScheduleTarget::RoundRobin),
+            "any_healthy" => Some(ScheduleTarget::AnyHealthy),
+            "broadcast" => Some(ScheduleTarget::Broadcast),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleTarget::RoundRobin => "round_robin",
+            ScheduleTarget::AnyHealthy => "any_healthy",
+            ScheduleTarget::Broadcast => "broadcast",
+        }
+    }
 }
 
 impl Service {
-    pub fn supports(&self, method: &Method, endpoint: &str) -> bool {
-        matches!(method, &Method::Get) && self.allowed_get_endpoints.contains(endpoint)
+    pub fn resolve_operation(&self, method: &Method, endpoint: &str) -> OperationLookup<'_> {
+        self.operations.resolve(method.as_str(), endpoint)
     }
 
     pub fn runner_count(&self) -> usize {
@@ -49,11 +339,49 @@ impl Service {
         }
     }
 
+    pub fn undeclared_schedules(&self) -> impl Iterator<Item = &ServiceSchedule> {
+        self.schedules.iter().filter(|schedule| {
+            schedule.queue.is_none()
+                && self
+                    .operations
+                    .find(&schedule.method, &schedule.endpoint)
+                    .is_none()
+        })
+    }
+
     pub fn memory_page_limit(&self) -> Option<u32> {
         self.memory_limit_mb
             .and_then(|limit| limit.checked_mul(16))
             .and_then(|pages| pages.try_into().ok())
     }
+
+    // Baseline for module tests, which override the fields they care about
+    // with struct-update syntax.
+    #[cfg(test)]
+    pub fn for_test(name: &str) -> Service {
+        Service {
+            name: name.into(),
+            domain: "demo".into(),
+            kind: ServiceKind::Business,
+            prefix: name.into(),
+            base_url: "http://localhost:1234".into(),
+            runner_urls: vec!["http://localhost:1234".into()],
+            auto_ports: false,
+            operations: Default::default(),
+            queue_listeners: Vec::new(),
+            publishes: Vec::new(),
+            depends_on: Vec::new(),
+            env: Default::default(),
+            secrets: Default::default(),
+            schedules: Vec::new(),
+            memory_limit_mb: None,
+            runner_instances: 1,
+            validate_requests: false,
+            monitor_responses: false,
+            restart: Default::default(),
+            runtime: Default::default(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -83,7 +411,8 @@ impl ServiceKind {
 #[derive(Deserialize)]
 struct RawServiceConfig {
     prefix: String,
-    url: String,
+    #[serde(default)]
+    url: Option<String>,
     domain: String,
     #[serde(rename = "type")]
     kind: ServiceKind,
@@ -94,17 +423,99 @@ struct RawServiceConfig {
     #[serde(default)]
     listeners: Vec<HashMap<String, String>>,
     #[serde(default)]
+    publishes: Vec<String>,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    secrets: BTreeMap<String, String>,
+    #[serde(default)]
     schedules: Vec<RawScheduleConfig>,
+    #[serde(default)]
+    validate_requests: bool,
+    #[serde(default)]
+    monitor_responses: bool,
+    #[serde(default)]
+    restart: Option<RawRestartConfig>,
+    #[serde(default)]
+    runtime: Option<RawRuntimeConfig>,
+}
+
+// `runtime` is either just its type or an object with the command to run.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRuntimeConfig {
+    Kind(String),
+    Detailed(RawRuntimeDetails),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRuntimeDetails {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    capability_args: Option<Vec<String>>,
+    #[serde(default)]
+    memory_args: Option<Vec<String>>,
+}
+
+// `restart` is either just the policy name or an object with its limits.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRestartConfig {
+    Mode(String),
+    Detailed(RawRestartDetails),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRestartDetails {
+    #[serde(default)]
+    policy: Option<String>,
+    #[serde(default)]
+    max_restarts: Option<u32>,
+    #[serde(default)]
+    window_secs: Option<u64>,
+    #[serde(default)]
+    backoff_secs: Option<u64>,
+    #[serde(default)]
+    max_backoff_secs: Option<u64>,
 }
 
 fn default_runner_instances() -> usize {
     1
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct RawScheduleConfig {
     endpoint: String,
-    interval_secs: u64,
+    queue: Option<String>,
+    interval_secs: Option<u64>,
+    cron: Option<String>,
+    timezone: Option<String>,
+    method: Option<String>,
+    headers: BTreeMap<String, String>,
+    body: Option<Value>,
+    concurrency: Option<String>,
+    catch_up: Option<String>,
+    jitter_secs: Option<u64>,
+    target: Option<String>,
+    retry: Option<RawRetryConfig>,
+    alert_after: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRetryConfig {
+    max_attempts: Option<u64>,
+    backoff_secs: Option<u64>,
+    max_backoff_secs: Option<u64>,
 }
 
 impl<'de> Deserialize<'de> for RawScheduleConfig {
@@ -124,9 +535,8 @@ impl<'de> Deserialize<'de> for RawScheduleConfig {
     }
 }
 
-pub fn load_services() -> Result<Vec<Service>> {
+pub fn load_services(services_dir: &Path) -> Result<Vec<Service>> {
     let mut services = Vec::new();
-    let services_dir = Path::new("services");
 
     if !services_dir.exists() {
         println!(
@@ -136,6 +546,157 @@ pub fn load_services() -> Result<Vec<Service>> {
         return Ok(services);
     }
 
+    for name in list_service_names(services_dir)? {
+        let config_path = config_path(services_dir, &name);
+
+        if !config_path.exists() {
+            println!(
+                "Skipping service '{}' because configuration was not found at {}",
+                name,
+                config_path.display()
+            );
+            continue;
+        }
+
+        let manifest_path = service_manifest_path(services_dir, &name);
+
+        if !manifest_path.exists() {
+            println!(
+                "Skipping service '{}' because manifest was not found at {}",
+                name,
+                manifest_path.display()
+            );
+            continue;
+        }
+
+        let service = load_service(services_dir, &name)?;
+
+        check_service_schedules(&service)?;
+
+        services.push(service);
+    }
+
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    dependency_order(&services)?;
+    Ok(services)
+}
+
+// Every schedule must target a declared operation and send a request that
+// matches its contract.
+pub fn check_service_schedules(service: &Service) -> Result<()> {
+    if let Some(schedule) = service.undeclared_schedules().next() {
+        bail!(
+            "schedule endpoint '{} /{}' for service '{}' is not declared in its OpenAPI document",
+            schedule.method,
+            schedule.endpoint,
+            service.name
+        );
+    }
+
+    for schedule in &service.schedules {
+        let violations = schedule_contract_violations(service, schedule);
+        if !violations.is_empty() {
+            let details: Vec<String> = violations
+                .iter()
+                .map(|violation| format!("{}: {}", violation.location, violation.message))
+                .collect();
+            bail!(
+                "schedule request '{} /{}' for service '{}' does not match its OpenAPI operation: {}",
+                schedule.method,
+                schedule.endpoint,
+                service.name,
+                details.join("; ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Kahn's algorithm over the `depends_on` edges; ties are broken by name so the
+// startup order is stable between runs.
+pub fn dependency_order(services: &[Service]) -> Result<Vec<&Service>> {
+    for service in services {
+        for dependency in &service.depends_on {
+            if !services
+                .iter()
+                .any(|candidate| &candidate.name == dependency)
+            {
+                bail!(
+                    "service '{}' depends on '{}', which is not a loaded service",
+                    service.name,
+                    dependency
+                );
+            }
+        }
+    }
+
+    if let Some(cycle) = dependency_cycle(services) {
+        bail!("dependency cycle between services: {}", cycle.join(" -> "));
+    }
+
+    let mut ordered: Vec<&Service> = Vec::with_capacity(services.len());
+    let mut remaining: Vec<&Service> = services.iter().collect();
+    remaining.sort_by(|a, b| a.name.cmp(&b.name));
+
+    while !remaining.is_empty() {
+        let Some(position) = remaining.iter().position(|service| {
+            service
+                .depends_on
+                .iter()
+                .all(|dependency| ordered.iter().any(|started| &started.name == dependency))
+        }) else {
+            bail!("dependency cycle between services");
+        };
+        ordered.push(remaining.remove(position));
+    }
+
+    Ok(ordered)
+}
+
+// Dependencies on services that are not part of the set are ignored here; the
+// returned path starts and ends on the same service.
+pub fn dependency_cycle(services: &[Service]) -> Option<Vec<String>> {
+    fn visit<'a>(
+        name: &'a str,
+        services: &'a [Service],
+        path: &mut Vec<&'a str>,
+        done: &mut Vec<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|entry| *entry == name) {
+            let mut cycle: Vec<String> = path[start..]
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect();
+            cycle.push(name.to_string());
+            return Some(cycle);
+        }
+
+        if done.contains(&name) {
+            return None;
+        }
+
+        let service = services.iter().find(|service| service.name == name)?;
+        path.push(name);
+        for dependency in &service.depends_on {
+            if let Some(cycle) = visit(dependency, services, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.push(name);
+        None
+    }
+
+    let mut done = Vec::new();
+    services
+        .iter()
+        .find_map(|service| visit(&service.name, services, &mut Vec::new(), &mut done))
+}
+
+pub fn list_service_names(services_dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+
     for entry in fs::read_dir(services_dir).with_context(|| {
         format!(
             "failed to read services directory at {}",
@@ -157,88 +718,393 @@ pub fn load_services() -> Result<Vec<Service>> {
             continue;
         };
 
-        let name = name.to_owned();
-        let config_path = config_path(&name);
+        names.push(name.to_owned());
+    }
 
-        if !config_path.exists() {
-            println!(
-                "Skipping service '{}' because configuration was not found at {}",
-                name,
-                config_path.display()
-            );
-            continue;
+    names.sort();
+    Ok(names)
+}
+
+pub fn load_service(services_dir: &Path, name: &str) -> Result<Service> {
+    let RawServiceConfig {
+        prefix,
+        url,
+        domain,
+        kind,
+        runners,
+        memory_limit_mb,
+        listeners,
+        publishes,
+        depends_on,
+        env,
+        secrets,
+        schedules: raw_schedules,
+        validate_requests,
+        monitor_responses,
+        restart,
+        runtime,
+    } = read_service_config(services_dir, name)?;
+
+    let operations = read_service_openapi(services_dir, name)?;
+    let queue_listeners = parse_queue_listeners(name, &listeners)
+        .with_context(|| format!("failed to parse queue listeners for service '{}'", name))?;
+    let publishes = normalize_published_queues(name, &publishes)?;
+    let depends_on = normalize_dependencies(name, &depends_on)?;
+    let env = resolve_service_env(name, &env, |variable| std::env::var(variable).ok())?;
+    let secrets = normalize_service_secrets(name, &env, &secrets)?;
+    let schedules = normalize_service_schedules(name, &raw_schedules)?;
+    let restart = normalize_restart_policy(name, restart.as_ref())?;
+    let runtime = normalize_runtime(name, runtime)?;
+
+    // Without a `url` the runner picks the ports once the whole catalog is
+    // known (see `ports::assign_service_ports`).
+    let (runner_urls, auto_ports) = match &url {
+        Some(url) => (build_runner_urls(name, url, runners)?, false),
+        None => (Vec::new(), true),
+    };
+    let base_url = runner_urls.first().cloned().unwrap_or_default();
+
+    Ok(Service {
+        name: name.to_string(),
+        domain,
+        kind,
+        prefix,
+        base_url,
+        runner_urls,
+        auto_ports,
+        operations,
+        queue_listeners,
+        publishes,
+        depends_on,
+        env,
+        secrets,
+        schedules,
+        memory_limit_mb,
+        runner_instances: runners,
+        validate_requests,
+        monitor_responses,
+        restart,
+        runtime,
+    })
+}
+
+fn normalize_restart_policy(
+    service_name: &str,
+    raw: Option<&RawRestartConfig>,
+) -> Result<RestartPolicy> {
+    let defaults = RestartPolicy::default();
+    let (mode, details) = match raw {
+        None => return Ok(defaults),
+        Some(RawRestartConfig::Mode(mode)) => (Some(mode.as_str()), None),
+        Some(RawRestartConfig::Detailed(details)) => (details.policy.as_deref(), Some(details)),
+    };
+    let mode = match mode.map(str::trim) {
+        Some(value) => RestartMode::parse(value).ok_or_else(|| {
+            anyhow!(
+                "service '{service_name}' has an unknown restart policy '{value}', expected always, on-failure or never"
+            )
+        })?,
+        None => defaults.mode,
+    };
+    let policy = RestartPolicy {
+        mode,
+        max_restarts: details
+            .and_then(|details| details.max_restarts)
+            .unwrap_or(defaults.max_restarts),
+        window_secs: details
+            .and_then(|details| details.window_secs)
+            .unwrap_or(defaults.window_secs),
+        backoff_secs: details
+            .and_then(|details| details.backoff_secs)
+            .unwrap_or(defaults.backoff_secs),
+        max_backoff_secs: details
+            .and_then(|details| details.max_backoff_secs)
+            .unwrap_or(defaults.max_backoff_secs),
+    };
+
+    if policy.window_secs == 0 {
+        bail!("service '{service_name}' must use a restart window_secs above zero");
+    }
+    if policy.backoff_secs == 0 || policy.max_backoff_secs < policy.backoff_secs {
+        bail!(
+            "service '{service_name}' needs a restart backoff_secs above zero and no larger than max_backoff_secs"
+        );
+    }
+
+    Ok(policy)
+}
+
+fn normalize_runtime(service_name: &str, raw: Option<RawRuntimeConfig>) -> Result<RuntimeConfig> {
+    let details = match raw {
+        None => return Ok(RuntimeConfig::default()),
+        Some(RawRuntimeConfig::Kind(kind)) => RawRuntimeDetails {
+            kind,
+            command: None,
+            args: Vec::new(),
+            capability_args: None,
+            memory_args: None,
+        },
+        Some(RawRuntimeConfig::Detailed(details)) => details,
+    };
+    let command = details.command.map(|command| command.trim().to_string());
+    if command.as_deref() == Some("") {
+        bail!("service '{service_name}' has an empty runtime command");
+    }
+    let kind = details.kind.trim();
+    let args = details.args;
+    let wasi_flags = details.capability_args.is_some() || details.memory_args.is_some();
+    if wasi_flags && !matches!(kind, "wasi" | "wasmtime") {
+        bail!("service '{service_name}' can only set capability_args or memory_args for a wasi runtime");
+    }
+
+    match kind {
+        "wasmedge" if command.is_none() && args.is_empty() => Ok(RuntimeConfig::WasmEdge),
+        "wasmedge" => {
+            bail!("service '{service_name}' cannot set a command or args for the wasmedge runtime")
+        }
+        "wasi" | "wasmtime" => {
+            let command = command.unwrap_or_else(|| "wasmtime".to_string());
+            let wasmtime = Path::new(&command)
+                .file_stem()
+                .is_some_and(|stem| stem == "wasmtime");
+            let preset = |flags: &[&str]| {
+                if wasmtime {
+                    flags.iter().map(|flag| flag.to_string()).collect()
+                } else {
+                    Vec::new()
+                }
+            };
+            Ok(RuntimeConfig::Wasi {
+                capability_args: details.capability_args.unwrap_or_else(|| {
+                    preset(&["-S", "inherit-network=y", "-S", "allow-ip-name-lookup=y"])
+                }),
+                memory_args: details
+                    .memory_args
+                    .unwrap_or_else(|| preset(&["-W", "max-memory-size={bytes}"])),
+                command,
+                args,
+            })
+        }
+        "native" => match command {
+            Some(command) => Ok(RuntimeConfig::Native { command, args }),
+            None => bail!("service '{service_name}' needs a command for the native runtime"),
+        },
+        other => bail!(
+            "service '{service_name}' has an unknown runtime '{other}', expected wasmedge, wasi, wasmtime or native"
+        ),
+    }
+}
+
+// Parses one entry in the same format `schedules` accepts in service.json.
+pub fn parse_service_schedule(service_name: &str, value: Value) -> Result<ServiceSchedule> {
+    let raw: RawScheduleConfig = serde_json::from_value(value)
+        .map_err(|error| anyhow!("invalid schedule for service '{service_name}': {error}"))?;
+    let mut schedules = normalize_service_schedules(service_name, &[raw])?;
+    Ok(schedules.remove(0))
+}
+
+// The inverse of `parse_service_schedule`; defaults are left out so the
+// written config stays as short as a hand-written one.
+pub fn schedule_to_value(schedule: &ServiceSchedule) -> serde_json::Map<String, Value> {
+    let mut entry = serde_json::Map::new();
+    match &schedule.queue {
+        Some(queue) => {
+            entry.insert("queue".into(), queue.clone().into());
+        }
+        None => {
+            entry.insert("endpoint".into(), format!("/{}", schedule.endpoint).into());
+            if schedule.method != "GET" {
+                entry.insert("method".into(), schedule.method.clone().into());
+            }
+        }
+    }
+    match &schedule.trigger {
+        ScheduleTrigger::Interval { secs } => {
+            entry.insert("interval_secs".into(), (*secs).into());
+        }
+        ScheduleTrigger::Cron {
+            expression,
+            timezone,
+        } => {
+            entry.insert("cron".into(), expression.clone().into());
+            entry.insert("timezone".into(), timezone.name().into());
+        }
+    }
+    if !schedule.headers.is_empty() {
+        entry.insert(
+            "headers".into(),
+            serde_json::to_value(&schedule.headers).unwrap_or_default(),
+        );
+    }
+    match &schedule.body {
+        Some(BodyTemplate::Json(value)) => {
+            entry.insert("body".into(), value.clone());
+        }
+        Some(BodyTemplate::Text(text)) => {
+            entry.insert("body".into(), text.clone().into());
+        }
+        None => {}
+    }
+    if schedule.concurrency != ConcurrencyPolicy::default() {
+        entry.insert("concurrency".into(), schedule.concurrency.as_str().into());
+    }
+    if schedule.catch_up != CatchUpPolicy::default() {
+        entry.insert("catch_up".into(), schedule.catch_up.as_str().into());
+    }
+    if schedule.jitter_secs > 0 {
+        entry.insert("jitter_secs".into(), schedule.jitter_secs.into());
+    }
+    if schedule.target != ScheduleTarget::default() {
+        entry.insert("target".into(), schedule.target.as_str().into());
+    }
+    if schedule.retry != RetryPolicy::default() {
+        entry.insert(
+            "retry".into(),
+            serde_json::json!({
+                "max_attempts": schedule.retry.max_attempts,
+                "backoff_secs": schedule.retry.backoff_secs,
+                "max_backoff_secs": schedule.retry.max_backoff_secs,
+            }),
+        );
+    }
+    if schedule.alert_after != DEFAULT_ALERT_AFTER {
+        entry.insert("alert_after".into(), schedule.alert_after.into());
+    }
+    entry
+}
+
+// Splices the new `schedules` value into the file text, so every other key
+// keeps its order, values and formatting.
+pub fn write_service_schedules(
+    services_dir: &Path,
+    name: &str,
+    schedules: &[ServiceSchedule],
+) -> Result<()> {
+    let path = config_path(services_dir, name);
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let config: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    let Some(object) = config.as_object() else {
+        bail!("{} does not contain a JSON object", path.display());
+    };
+    let schedules = Value::Array(
+        schedules
+            .iter()
+            .map(|schedule| Value::Object(schedule_to_value(schedule)))
+            .collect(),
+    );
+
+    let updated = splice_top_level_key(&contents, object.is_empty(), "schedules", &schedules);
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, updated)
+        .with_context(|| format!("failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, &path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+// `contents` must already be known to hold a valid JSON object.
+fn splice_top_level_key(contents: &str, empty: bool, key: &str, value: &Value) -> String {
+    let span = top_level_value_span(contents, key);
+    let anchor = span
+        .map(|(start, _)| start)
+        .or_else(|| first_key_start(contents));
+    let indent = anchor
+        .map(|anchor| line_indent(contents, anchor))
+        .unwrap_or("  ");
+    let rendered = serde_json::to_string_pretty(value)
+        .unwrap_or_default()
+        .replace('\n', &format!("\n{indent}"));
+
+    if let Some((start, end)) = span {
+        return format!("{}{rendered}{}", &contents[..start], &contents[end..]);
+    }
+
+    let close = contents.rfind('}').unwrap_or(contents.len());
+    let last = contents[..close].trim_end().len();
+    let separator = if empty { "" } else { "," };
+    format!(
+        "{}{separator}\n{indent}\"{key}\": {rendered}\n{}",
+        &contents[..last],
+        &contents[close..]
+    )
+}
+
+// Byte range of the value stored under `key` in the outermost object.
+fn top_level_value_span(contents: &str, key: &str) -> Option<(usize, usize)> {
+    let bytes = contents.as_bytes();
+    let mut depth = 0usize;
+    let mut last_string = None;
+    let mut value_start = None;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'"' => {
+                let start = index + 1;
+                index = start;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                if depth == 1 && value_start.is_none() {
+                    last_string = contents.get(start..index);
+                }
+            }
+            b'{' | b'[' => depth += 1,
+            b':' if depth == 1 && last_string == Some(key) => {
+                let rest = &contents[index + 1..];
+                value_start = Some(index + 1 + (rest.len() - rest.trim_start().len()));
+            }
+            b',' | b'}' if depth == 1 => {
+                if let Some(start) = value_start {
+                    return Some((start, contents[..index].trim_end().len()));
+                }
+                last_string = None;
+                if bytes[index] == b'}' {
+                    depth -= 1;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
         }
+        index += 1;
+    }
 
-        let manifest_path = service_manifest_path(&name);
+    None
+}
 
-        if !manifest_path.exists() {
-            println!(
-                "Skipping service '{}' because manifest was not found at {}",
-                name,
-                manifest_path.display()
-            );
-            continue;
-        }
+fn first_key_start(contents: &str) -> Option<usize> {
+    let open = contents.find('{')?;
+    contents[open + 1..]
+        .find('"')
+        .map(|offset| open + 1 + offset)
+}
 
-        let RawServiceConfig {
-            prefix,
-            url,
-            domain,
-            kind,
-            runners,
-            memory_limit_mb,
-            listeners,
-            schedules: raw_schedules,
-        } = read_service_config(&name)?;
-
-        let allowed_get_endpoints = read_service_openapi(&name)?;
-        let queue_listeners = parse_queue_listeners(&name, &listeners)
-            .with_context(|| format!("failed to parse queue listeners for service '{}'", name))?;
-        let schedules = normalize_service_schedules(&name, &allowed_get_endpoints, &raw_schedules)?;
-
-        let runner_urls = build_runner_urls(&name, &url, runners)?;
-        let base_url = runner_urls
-            .first()
-            .cloned()
-            .unwrap_or_else(|| url.trim_end_matches('/').to_string());
-        let runner_instances = runner_urls.len();
-
-        services.push(Service {
-            name,
-            domain,
-            kind,
-            prefix,
-            base_url,
-            runner_urls,
-            allowed_get_endpoints,
-            queue_listeners,
-            schedules,
-            memory_limit_mb,
-            runner_instances,
-        });
-    }
+fn line_indent(contents: &str, position: usize) -> &str {
+    let line_start = contents[..position]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line = &contents[line_start..position];
+    &line[..line.len() - line.trim_start().len()]
+}
 
-    services.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(services)
+pub fn config_path(services_dir: &Path, name: &str) -> PathBuf {
+    services_dir.join(name).join("config").join("service.json")
 }
 
-pub fn config_path(name: &str) -> PathBuf {
-    PathBuf::from("services")
-        .join(name)
-        .join("config")
-        .join("service.json")
+pub fn service_manifest_path(services_dir: &Path, name: &str) -> PathBuf {
+    services_dir.join(name).join("Cargo.toml")
 }
 
-pub fn service_manifest_path(name: &str) -> PathBuf {
-    PathBuf::from("services").join(name).join("Cargo.toml")
+pub fn openapi_path(services_dir: &Path, name: &str) -> PathBuf {
+    services_dir.join(name).join("openapi.json")
 }
 
-pub fn openapi_path(name: &str) -> PathBuf {
-    PathBuf::from("services").join(name).join("openapi.json")
+pub fn module_wasm_path(services_dir: &Path, name: &str) -> PathBuf {
+    services_dir.join(name).join(format!("{name}.wasm"))
 }
 
-fn read_service_config(name: &str) -> Result<RawServiceConfig> {
-    let path = config_path(name);
+fn read_service_config(services_dir: &Path, name: &str) -> Result<RawServiceConfig> {
+    let path = config_path(services_dir, name);
     let contents = fs::read_to_string(&path).with_context(|| {
         format!(
             "failed to read configuration for service '{}' at {}",
@@ -261,12 +1127,16 @@ fn read_service_config(name: &str) -> Result<RawServiceConfig> {
 }
 
 fn validate_service_config(name: &str, config: &RawServiceConfig) -> Result<()> {
-    if config.prefix.trim().is_empty() {
+    if config.prefix.trim().trim_matches('/').is_empty() {
         bail!("prefix for service '{}' cannot be empty", name);
     }
 
-    if config.url.trim().is_empty() {
-        bail!("url for service '{}' cannot be empty", name);
+    if config
+        .url
+        .as_deref()
+        .is_some_and(|url| url.trim().is_empty())
+    {
+        bail!("url for service '{name}' cannot be empty; omit it to let the runner pick the ports");
     }
 
     if config.domain.trim().is_empty() {
@@ -387,8 +1257,8 @@ fn parse_queue_listeners(
     Ok(listeners)
 }
 
-fn read_service_openapi(name: &str) -> Result<HashSet<String>> {
-    let path = openapi_path(name);
+fn read_service_openapi(services_dir: &Path, name: &str) -> Result<OperationTable> {
+    let path = openapi_path(services_dir, name);
     let contents = fs::read_to_string(&path).with_context(|| {
         format!(
             "failed to read OpenAPI specification for service '{}' at {}",
@@ -405,9 +1275,9 @@ fn read_service_openapi(name: &str) -> Result<HashSet<String>> {
         )
     })?;
 
-    collect_get_endpoints(&document).with_context(|| {
+    OperationTable::from_document(&document).with_context(|| {
         format!(
-            "OpenAPI specification for service '{}' missing 'paths' object",
+            "OpenAPI specification for service '{}' does not declare valid operations",
             name
         )
     })
@@ -419,7 +1289,7 @@ fn parse_schedule_from_array(items: Vec<Value>) -> Result<RawScheduleConfig, Str
     }
 
     let endpoint = items
-        .get(0)
+        .first()
         .and_then(Value::as_str)
         .ok_or_else(|| "schedule array first item must be a string endpoint".to_string())?
         .to_owned();
@@ -432,177 +1302,555 @@ fn parse_schedule_from_array(items: Vec<Value>) -> Result<RawScheduleConfig, Str
 
     Ok(RawScheduleConfig {
         endpoint,
-        interval_secs,
+        interval_secs: Some(interval_secs),
+        ..RawScheduleConfig::default()
     })
 }
 
+// Every key an object schedule entry understands, aliases included.
+pub const SCHEDULE_KEYS: [&str; 18] = [
+    "endpoint",
+    "path",
+    "interval",
+    "interval_secs",
+    "seconds",
+    "every_secs",
+    "cron",
+    "timezone",
+    "method",
+    "headers",
+    "body",
+    "concurrency",
+    "catch_up",
+    "jitter_secs",
+    "target",
+    "retry",
+    "alert_after",
+    "queue",
+];
+
 fn parse_schedule_from_object(
     mut map: serde_json::Map<String, Value>,
 ) -> Result<RawScheduleConfig, String> {
     if map.len() == 1 {
         if let Some((key, value)) = map.iter().next() {
-            let special_key = matches!(
-                key.as_str(),
-                "endpoint" | "path" | "interval" | "interval_secs" | "seconds" | "every_secs"
-            );
+            let special_key = SCHEDULE_KEYS.contains(&key.as_str());
 
             if !special_key {
                 let interval_secs = parse_interval_value(value)?;
                 return Ok(RawScheduleConfig {
                     endpoint: key.clone(),
-                    interval_secs,
+                    interval_secs: Some(interval_secs),
+                    ..RawScheduleConfig::default()
                 });
             }
         }
     }
 
-    let endpoint_value = map
-        .remove("endpoint")
-        .or_else(|| map.remove("path"))
-        .ok_or_else(|| "schedule object missing 'endpoint' field".to_string())?;
-
-    let endpoint = endpoint_value
-        .as_str()
-        .ok_or_else(|| "schedule 'endpoint' must be a string".to_string())?
-        .to_owned();
+    let queue = map
+        .remove("queue")
+        .map(|value| parse_text_value("queue", value))
+        .transpose()?;
+    let endpoint = match map.remove("endpoint").or_else(|| map.remove("path")) {
+        Some(value) => value
+            .as_str()
+            .ok_or_else(|| "schedule 'endpoint' must be a string".to_string())?
+            .to_owned(),
+        None if queue.is_some() => String::new(),
+        None => return Err("schedule object missing 'endpoint' or 'queue' field".to_string()),
+    };
 
     let interval_value = map
         .remove("interval_secs")
         .or_else(|| map.remove("seconds"))
         .or_else(|| map.remove("interval"))
-        .or_else(|| map.remove("every_secs"))
-        .ok_or_else(|| "schedule object missing interval field".to_string())?;
+        .or_else(|| map.remove("every_secs"));
+    let cron = map
+        .remove("cron")
+        .map(|value| parse_text_value("cron", value))
+        .transpose()?;
+    let timezone = map
+        .remove("timezone")
+        .map(|value| parse_text_value("timezone", value))
+        .transpose()?;
+
+    let interval_secs = match (interval_value, &cron) {
+        (Some(_), Some(_)) => {
+            return Err(
+                "schedule cannot declare both an interval and a cron expression".to_string(),
+            )
+        }
+        (None, None) => {
+            return Err("schedule object missing interval or cron field".to_string());
+        }
+        (Some(value), None) => Some(parse_interval_value(&value)?),
+        (None, Some(_)) => None,
+    };
 
-    let interval_secs = parse_interval_value(&interval_value)?;
+    if timezone.is_some() && cron.is_none() {
+        return Err("schedule 'timezone' only applies to cron expressions".to_string());
+    }
+
+    let method = map
+        .remove("method")
+        .map(|value| parse_text_value("method", value))
+        .transpose()?;
+    let headers = match map.remove("headers") {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|_| "schedule 'headers' must map header names to strings".to_string())?,
+        None => BTreeMap::new(),
+    };
+    let body = map.remove("body");
+    let concurrency = map
+        .remove("concurrency")
+        .map(|value| parse_text_value("concurrency", value))
+        .transpose()?;
+    let catch_up = map
+        .remove("catch_up")
+        .map(|value| parse_text_value("catch_up", value))
+        .transpose()?;
+    let jitter_secs = map
+        .remove("jitter_secs")
+        .map(|value| {
+            value
+                .as_u64()
+                .ok_or_else(|| "schedule 'jitter_secs' must be a non-negative integer".to_string())
+        })
+        .transpose()?;
+    let target = map
+        .remove("target")
+        .map(|value| parse_text_value("target", value))
+        .transpose()?;
+    let retry = map
+        .remove("retry")
+        .map(|value| {
+            serde_json::from_value(value).map_err(|error| {
+                format!("schedule 'retry' must be an object with max_attempts, backoff_secs and max_backoff_secs: {error}")
+            })
+        })
+        .transpose()?;
+    let alert_after = map
+        .remove("alert_after")
+        .map(|value| {
+            value
+                .as_u64()
+                .ok_or_else(|| "schedule 'alert_after' must be a non-negative integer".to_string())
+        })
+        .transpose()?;
 
     Ok(RawScheduleConfig {
         endpoint,
+        queue,
         interval_secs,
+        cron,
+        timezone,
+        method,
+        headers,
+        body,
+        concurrency,
+        catch_up,
+        jitter_secs,
+        target,
+        retry,
+        alert_after,
     })
 }
 
+fn parse_text_value(field: &str, value: Value) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(text),
+        _ => Err(format!("schedule '{field}' must be a string")),
+    }
+}
+
 fn parse_interval_value(value: &Value) -> Result<u64, String> {
     value
         .as_u64()
         .ok_or_else(|| "schedule interval must be a positive integer".to_string())
 }
 
-fn normalize_service_schedules(
+fn normalize_published_queues(service_name: &str, raw_queues: &[String]) -> Result<Vec<String>> {
+    let mut queues = Vec::new();
+
+    for queue in raw_queues {
+        let queue = queue.trim();
+        if queue.is_empty() {
+            bail!("service '{service_name}' declares an empty published queue name");
+        }
+
+        if !queues.iter().any(|existing| existing == queue) {
+            queues.push(queue.to_string());
+        }
+    }
+
+    Ok(queues)
+}
+
+fn normalize_dependencies(service_name: &str, raw_dependencies: &[String]) -> Result<Vec<String>> {
+    let mut dependencies = Vec::new();
+
+    for dependency in raw_dependencies {
+        let dependency = dependency.trim();
+        if dependency.is_empty() {
+            bail!("service '{service_name}' declares an empty dependency in 'depends_on'");
+        }
+
+        if dependency == service_name {
+            bail!("service '{service_name}' cannot depend on itself");
+        }
+
+        if !dependencies.iter().any(|existing| existing == dependency) {
+            dependencies.push(dependency.to_string());
+        }
+    }
+
+    Ok(dependencies)
+}
+
+const RESERVED_ENV_PREFIX: &str = "WR_RUNNER_";
+
+fn validate_env_name(service_name: &str, variable: &str) -> Result<()> {
+    let mut chars = variable.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        bail!(
+            "service '{service_name}' declares an invalid environment variable name '{variable}'"
+        );
+    }
+
+    if variable.starts_with(RESERVED_ENV_PREFIX) {
+        bail!(
+            "service '{service_name}' cannot override the reserved variable '{variable}' ({RESERVED_ENV_PREFIX}* is set by the runner)"
+        );
+    }
+
+    Ok(())
+}
+
+fn resolve_service_env<F>(
     service_name: &str,
-    allowed_endpoints: &HashSet<String>,
-    raw_schedules: &[RawScheduleConfig],
-) -> Result<Vec<ServiceSchedule>> {
-    let mut schedules = Vec::new();
+    raw_env: &BTreeMap<String, String>,
+    lookup: F,
+) -> Result<BTreeMap<String, String>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut env = BTreeMap::new();
+
+    for (variable, template) in raw_env {
+        validate_env_name(service_name, variable)?;
+        let value = interpolate_env(template, &lookup).with_context(|| {
+            format!(
+                "failed to resolve environment variable '{variable}' for service '{service_name}'"
+            )
+        })?;
+        env.insert(variable.clone(), value);
+    }
 
-    for (idx, raw) in raw_schedules.iter().enumerate() {
-        let endpoint = raw.endpoint.trim().trim_matches('/');
+    Ok(env)
+}
 
-        if endpoint.is_empty() {
-            bail!(
-                "schedule entry #{idx} for service '{service_name}' must declare a non-empty endpoint"
-            );
+// Expands `${VAR}` references from the runner's environment; `$$` yields a
+// literal dollar sign and any other `$` is copied unchanged.
+fn interpolate_env<F>(template: &str, lookup: &F) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(position) = rest.find('$') {
+        output.push_str(&rest[..position]);
+        let after = &rest[position + 1..];
+
+        if let Some(remaining) = after.strip_prefix('$') {
+            output.push('$');
+            rest = remaining;
+        } else if let Some(reference) = after.strip_prefix('{') {
+            let Some(end) = reference.find('}') else {
+                bail!("unclosed '${{' in '{template}'");
+            };
+            let variable = reference[..end].trim();
+            if variable.is_empty() {
+                bail!("empty variable reference in '{template}'");
+            }
+            let value = lookup(variable).ok_or_else(|| {
+                anyhow!("variable '{variable}' is not set in the runner environment")
+            })?;
+            output.push_str(&value);
+            rest = &reference[end + 1..];
+        } else {
+            output.push('$');
+            rest = after;
         }
+    }
 
-        if raw.interval_secs == 0 {
-            bail!(
-                "schedule entry '/{endpoint}' for service '{service_name}' must declare an interval greater than zero"
-            );
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn normalize_service_secrets(
+    service_name: &str,
+    env: &BTreeMap<String, String>,
+    raw_secrets: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>> {
+    let mut secrets = BTreeMap::new();
+
+    for (variable, key) in raw_secrets {
+        validate_env_name(service_name, variable)?;
+
+        if env.contains_key(variable) {
+            bail!("service '{service_name}' declares '{variable}' both in 'env' and in 'secrets'");
         }
 
-        if !allowed_endpoints.contains(endpoint) {
-            bail!(
-                "schedule endpoint '/{endpoint}' for service '{service_name}' is not declared in its OpenAPI document"
-            );
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("secret for '{variable}' in service '{service_name}' must reference a key");
         }
 
-        schedules.push(ServiceSchedule {
-            endpoint: endpoint.to_string(),
-            interval_secs: raw.interval_secs,
-        });
+        secrets.insert(variable.clone(), key.to_string());
     }
 
-    Ok(schedules)
+    Ok(secrets)
 }
 
-fn collect_get_endpoints(document: &Value) -> Result<HashSet<String>> {
-    let paths = document
-        .get("paths")
-        .and_then(|value| value.as_object())
-        .ok_or_else(|| anyhow!("document missing 'paths'"))?;
+fn is_header_name_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
 
-    let mut allowed = HashSet::new();
+fn normalize_service_schedules(
+    service_name: &str,
+    raw_schedules: &[RawScheduleConfig],
+) -> Result<Vec<ServiceSchedule>> {
+    let mut schedules = Vec::new();
 
-    for (path_key, methods_value) in paths {
-        let Some(methods) = methods_value.as_object() else {
-            continue;
+    for (idx, raw) in raw_schedules.iter().enumerate() {
+        let endpoint = raw.endpoint.trim().trim_matches('/');
+        let queue = raw.queue.as_deref().map(str::trim);
+        let label = match queue {
+            Some("") => bail!(
+                "schedule entry #{idx} for service '{service_name}' must declare a non-empty queue"
+            ),
+            Some(_) if !endpoint.is_empty() => bail!(
+                "schedule entry #{idx} for service '{service_name}' cannot declare both an endpoint and a queue"
+            ),
+            Some(queue) => format!("queue {queue}"),
+            None if endpoint.is_empty() => bail!(
+                "schedule entry #{idx} for service '{service_name}' must declare a non-empty endpoint"
+            ),
+            None => format!("/{endpoint}"),
         };
 
-        let allows_get = methods
-            .keys()
-            .any(|method| method.eq_ignore_ascii_case("get"));
+        let trigger = match (&raw.cron, raw.interval_secs) {
+            (Some(expression), _) => ScheduleTrigger::cron(expression, raw.timezone.as_deref())
+                .with_context(|| {
+                    format!(
+                        "schedule entry '{label}' for service '{service_name}' has an invalid cron trigger"
+                    )
+                })?,
+            (None, Some(secs)) if secs > 0 => ScheduleTrigger::Interval { secs },
+            (None, _) => bail!(
+                "schedule entry '{label}' for service '{service_name}' must declare an interval greater than zero"
+            ),
+        };
 
-        if !allows_get {
-            continue;
+        // A queue message never reaches a module, so only its Content-Type
+        // carries over from the HTTP options.
+        if queue.is_some() {
+            let http_only = [
+                ("method", raw.method.is_some()),
+                ("target", raw.target.is_some()),
+                (
+                    "headers",
+                    raw.headers
+                        .keys()
+                        .any(|name| !name.trim().eq_ignore_ascii_case("content-type")),
+                ),
+            ];
+            if let Some((option, _)) = http_only.iter().find(|(_, set)| *set) {
+                bail!(
+                    "schedule entry '{label}' for service '{service_name}' publishes to a queue and cannot set '{option}'"
+                );
+            }
+        }
+        let method = match queue {
+            Some(_) => "POST".to_string(),
+            None => raw
+                .method
+                .as_deref()
+                .unwrap_or("GET")
+                .trim()
+                .to_ascii_uppercase(),
+        };
+        if !matches!(method.as_str(), "GET" | "POST" | "PUT" | "PATCH" | "DELETE") {
+            bail!(
+                "schedule entry '{label}' for service '{service_name}' uses unsupported method '{method}'"
+            );
         }
 
-        let endpoint = path_key.trim_matches('/');
+        let mut headers = BTreeMap::new();
+        for (name, value) in &raw.headers {
+            let name = name.trim();
+            if name.is_empty() || !name.bytes().all(is_header_name_byte) {
+                bail!(
+                    "schedule entry '{label}' for service '{service_name}' declares an invalid header name '{name}'"
+                );
+            }
+            headers.insert(name.to_string(), value.clone());
+        }
 
-        if endpoint.is_empty() {
-            continue;
+        let body = raw.body.clone().map(BodyTemplate::from_value);
+        if body.is_some() && method == "GET" {
+            bail!(
+                "schedule entry '{label}' for service '{service_name}' cannot send a body with GET"
+            );
         }
+        check_placeholders(&headers, body.as_ref()).with_context(|| {
+            format!("schedule entry '{label}' for service '{service_name}' has an invalid template")
+        })?;
 
-        allowed.insert(endpoint.to_string());
-    }
+        let concurrency = match raw.concurrency.as_deref().map(str::trim) {
+            Some(value) => ConcurrencyPolicy::parse(value).ok_or_else(|| {
+                anyhow!(
+                    "schedule entry '{label}' for service '{service_name}' has an unknown concurrency '{value}', expected skip, queue or allow"
+                )
+            })?,
+            None => ConcurrencyPolicy::default(),
+        };
+        let catch_up = match raw.catch_up.as_deref().map(str::trim) {
+            Some(value) => CatchUpPolicy::parse(value).ok_or_else(|| {
+                anyhow!(
+                    "schedule entry '{label}' for service '{service_name}' has an unknown catch_up '{value}', expected none, once or all"
+                )
+            })?,
+            None => CatchUpPolicy::default(),
+        };
+        let target = match raw.target.as_deref().map(str::trim) {
+            Some(value) => ScheduleTarget::parse(value).ok_or_else(|| {
+                anyhow!(
+                    "schedule entry '{label}' for service '{service_name}' has an unknown target '{value}', expected round_robin, any_healthy or broadcast"
+                )
+            })?,
+            None => ScheduleTarget::default(),
+        };
+        let jitter_secs = raw.jitter_secs.unwrap_or(0);
+        if let ScheduleTrigger::Interval { secs } = trigger {
+            if jitter_secs >= secs {
+                bail!(
+                    "schedule entry '{label}' for service '{service_name}' must use a jitter_secs shorter than its interval"
+                );
+            }
+        }
+
+        let retry = match &raw.retry {
+            Some(raw_retry) => {
+                let defaults = RetryPolicy::default();
+                let retry = RetryPolicy {
+                    max_attempts: u32::try_from(raw_retry.max_attempts.unwrap_or(1))
+                        .unwrap_or(u32::MAX),
+                    backoff_secs: raw_retry.backoff_secs.unwrap_or(defaults.backoff_secs),
+                    max_backoff_secs: raw_retry
+                        .max_backoff_secs
+                        .unwrap_or(defaults.max_backoff_secs),
+                };
+                if retry.max_attempts == 0 || retry.max_attempts > MAX_RETRY_ATTEMPTS {
+                    bail!(
+                        "schedule entry '{label}' for service '{service_name}' must set retry max_attempts between 1 and {MAX_RETRY_ATTEMPTS}"
+                    );
+                }
+                if retry.backoff_secs == 0 || retry.max_backoff_secs < retry.backoff_secs {
+                    bail!(
+                        "schedule entry '{label}' for service '{service_name}' needs a retry backoff_secs above zero and no larger than max_backoff_secs"
+                    );
+                }
+                retry
+            }
+            None => RetryPolicy::default(),
+        };
+        let alert_after = match raw.alert_after {
+            Some(value) => u32::try_from(value).map_err(|_| {
+                anyhow!(
+                    "schedule entry '{label}' for service '{service_name}' has an alert_after that is too large"
+                )
+            })?,
+            None => DEFAULT_ALERT_AFTER,
+        };
 
-    if allowed.is_empty() {
-        bail!("document does not declare any GET endpoints");
+        schedules.push(ServiceSchedule {
+            endpoint: endpoint.to_string(),
+            queue: queue.map(str::to_string),
+            trigger,
+            method,
+            headers,
+            body,
+            concurrency,
+            catch_up,
+            jitter_secs,
+            target,
+            retry,
+            alert_after,
+            origin: ScheduleOrigin::Config,
+        });
     }
 
-    Ok(allowed)
+    Ok(schedules)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, HashMap};
     use tiny_http::Method;
 
+    fn operation_table(document: Value) -> OperationTable {
+        OperationTable::from_document(&document).expect("operation table")
+    }
+
     #[test]
-    fn supports_only_known_get_endpoints() {
+    fn resolves_declared_operations_by_method() {
         let service = Service {
-            name: "example".into(),
-            domain: "demo".into(),
-            kind: ServiceKind::Business,
             prefix: "foo".into(),
-            base_url: "http://localhost".into(),
-            runner_urls: vec!["http://localhost".into()],
-            allowed_get_endpoints: ["ping".into()].into_iter().collect(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
-            memory_limit_mb: None,
-            runner_instances: 1,
+            operations: operation_table(json!({
+                "paths": {
+                    "/ping": { "get": {} },
+                    "/accounts/{accountId}": { "get": {}, "delete": {} }
+                }
+            })),
+            ..Service::for_test("example")
         };
 
-        assert!(service.supports(&Method::Get, "ping"));
-        assert!(!service.supports(&Method::Post, "ping"));
-        assert!(!service.supports(&Method::Get, "pong"));
+        assert!(matches!(
+            service.resolve_operation(&Method::Get, "ping"),
+            OperationLookup::Found(_)
+        ));
+        assert!(matches!(
+            service.resolve_operation(&Method::Post, "ping"),
+            OperationLookup::MethodNotAllowed { .. }
+        ));
+        assert!(matches!(
+            service.resolve_operation(&Method::Get, "pong"),
+            OperationLookup::NotFound
+        ));
+
+        let OperationLookup::Found(matched) =
+            service.resolve_operation(&Method::Delete, "accounts/123")
+        else {
+            panic!("templated DELETE operation should resolve");
+        };
+        assert_eq!(matched.operation.template, "accounts/{accountId}");
+        assert_eq!(matched.params["accountId"], "123");
     }
 
     #[test]
     fn converts_memory_limit_to_pages() {
         let mut service = Service {
-            name: "example".into(),
-            domain: "demo".into(),
-            kind: ServiceKind::Business,
-            prefix: "foo".into(),
-            base_url: "http://localhost".into(),
-            runner_urls: vec!["http://localhost".into()],
-            allowed_get_endpoints: HashSet::new(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
             memory_limit_mb: Some(100),
-            runner_instances: 1,
+            ..Service::for_test("example")
         };
 
         assert_eq!(service.memory_page_limit(), Some(1600));
@@ -611,41 +1859,6 @@ mod tests {
         assert_eq!(service.memory_page_limit(), None);
     }
 
-    #[test]
-    fn collects_get_endpoints_ignoring_non_get_methods() {
-        let document = json!({
-            "paths": {
-                "/ping": {
-                    "get": {},
-                    "post": {}
-                },
-                "/health": {
-                    "post": {}
-                },
-                "/": {
-                    "get": {}
-                }
-            }
-        });
-
-        let endpoints = collect_get_endpoints(&document).expect("collect endpoints");
-        assert_eq!(endpoints, ["ping".into()].into_iter().collect());
-    }
-
-    #[test]
-    fn fails_when_no_get_endpoints_declared() {
-        let document = json!({
-            "paths": {
-                "/health": {
-                    "post": {}
-                }
-            }
-        });
-
-        let error = collect_get_endpoints(&document).unwrap_err();
-        assert!(error.to_string().contains("does not declare any GET"));
-    }
-
     #[test]
     fn parses_queue_listeners_enforcing_invariants() {
         let listeners = vec![HashMap::from([(
@@ -677,37 +1890,321 @@ mod tests {
         let raw: Vec<RawScheduleConfig> = serde_json::from_value(json!([
             ["/ping", 30],
             {"endpoint": "hello", "seconds": 45},
-            {"/health": 5}
+            {"/health": 5},
+            {"endpoint": "/nightly", "cron": "0 2 * * *", "timezone": "Europe/Madrid"}
         ]))
         .expect("parse schedules");
 
-        let allowed = ["ping", "hello", "health"]
-            .into_iter()
-            .map(String::from)
-            .collect();
-
-        let schedules =
-            normalize_service_schedules("svc", &allowed, &raw).expect("normalize schedules");
+        let schedules = normalize_service_schedules("svc", &raw).expect("normalize schedules");
 
-        assert_eq!(schedules.len(), 3);
+        let interval = |secs| ScheduleTrigger::Interval { secs };
+        assert_eq!(schedules.len(), 4);
         assert_eq!(schedules[0].endpoint, "ping");
-        assert_eq!(schedules[0].interval_secs, 30);
+        assert_eq!(schedules[0].trigger, interval(30));
         assert_eq!(schedules[1].endpoint, "hello");
-        assert_eq!(schedules[1].interval_secs, 45);
+        assert_eq!(schedules[1].trigger, interval(45));
         assert_eq!(schedules[2].endpoint, "health");
-        assert_eq!(schedules[2].interval_secs, 5);
+        assert_eq!(schedules[2].trigger, interval(5));
+        assert_eq!(schedules[3].endpoint, "nightly");
+        assert_eq!(
+            schedules[3].trigger,
+            ScheduleTrigger::Cron {
+                expression: "0 2 * * *".into(),
+                timezone: chrono_tz::Europe::Madrid,
+            }
+        );
+
+        for invalid in [
+            json!([{"endpoint": "a", "cron": "0 2 * * *", "interval_secs": 5}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "timezone": "UTC"}]),
+        ] {
+            assert!(serde_json::from_value::<Vec<RawScheduleConfig>>(invalid).is_err());
+        }
+        for invalid in [
+            json!([{"endpoint": "a", "cron": "0 2 * *"}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "body": {"x": 1}}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "method": "TRACE"}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "headers": {"X Bad": "1"}}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "concurrency": "parallel"}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "jitter_secs": 5}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "target": "random"}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "retry": {"max_attempts": 11}}]),
+            json!([{"endpoint": "a", "queue": "ticks", "interval_secs": 5}]),
+            json!([{"queue": "ticks", "interval_secs": 5, "method": "POST"}]),
+            json!([{"endpoint": "a", "interval_secs": 5, "retry": {"max_attempts": 2, "backoff_secs": 0}}]),
+        ] {
+            let raw: Vec<RawScheduleConfig> = serde_json::from_value(invalid).expect("parse");
+            assert!(normalize_service_schedules("svc", &raw).is_err());
+        }
+
+        let raw: Vec<RawScheduleConfig> = serde_json::from_value(json!([{
+            "endpoint": "sync", "method": "post", "interval_secs": 60,
+            "headers": {"X-Cursor": "{{previous_run}}"}, "body": "since={{previous_run}}",
+            "concurrency": "queue", "catch_up": "all", "jitter_secs": 15,
+            "target": "broadcast", "retry": {"max_attempts": 4, "backoff_secs": 10},
+            "alert_after": 0
+        }]))
+        .expect("parse schedules");
+        let schedules = normalize_service_schedules("svc", &raw).expect("normalize schedules");
+        assert_eq!(schedules[0].method, "POST");
+        assert_eq!(schedules[0].concurrency, ConcurrencyPolicy::Queue);
+        assert_eq!(schedules[0].catch_up, CatchUpPolicy::All);
+        assert_eq!(schedules[0].jitter_secs, 15);
+        assert_eq!(schedules[0].target, ScheduleTarget::Broadcast);
+        assert_eq!(schedules[0].retry.max_attempts, 4);
+        assert_eq!(schedules[0].retry.backoff(3), Duration::from_secs(40));
+        assert_eq!(schedules[0].alert_after, 0);
+        assert_eq!(
+            parse_service_schedule("svc", Value::Object(schedule_to_value(&schedules[0])))
+                .expect("round trip"),
+            schedules[0]
+        );
+        assert_eq!(
+            schedules[0].body,
+            Some(BodyTemplate::Text("since={{previous_run}}".into()))
+        );
+        assert_eq!(schedules.len(), 1);
+
+        let tick = parse_service_schedule(
+            "svc",
+            json!({"queue": "orders.tick", "interval_secs": 60, "body": {"source": "{{service}}"}}),
+        )
+        .expect("queue schedule");
+        assert_eq!(tick.queue.as_deref(), Some("orders.tick"));
+        assert_eq!(tick.label(), "queue orders.tick");
+        assert_eq!(
+            parse_service_schedule("svc", Value::Object(schedule_to_value(&tick)))
+                .expect("round trip"),
+            tick
+        );
     }
 
     #[test]
-    fn normalize_schedules_rejects_unknown_endpoint() {
+    fn reports_schedules_without_a_declared_get_operation() {
         let raw: Vec<RawScheduleConfig> =
-            serde_json::from_value(json!([["/unknown", 10]])).expect("parse schedules");
+            serde_json::from_value(json!([["/ping", 5], ["/unknown", 10]]))
+                .expect("parse schedules");
+
+        let service = Service {
+            kind: ServiceKind::Adapter,
+            operations: operation_table(json!({
+                "paths": {
+                    "/ping": { "get": {} },
+                    "/unknown": { "post": {} }
+                }
+            })),
+            schedules: normalize_service_schedules("svc", &raw).expect("normalize schedules"),
+            ..Service::for_test("svc")
+        };
+
+        let undeclared: Vec<_> = service
+            .undeclared_schedules()
+            .map(|schedule| schedule.endpoint.as_str())
+            .collect();
+        assert_eq!(undeclared, vec!["unknown"]);
+    }
+
+    #[test]
+    fn interpolates_service_env_from_the_runner_environment() {
+        let raw: BTreeMap<String, String> = serde_json::from_value(json!({
+            "SAP_BASE_URL": "https://${SAP_HOST}:${SAP_PORT}/api",
+            "PRICE": "$$10 or $5"
+        }))
+        .expect("parse env");
+        let lookup = |variable: &str| match variable {
+            "SAP_HOST" => Some("sap.local".to_string()),
+            "SAP_PORT" => Some("8443".to_string()),
+            _ => None,
+        };
+
+        let env = resolve_service_env("svc", &raw, lookup).expect("resolve env");
+        assert_eq!(env["SAP_BASE_URL"], "https://sap.local:8443/api");
+        assert_eq!(env["PRICE"], "$10 or $5");
+
+        let missing = BTreeMap::from([("TOKEN".to_string(), "${UNSET}".to_string())]);
+        let error = resolve_service_env("svc", &missing, lookup).unwrap_err();
+        assert!(format!("{error:#}").contains("'UNSET' is not set"));
+    }
+
+    #[test]
+    fn rejects_reserved_and_conflicting_variables() {
+        let reserved = BTreeMap::from([("WR_RUNNER_PORT".to_string(), "1".to_string())]);
+        assert!(resolve_service_env("svc", &reserved, |_| None).is_err());
+
+        let invalid = BTreeMap::from([("1TOKEN".to_string(), "x".to_string())]);
+        assert!(resolve_service_env("svc", &invalid, |_| None).is_err());
+
+        let env = BTreeMap::from([("TOKEN".to_string(), "x".to_string())]);
+        let secrets = BTreeMap::from([("TOKEN".to_string(), "sap.token".to_string())]);
+        let error = normalize_service_secrets("svc", &env, &secrets).unwrap_err();
+        assert!(error.to_string().contains("both in 'env' and in 'secrets'"));
+    }
+
+    #[test]
+    fn orders_services_by_dependencies_and_rejects_cycles() {
+        let service = |name: &str, depends_on: &[&str]| Service {
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
+            ..Service::for_test(name)
+        };
+
+        let services = vec![
+            service("bff", &["business"]),
+            service("business", &[]),
+            service("sap", &["business"]),
+        ];
+        let order: Vec<&str> = dependency_order(&services)
+            .expect("acyclic")
+            .iter()
+            .map(|service| service.name.as_str())
+            .collect();
+        assert_eq!(order, vec!["business", "bff", "sap"]);
+
+        let cyclic = vec![
+            service("a", &["b"]),
+            service("b", &["c"]),
+            service("c", &["a"]),
+        ];
+        let error = dependency_order(&cyclic).unwrap_err();
+        assert!(error.to_string().contains("a -> b -> c -> a"));
+
+        let unknown = vec![service("a", &["ghost"])];
+        assert!(dependency_order(&unknown).is_err());
+        assert!(normalize_dependencies("a", &["a".to_string()]).is_err());
+    }
+
+    #[test]
+    fn parses_runtimes_from_a_type_or_an_object() {
+        let parse = |value: Value| {
+            let raw: RawRuntimeConfig = serde_json::from_value(value).expect("runtime entry");
+            normalize_runtime("svc", Some(raw))
+        };
+
+        assert_eq!(
+            normalize_runtime("svc", None).unwrap(),
+            RuntimeConfig::WasmEdge
+        );
+        assert_eq!(
+            parse(json!("wasmtime")).unwrap(),
+            RuntimeConfig::Wasi {
+                command: "wasmtime".into(),
+                args: Vec::new(),
+                capability_args: ["-S", "inherit-network=y", "-S", "allow-ip-name-lookup=y"]
+                    .map(String::from)
+                    .to_vec(),
+                memory_args: vec!["-W".into(), "max-memory-size={bytes}".into()],
+            }
+        );
+        let other = parse(json!({
+            "type": "wasi",
+            "command": "wasmer",
+            "capability_args": ["--net"]
+        }))
+        .unwrap();
+        assert_eq!(
+            other,
+            RuntimeConfig::Wasi {
+                command: "wasmer".into(),
+                args: Vec::new(),
+                capability_args: vec!["--net".into()],
+                memory_args: Vec::new(),
+            }
+        );
+        assert!(!other.enforces_memory_limit());
+        assert!(parse(json!({ "type": "native", "command": "x", "memory_args": [] })).is_err());
+        assert_eq!(
+            parse(json!({ "type": "native", "command": "bin/server", "args": ["--quiet"] }))
+                .unwrap(),
+            RuntimeConfig::Native {
+                command: "bin/server".into(),
+                args: vec!["--quiet".into()]
+            }
+        );
+        assert!(parse(json!("native")).is_err());
+        assert!(parse(json!({ "type": "wasmedge", "args": ["--x"] })).is_err());
+        assert!(parse(json!("docker")).is_err());
+    }
+
+    #[test]
+    fn parses_restart_policies_from_a_name_or_an_object() {
+        let parse = |value: Value| {
+            let raw: RawRestartConfig = serde_json::from_value(value).expect("restart entry");
+            normalize_restart_policy("svc", Some(&raw))
+        };
+
+        assert_eq!(
+            normalize_restart_policy("svc", None).unwrap(),
+            RestartPolicy::default()
+        );
+        assert_eq!(parse(json!("always")).unwrap().mode, RestartMode::Always);
+        let policy = parse(json!({ "policy": "never", "max_restarts": 2, "backoff_secs": 4 }))
+            .expect("detailed policy");
+        assert_eq!(policy.mode, RestartMode::Never);
+        assert_eq!(policy.max_restarts, 2);
+        assert_eq!(policy.backoff(0), Duration::from_secs(4));
+        assert_eq!(policy.backoff(5), Duration::from_secs(60));
+
+        assert!(parse(json!("sometimes")).is_err());
+        assert!(parse(json!({ "window_secs": 0 })).is_err());
+        assert!(parse(json!({ "backoff_secs": 90 })).is_err());
+        assert!(serde_json::from_value::<RawRestartConfig>(json!({ "retries": 1 })).is_err());
+    }
+
+    #[test]
+    fn writes_schedules_without_touching_the_other_keys() {
+        let dir = std::env::temp_dir().join(format!(
+            "rustrunner-config-schedules-{}",
+            std::process::id()
+        ));
+        let path = config_path(&dir, "billing");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let schedules = vec![parse_service_schedule(
+            "billing",
+            json!({"endpoint": "/sync", "interval_secs": 30}),
+        )
+        .expect("schedule")];
+        let written = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            write_service_schedules(&dir, "billing", &schedules).expect("write");
+            fs::read_to_string(&path).unwrap()
+        };
 
-        let allowed = ["ping"].into_iter().map(String::from).collect();
+        let replaced = written(
+            r#"{
+    "url": "http://localhost:15001",
+    "schedules": [["/sync", 60]],
+    "env": { "Z": "1", "A": "2" }
+}
+"#,
+        );
+        let appended = written("{\n  \"url\": \"http://localhost:15001\"\n}\n");
+        let _ = fs::remove_dir_all(&dir);
 
-        let error = normalize_service_schedules("svc", &allowed, &raw).unwrap_err();
-        assert!(error
-            .to_string()
-            .contains("is not declared in its OpenAPI document"));
+        assert_eq!(
+            replaced,
+            r#"{
+    "url": "http://localhost:15001",
+    "schedules": [
+      {
+        "endpoint": "/sync",
+        "interval_secs": 30
+      }
+    ],
+    "env": { "Z": "1", "A": "2" }
+}
+"#
+        );
+        assert_eq!(
+            appended,
+            r#"{
+  "url": "http://localhost:15001",
+  "schedules": [
+    {
+      "endpoint": "/sync",
+      "interval_secs": 30
+    }
+  ]
+}
+"#
+        );
     }
 }
@@ -0,0 +1,197 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::schema::SchemaViolation;
+
+const MAX_RECENT_VIOLATIONS: usize = 20;
+
+pub type SharedContractLog = Arc<Mutex<ContractLog>>;
+
+#[derive(Debug, Default)]
+pub struct ContractLog {
+    data: HashMap<String, HashMap<String, EndpointViolations>>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointViolations {
+    total: u64,
+    last_seen: Option<SystemTime>,
+    recent: VecDeque<ViolationRecord>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ViolationRecord {
+    pub timestamp: u64,
+    pub status: u16,
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ContractSnapshot {
+    pub generated_at: u64,
+    pub services: Vec<ServiceContractSnapshot>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ServiceContractSnapshot {
+    pub service: String,
+    pub endpoints: Vec<EndpointContractSnapshot>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct EndpointContractSnapshot {
+    pub endpoint: String,
+    pub total: u64,
+    pub last_seen: Option<u64>,
+    pub recent: Vec<ViolationRecord>,
+}
+
+impl ContractLog {
+    pub fn record(
+        &mut self,
+        service: &str,
+        endpoint: &str,
+        status: u16,
+        violations: &[SchemaViolation],
+        timestamp: SystemTime,
+    ) {
+        if violations.is_empty() {
+            return;
+        }
+
+        let entry = self
+            .data
+            .entry(service.to_string())
+            .or_default()
+            .entry(endpoint.to_string())
+            .or_default();
+
+        let seconds = epoch_seconds(timestamp);
+        entry.total = entry.total.saturating_add(violations.len() as u64);
+        entry.last_seen = Some(timestamp);
+
+        for violation in violations {
+            entry.recent.push_back(ViolationRecord {
+                timestamp: seconds,
+                status,
+                location: violation.location.clone(),
+                message: violation.message.clone(),
+            });
+        }
+
+        while entry.recent.len() > MAX_RECENT_VIOLATIONS {
+            entry.recent.pop_front();
+        }
+    }
+
+    pub fn snapshot(&self, now: SystemTime) -> ContractSnapshot {
+        let mut services: Vec<_> = self
+            .data
+            .iter()
+            .map(|(service, endpoints)| {
+                let mut endpoints: Vec<_> = endpoints
+                    .iter()
+                    .map(|(endpoint, entry)| EndpointContractSnapshot {
+                        endpoint: endpoint.clone(),
+                        total: entry.total,
+                        last_seen: entry.last_seen.map(epoch_seconds),
+                        recent: entry.recent.iter().rev().cloned().collect(),
+                    })
+                    .collect();
+                endpoints.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+
+                ServiceContractSnapshot {
+                    service: service.clone(),
+                    endpoints,
+                }
+            })
+            .collect();
+
+        services.sort_by(|a, b| a.service.cmp(&b.service));
+
+        ContractSnapshot {
+            generated_at: epoch_seconds(now),
+            services,
+        }
+    }
+}
+
+fn epoch_seconds(timestamp: SystemTime) -> u64 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs()
+}
+
+pub fn initialize_contract_log() -> SharedContractLog {
+    Arc::new(Mutex::new(ContractLog::default()))
+}
+
+pub fn record_contract_violations(
+    contracts: &SharedContractLog,
+    service: &str,
+    endpoint: &str,
+    status: u16,
+    violations: &[SchemaViolation],
+) {
+    if let Ok(mut guard) = contracts.lock() {
+        guard.record(service, endpoint, status, violations, SystemTime::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn groups_violations_by_service_and_endpoint() {
+        let mut log = ContractLog::default();
+        let violation = SchemaViolation::new("status", "status 418 is not declared");
+
+        log.record("svc-b", "GET /ping", 418, &[violation], at(10));
+        log.record("svc-a", "GET /orders", 200, &[], at(11));
+        log.record(
+            "svc-a",
+            "GET /orders",
+            200,
+            &[
+                SchemaViolation::new("body.id", "expected integer, found string"),
+                SchemaViolation::new("body.total", "required property is missing"),
+            ],
+            at(12),
+        );
+
+        let snapshot = log.snapshot(at(20));
+        assert_eq!(snapshot.generated_at, 20);
+        assert_eq!(snapshot.services.len(), 2);
+        assert_eq!(snapshot.services[0].service, "svc-a");
+
+        let orders = &snapshot.services[0].endpoints[0];
+        assert_eq!(orders.total, 2);
+        assert_eq!(orders.last_seen, Some(12));
+        assert_eq!(orders.recent[0].location, "body.total");
+    }
+
+    #[test]
+    fn keeps_a_bounded_history() {
+        let mut log = ContractLog::default();
+        let violations = [SchemaViolation::new("status", "status 500 is not declared")];
+
+        for second in 0..(MAX_RECENT_VIOLATIONS as u64 + 5) {
+            log.record("svc", "GET /ping", 500, &violations, at(second));
+        }
+
+        let snapshot = log.snapshot(at(100));
+        let endpoint = &snapshot.services[0].endpoints[0];
+        assert_eq!(endpoint.total, MAX_RECENT_VIOLATIONS as u64 + 5);
+        assert_eq!(endpoint.recent.len(), MAX_RECENT_VIOLATIONS);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleTrigger {
+    Interval { secs: u64 },
+    Cron { expression: String, timezone: Tz },
+}
+
+impl ScheduleTrigger {
+    // Cron expressions use the classic five fields and are evaluated on the
+    // wall clock of `timezone` (UTC when omitted), so daylight saving changes
+    // do not shift the local fire time.
+    pub fn cron(expression: &str, timezone: Option<&str>) -> Result<Self> {
+        let expression = expression.split_whitespace().collect::<Vec<_>>().join(" ");
+        parse_cron(&expression)?;
+
+        let timezone = match timezone.map(str::trim) {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| anyhow!("unknown time zone '{name}'"))?,
+            None => Tz::UTC,
+        };
+
+        Ok(ScheduleTrigger::Cron {
+            expression,
+            timezone,
+        })
+    }
+
+    pub fn timezone(&self) -> Tz {
+        match self {
+            ScheduleTrigger::Interval { .. } => Tz::UTC,
+            ScheduleTrigger::Cron { timezone, .. } => *timezone,
+        }
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            ScheduleTrigger::Interval { secs } => {
+                let secs = i64::try_from((*secs).max(1)).ok()?;
+                after.checked_add_signed(Duration::seconds(secs))
+            }
+            ScheduleTrigger::Cron {
+                expression,
+                timezone,
+            } => parse_cron(expression)
+                .ok()?
+                .find_next_occurrence(&after.with_timezone(timezone), false)
+                .ok()
+                .map(|next| next.with_timezone(&Utc)),
+        }
+    }
+}
+
+fn parse_cron(expression: &str) -> Result<Cron> {
+    Cron::new(expression)
+        .parse()
+        .map_err(|error| anyhow!("invalid cron expression '{expression}': {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn computes_next_fire_time_on_the_local_wall_clock() {
+        let trigger = ScheduleTrigger::cron("0  2 * * *", Some("Europe/Madrid")).expect("cron");
+        assert_eq!(
+            trigger,
+            ScheduleTrigger::Cron {
+                expression: "0 2 * * *".into(),
+                timezone: chrono_tz::Europe::Madrid,
+            }
+        );
+
+        // 02:00 in Madrid is 00:00 UTC in summer and 01:00 UTC in winter.
+        let summer = Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            trigger.next_after(summer),
+            Some(Utc.with_ymd_and_hms(2026, 7, 2, 0, 0, 0).unwrap())
+        );
+        let winter = Utc.with_ymd_and_hms(2026, 12, 1, 0, 30, 0).unwrap();
+        assert_eq!(
+            trigger.next_after(winter),
+            Some(Utc.with_ymd_and_hms(2026, 12, 1, 1, 0, 0).unwrap())
+        );
+
+        let interval = ScheduleTrigger::Interval { secs: 30 };
+        assert_eq!(
+            interval.next_after(summer),
+            Some(Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 30).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions_and_time_zones() {
+        assert!(ScheduleTrigger::cron("0 2 * *", None).is_err());
+        assert!(ScheduleTrigger::cron("0 25 * * *", None).is_err());
+        let error = ScheduleTrigger::cron("0 2 * * *", Some("Europe/Atlantis")).unwrap_err();
+        assert!(error.to_string().contains("Europe/Atlantis"));
+    }
+}
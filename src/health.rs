@@ -1,12 +1,13 @@
 use std::collections::HashMap;
+use std::net::TcpStream;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::config::Service;
+use url::Url;
 
-pub const HEALTH_POLL_INTERVAL_SECS: u64 = 5;
-pub const HEALTH_REQUEST_TIMEOUT_SECS: u64 = 2;
+use crate::catalog::{current_catalog, SharedServiceCatalog};
+use crate::config::Service;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum HealthStatus {
@@ -16,118 +17,172 @@ pub enum HealthStatus {
     Unhealthy,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ServiceHealth {
+    // Healthy only while every runner instance is.
     pub status: HealthStatus,
     pub last_checked: Option<Instant>,
+    // One entry per runner endpoint, in `runner_endpoints()` order.
+    pub instances: Vec<HealthStatus>,
 }
 
-pub type SharedHealthMap = Arc<Mutex<HashMap<String, ServiceHealth>>>;
-
-pub fn start_health_monitor(services: &[Service]) -> SharedHealthMap {
-    let health_map: SharedHealthMap = Arc::new(Mutex::new(HashMap::new()));
-
-    if let Ok(mut guard) = health_map.lock() {
-        for service in services {
-            guard.insert(service.name.clone(), ServiceHealth::default());
-        }
+impl ServiceHealth {
+    pub fn instance(&self, index: usize) -> HealthStatus {
+        self.instances.get(index).copied().unwrap_or_default()
     }
 
-    if services.is_empty() {
-        return health_map;
+    pub fn healthy_instances(&self) -> usize {
+        self.instances
+            .iter()
+            .filter(|status| **status == HealthStatus::Healthy)
+            .count()
     }
+}
+
+pub type SharedHealthMap = Arc<Mutex<HashMap<String, ServiceHealth>>>;
 
-    let services_for_monitor = services.to_vec();
+pub fn start_health_monitor(
+    catalog: &SharedServiceCatalog,
+    poll_interval: Duration,
+    request_timeout: Duration,
+) -> SharedHealthMap {
+    let health_map: SharedHealthMap = Arc::new(Mutex::new(HashMap::new()));
+    sync_health_entries(&health_map, current_catalog(catalog).services(), &[]);
+
+    let catalog = Arc::clone(catalog);
     let health_clone = Arc::clone(&health_map);
 
     thread::spawn(move || loop {
-        for service in &services_for_monitor {
+        let snapshot = current_catalog(&catalog);
+        for service in snapshot.services() {
             let now = Instant::now();
-            let status = perform_health_check(service);
+            let instances: Vec<HealthStatus> = service
+                .runner_endpoints()
+                .iter()
+                .map(|endpoint| perform_health_check(&service.name, endpoint, request_timeout))
+                .collect();
 
             if let Ok(mut map) = health_clone.lock() {
-                let entry = map.entry(service.name.clone()).or_default();
-                entry.status = status;
-                entry.last_checked = Some(now);
+                if let Some(entry) = map.get_mut(&service.name) {
+                    entry.status = overall_status(&instances);
+                    entry.last_checked = Some(now);
+                    entry.instances = instances;
+                }
             }
         }
 
-        thread::sleep(Duration::from_secs(HEALTH_POLL_INTERVAL_SECS));
+        thread::sleep(poll_interval);
     });
 
     health_map
 }
 
-fn perform_health_check(service: &Service) -> HealthStatus {
-    let url = healthcheck_url(service);
-    match ureq::get(&url)
-        .timeout(Duration::from_secs(HEALTH_REQUEST_TIMEOUT_SECS))
-        .call()
-    {
+pub fn sync_health_entries(health: &SharedHealthMap, services: &[Service], restarted: &[&str]) {
+    if let Ok(mut map) = health.lock() {
+        map.retain(|name, _| services.iter().any(|service| &service.name == name));
+        for service in services {
+            let entry = map.entry(service.name.clone()).or_default();
+            if restarted.contains(&service.name.as_str()) {
+                *entry = ServiceHealth::default();
+            }
+        }
+    }
+}
+
+fn perform_health_check(
+    service_name: &str,
+    base_url: &str,
+    request_timeout: Duration,
+) -> HealthStatus {
+    let url = health_url(base_url);
+    match ureq::get(&url).timeout(request_timeout).call() {
         Ok(response) if response.status() == 200 => HealthStatus::Healthy,
         Ok(response) => {
             eprintln!(
-                "Servicio '{}' respondió {} en su healthcheck",
-                service.name,
-                response.status()
+                "Servicio '{}' respondió {} en su healthcheck ({})",
+                service_name,
+                response.status(),
+                url
             );
             HealthStatus::Unhealthy
         }
         Err(error) => {
             eprintln!(
                 "No se pudo contactar al servicio '{}' en {}: {}",
-                service.name, url, error
+                service_name, url, error
             );
             HealthStatus::Unhealthy
         }
     }
 }
 
-fn healthcheck_url(service: &Service) -> String {
-    format!("{}/health", service.base_url.trim_end_matches('/'))
+fn overall_status(instances: &[HealthStatus]) -> HealthStatus {
+    if instances.is_empty() {
+        HealthStatus::Unknown
+    } else if instances
+        .iter()
+        .all(|status| *status == HealthStatus::Healthy)
+    {
+        HealthStatus::Healthy
+    } else {
+        HealthStatus::Unhealthy
+    }
+}
+
+// Used while an instance is starting, so failures are expected and not
+// logged. The TCP connect fails fast while the port is still closed.
+pub fn instance_ready(endpoint: &str, timeout: Duration) -> bool {
+    let address = Url::parse(endpoint)
+        .ok()
+        .and_then(|url| url.socket_addrs(|| None).ok())
+        .and_then(|addresses| addresses.into_iter().next());
+    let Some(address) = address else {
+        return false;
+    };
+    if TcpStream::connect_timeout(&address, timeout).is_err() {
+        return false;
+    }
+    matches!(
+        ureq::get(&health_url(endpoint)).timeout(timeout).call(),
+        Ok(response) if response.status() == 200
+    )
+}
+
+fn health_url(base_url: &str) -> String {
+    format!("{}/health", base_url.trim_end_matches('/'))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ServiceKind;
 
     #[test]
     fn healthcheck_url_trims_trailing_slashes() {
         let service = Service {
-            name: "svc".into(),
-            domain: "demo".into(),
-            kind: ServiceKind::Business,
-            prefix: "svc".into(),
             base_url: "http://localhost:1234/".into(),
-            runner_urls: vec!["http://localhost:1234".into()],
-            allowed_get_endpoints: Default::default(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
-            memory_limit_mb: None,
-            runner_instances: 1,
+            ..Service::for_test("svc")
         };
 
-        assert_eq!(healthcheck_url(&service), "http://localhost:1234/health");
+        assert_eq!(
+            health_url(&service.base_url),
+            "http://localhost:1234/health"
+        );
+        assert_eq!(
+            overall_status(&[HealthStatus::Healthy, HealthStatus::Unhealthy]),
+            HealthStatus::Unhealthy
+        );
+        assert_eq!(overall_status(&[]), HealthStatus::Unknown);
     }
 
     #[test]
     fn start_health_monitor_initializes_map() {
-        let service = Service {
-            name: "svc".into(),
-            domain: "demo".into(),
-            kind: ServiceKind::Business,
-            prefix: "svc".into(),
-            base_url: "http://localhost:1234".into(),
-            runner_urls: vec!["http://localhost:1234".into()],
-            allowed_get_endpoints: Default::default(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
-            memory_limit_mb: None,
-            runner_instances: 1,
-        };
+        let service = Service::for_test("svc");
 
-        let health = start_health_monitor(&[service.clone()]);
+        let catalog = crate::catalog::initialize_service_catalog(
+            crate::catalog::ServiceCatalog::build(vec![service.clone()]).expect("catalog"),
+        );
+        let health =
+            start_health_monitor(&catalog, Duration::from_secs(60), Duration::from_secs(1));
         let map = health.lock().expect("health map");
         assert!(map.contains_key(&service.name));
         assert_eq!(map[&service.name].status, HealthStatus::Unknown);
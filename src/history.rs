@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ServiceSchedule;
+
+pub const MAX_RESPONSE_BODY_BYTES: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    // The fire time the run stands for; `None` for runs launched by hand.
+    #[serde(default)]
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub response_body: Option<String>,
+    // Index of the runner instance that handled the call, in `runner_endpoints()`
+    // order; `None` when no instance could be picked.
+    #[serde(default)]
+    pub instance: Option<usize>,
+    // 1 for the first call, then one more for each retry of the same fire.
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedSchedule {
+    pub method: String,
+    pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<String>,
+    pub paused: bool,
+    // Runs in a row that failed after their last retry.
+    #[serde(default)]
+    pub failures: u32,
+    // Newest first.
+    pub runs: Vec<ScheduleRun>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScheduleHistoryStore {
+    dir: PathBuf,
+    limit: usize,
+}
+
+impl ScheduleRun {
+    // What `{{previous_run}}` renders for the run that follows this one.
+    pub fn fired_at(&self) -> DateTime<Utc> {
+        self.scheduled_for.unwrap_or(self.started_at)
+    }
+
+    // Worth a retry: the call never got an answer or the service failed.
+    pub fn failed(&self) -> bool {
+        self.error.is_some() || self.status.is_none_or(|status| status >= 500)
+    }
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+impl ScheduleHistoryStore {
+    pub fn new(state_dir: &Path, limit: usize) -> Self {
+        Self {
+            dir: state_dir.join("schedules"),
+            limit: limit.max(1),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    fn service_path(&self, service_name: &str) -> PathBuf {
+        self.dir.join(format!("{service_name}.json"))
+    }
+
+    // A missing or unreadable file only costs the history, so it never stops
+    // the schedules from running.
+    pub fn load(&self, service_name: &str) -> Vec<PersistedSchedule> {
+        let path = self.service_path(service_name);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Vec::new(),
+        };
+
+        match serde_json::from_str(&contents) {
+            Ok(schedules) => schedules,
+            Err(error) => {
+                eprintln!(
+                    "Ignoring schedule state for service '{service_name}' at {}: {error}",
+                    path.display()
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn save(&self, service_name: &str, schedules: &[PersistedSchedule]) -> Result<()> {
+        fs::create_dir_all(&self.dir).with_context(|| {
+            format!(
+                "failed to create schedule state directory {}",
+                self.dir.display()
+            )
+        })?;
+
+        let path = self.service_path(service_name);
+        let temporary = path.with_extension("json.tmp");
+        let contents = serde_json::to_string_pretty(schedules)?;
+        fs::write(&temporary, contents)
+            .with_context(|| format!("failed to write {}", temporary.display()))?;
+        fs::rename(&temporary, &path)
+            .with_context(|| format!("failed to replace {}", path.display()))
+    }
+}
+
+// Entries are matched by method and endpoint (or queue), so reordering the
+// schedules in the config keeps each one's history. Repeated endpoints match
+// in order.
+pub fn restore_schedules(
+    persisted: &[PersistedSchedule],
+    schedules: &[ServiceSchedule],
+) -> Vec<Option<PersistedSchedule>> {
+    schedules
+        .iter()
+        .enumerate()
+        .map(|(index, schedule)| {
+            let same = |method: &str, endpoint: &str, queue: &Option<String>| {
+                method == schedule.method
+                    && endpoint == schedule.endpoint
+                    && *queue == schedule.queue
+            };
+            let occurrence = schedules[..index]
+                .iter()
+                .filter(|earlier| same(&earlier.method, &earlier.endpoint, &earlier.queue))
+                .count();
+            persisted
+                .iter()
+                .filter(|entry| same(&entry.method, &entry.endpoint, &entry.queue))
+                .nth(occurrence)
+                .cloned()
+        })
+        .collect()
+}
+
+pub fn truncate_body(body: &[u8]) -> String {
+    if body.len() <= MAX_RESPONSE_BODY_BYTES {
+        return String::from_utf8_lossy(body).into_owned();
+    }
+
+    let mut text = String::from_utf8_lossy(&body[..MAX_RESPONSE_BODY_BYTES]).into_owned();
+    text.push('…');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cron::ScheduleTrigger;
+    use chrono::TimeZone;
+
+    fn schedule(method: &str, endpoint: &str) -> ServiceSchedule {
+        ServiceSchedule {
+            endpoint: endpoint.into(),
+            queue: None,
+            trigger: ScheduleTrigger::Interval { secs: 10 },
+            method: method.into(),
+            headers: Default::default(),
+            body: None,
+            concurrency: Default::default(),
+            catch_up: Default::default(),
+            jitter_secs: 0,
+            target: Default::default(),
+            retry: Default::default(),
+            alert_after: 3,
+            origin: Default::default(),
+        }
+    }
+
+    fn persisted(method: &str, endpoint: &str, paused: bool) -> PersistedSchedule {
+        PersistedSchedule {
+            method: method.into(),
+            endpoint: endpoint.into(),
+            queue: None,
+            paused,
+            failures: 0,
+            runs: vec![ScheduleRun {
+                scheduled_for: None,
+                started_at: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+                duration_ms: 12,
+                status: Some(200),
+                error: None,
+                response_body: Some("ok".into()),
+                instance: Some(0),
+                attempt: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn saves_and_restores_state_by_method_and_endpoint() {
+        let dir = std::env::temp_dir().join(format!("rustrunner-history-{}", std::process::id()));
+        let store = ScheduleHistoryStore::new(&dir, 5);
+        let saved = vec![
+            persisted("GET", "ping", true),
+            persisted("POST", "sync", false),
+            persisted("GET", "ping", false),
+        ];
+        store.save("svc", &saved).expect("save state");
+        let loaded = store.load("svc");
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(loaded, saved);
+        assert!(store.load("other").is_empty());
+
+        let restored = restore_schedules(
+            &loaded,
+            &[
+                schedule("POST", "sync"),
+                schedule("GET", "ping"),
+                schedule("GET", "ping"),
+                schedule("GET", "ping"),
+            ],
+        );
+        let paused: Vec<Option<bool>> = restored
+            .iter()
+            .map(|entry| entry.as_ref().map(|entry| entry.paused))
+            .collect();
+        assert_eq!(paused, vec![Some(false), Some(true), Some(false), None]);
+
+        let long = vec![b'x'; MAX_RESPONSE_BODY_BYTES + 10];
+        assert_eq!(
+            truncate_body(&long).chars().count(),
+            MAX_RESPONSE_BODY_BYTES + 1
+        );
+    }
+}
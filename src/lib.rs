@@ -1,82 +1,203 @@
+mod alerts;
+mod catalog;
 mod config;
+mod contracts;
+mod cron;
 mod health;
+mod history;
 mod logs;
 mod memory;
+mod openapi;
+mod ports;
 mod process;
 mod queue;
+mod reload;
+mod routes;
+mod runtime;
+mod runtime_schedules;
 mod scheduler;
+mod schema;
+mod secrets;
 mod server;
+mod settings;
+mod shutdown;
+mod startup;
 mod stats;
 mod templates;
+mod validate;
+mod webhook;
 
+pub use catalog::{
+    current_catalog, initialize_service_catalog, ServiceCatalog, SharedServiceCatalog,
+};
 pub use config::{load_services, Service, ServiceKind};
+pub use contracts::{initialize_contract_log, SharedContractLog};
 pub use health::{HealthStatus, ServiceHealth, SharedHealthMap};
+pub use history::ScheduleHistoryStore;
 pub use logs::{initialize_log_store, SharedLogMap};
 pub use memory::{initialize_memory_store, SharedMemoryMap};
-pub use process::run_module;
+pub use process::{initialize_module_registry, run_module, SharedModuleRegistry};
 pub use queue::{initialize_queue_registry, SharedQueueRegistry};
-pub use scheduler::{start_webhook_schedulers, SharedScheduleMap};
-pub use server::run_server;
+pub use runtime_schedules::{initialize_schedule_overrides, SharedScheduleOverrides};
+pub use scheduler::{start_webhook_schedulers, SharedScheduler};
+pub use server::{run_server, RunnerState};
+pub use settings::{load_settings, RunnerSettings, SettingsOverrides};
+pub use startup::{initialize_startup_map, SharedStartupMap, StartupPhase};
 pub use stats::{initialize_stats_store, record_http_status, SharedStats};
+pub use validate::{validate_services, ValidationIssue, ValidationReport};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use std::env;
 use std::io::Cursor;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+use alerts::AlertSink;
 use health::start_health_monitor;
 use logs::spawn_log_forwarder;
-use process::start_service_modules;
+use ports::assign_service_ports;
+use reload::start_service_watcher;
+use secrets::load_secret_store;
+use shutdown::{initialize_shutdown, install_signal_handler};
+use startup::launch_services;
 
 enum Invocation {
     Runner,
     Module(String),
+    Validate,
+}
+
+struct CommandLine {
+    invocation: Invocation,
+    config_file: Option<PathBuf>,
+    overrides: SettingsOverrides,
 }
 
 pub fn run() -> Result<()> {
-    match detect_invocation()? {
-        Invocation::Runner => run_high_level_runner(),
-        Invocation::Module(module) => run_module(&module),
+    let CommandLine {
+        invocation,
+        config_file,
+        overrides,
+    } = parse_command_line(env::args().skip(1))?;
+    let settings = load_settings(config_file.as_deref(), &overrides)?;
+
+    match invocation {
+        Invocation::Runner => run_high_level_runner(settings),
+        Invocation::Module(module) => run_module(&settings, &module),
+        Invocation::Validate => {
+            if !validate::run_validation(&settings)? {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
     }
 }
 
-fn detect_invocation() -> Result<Invocation> {
-    let mut args = env::args().skip(1);
+fn parse_command_line<I>(args: I) -> Result<CommandLine>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut invocation = None;
+    let mut config_file = None;
+    let mut overrides = SettingsOverrides::default();
 
-    let Some(first) = args.next() else {
-        return Ok(Invocation::Runner);
-    };
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value_for = |flag: &str| match inline_value {
+            Some(value) => Ok(value.to_string()),
+            None => args
+                .next()
+                .ok_or_else(|| anyhow!("Missing value after '{flag}'")),
+        };
 
-    match first.as_str() {
-        "--runner" => Ok(Invocation::Runner),
-        "--module" => {
-            let Some(name) = args.next() else {
-                bail!("Missing module name after '--module'");
-            };
-            Ok(Invocation::Module(name))
-        }
-        other if other.starts_with('-') => {
-            bail!("Unknown argument '{other}'. Use '--module <name>' to run a WebAssembly module.");
+        match flag.as_str() {
+            "--runner" => invocation = Some(Invocation::Runner),
+            "--module" => {
+                let name = value_for("--module")
+                    .map_err(|_| anyhow!("Missing module name after '--module'"))?;
+                invocation = Some(Invocation::Module(name));
+            }
+            "validate" | "--validate" => invocation = Some(Invocation::Validate),
+            "--config" => config_file = Some(PathBuf::from(value_for("--config")?)),
+            flag if SettingsOverrides::is_setting_flag(flag) => {
+                let value = value_for(flag)?;
+                overrides.set_from_flag(flag, &value)?;
+            }
+            other if other.starts_with('-') => {
+                bail!("Unknown argument '{other}'. Use '--module <name>' to run a WebAssembly module.");
+            }
+            module if invocation.is_none() => {
+                invocation = Some(Invocation::Module(module.to_string()));
+            }
+            extra => bail!("Unexpected argument '{extra}'"),
         }
-        module => Ok(Invocation::Module(module.to_string())),
     }
+
+    Ok(CommandLine {
+        invocation: invocation.unwrap_or(Invocation::Runner),
+        config_file,
+        overrides,
+    })
 }
 
-fn run_high_level_runner() -> Result<()> {
-    let services = load_services()?;
+fn run_high_level_runner(settings: RunnerSettings) -> Result<()> {
+    let mut services = load_services(&settings.services_dir)?;
+    assign_service_ports(&mut services, &[], settings.entry_port)?;
+    let catalog = initialize_service_catalog(ServiceCatalog::build(services)?);
+    let snapshot = current_catalog(&catalog);
+    let services = snapshot.services();
 
-    let logs = initialize_log_store(&services);
-    seed_log_store(&services, &logs);
-    let memory = initialize_memory_store(&services);
-    let _service_modules = start_service_modules(&services, &logs, &memory)?;
-    let health = start_health_monitor(&services);
-    let schedules = start_webhook_schedulers(&services);
+    let logs = initialize_log_store(services, settings.max_stored_log_lines);
+    seed_log_store(services, &logs);
+    let memory = initialize_memory_store(services);
+    let secrets = load_secret_store(&settings.secrets_file)?;
+    // Resolve every secret up front so a missing one aborts before any module starts.
+    for service in services {
+        secrets.resolve(service)?;
+    }
+    let health = start_health_monitor(
+        &catalog,
+        Duration::from_secs(settings.health_poll_interval_secs),
+        Duration::from_secs(settings.health_request_timeout_secs),
+    );
+    let queues = initialize_queue_registry(services);
+    let alerts = AlertSink::new(
+        settings.alert_webhook_url.clone(),
+        settings.alert_queue.clone(),
+        &queues,
+        Duration::from_secs(settings.schedule_request_timeout_secs),
+    );
+    let modules = initialize_module_registry();
+    let schedules =
+        start_webhook_schedulers(services, &health, &modules, &queues, alerts, &settings);
     let stats = initialize_stats_store();
-    let queues = initialize_queue_registry(&services);
+    let contracts = initialize_contract_log();
 
-    run_server(
-        &services, &health, &logs, &schedules, &stats, &queues, &memory,
-    )
+    let state = RunnerState {
+        catalog,
+        health,
+        logs,
+        schedules,
+        schedule_overrides: initialize_schedule_overrides(),
+        stats,
+        queues,
+        memory,
+        contracts,
+        startup: initialize_startup_map(),
+        modules,
+        shutdown: initialize_shutdown(),
+        settings,
+    };
+
+    install_signal_handler(&state)?;
+    launch_services(&state, services, &Arc::new(secrets));
+    start_service_watcher(&state);
+    run_server(&state)
 }
 
 fn seed_log_store(services: &[Service], logs: &SharedLogMap) {
@@ -91,3 +212,49 @@ fn seed_log_store(services: &[Service], logs: &SharedLogMap) {
         spawn_log_forwarder(service.name.clone(), reader, "info", Arc::clone(logs));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CommandLine> {
+        parse_command_line(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_settings_flags_around_the_invocation() {
+        let command = parse(&[
+            "--config",
+            "staging.toml",
+            "--port=15000",
+            "--module",
+            "billing",
+            "--services-dir",
+            "catalog",
+        ])
+        .expect("parse");
+
+        assert!(matches!(command.invocation, Invocation::Module(ref name) if name == "billing"));
+        assert_eq!(command.config_file, Some(PathBuf::from("staging.toml")));
+        assert_eq!(command.overrides.entry_port, Some(15000));
+        assert_eq!(
+            command.overrides.services_dir,
+            Some(PathBuf::from("catalog"))
+        );
+
+        let command = parse(&["--max-log-lines", "50"]).expect("parse");
+        assert!(matches!(command.invocation, Invocation::Runner));
+        assert_eq!(command.overrides.max_stored_log_lines, Some(50));
+
+        let command = parse(&["validate", "--services-dir", "catalog"]).expect("parse");
+        assert!(matches!(command.invocation, Invocation::Validate));
+    }
+
+    #[test]
+    fn rejects_unknown_flags_and_missing_values() {
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["--port"]).is_err());
+        assert!(parse(&["--module"]).is_err());
+        assert!(parse(&["billing", "shipping"]).is_err());
+    }
+}
@@ -5,20 +5,35 @@ use std::thread;
 
 use crate::config::Service;
 
-pub const MAX_STORED_LOG_LINES: usize = 200;
+pub type SharedLogMap = Arc<Mutex<LogStore>>;
 
-pub type SharedLogMap = Arc<Mutex<HashMap<String, VecDeque<String>>>>;
+#[derive(Debug)]
+pub struct LogStore {
+    max_lines: usize,
+    lines: HashMap<String, VecDeque<String>>,
+}
 
-pub fn initialize_log_store(services: &[Service]) -> SharedLogMap {
-    let store: SharedLogMap = Arc::new(Mutex::new(HashMap::new()));
+impl LogStore {
+    pub fn lines(&self, service_name: &str) -> Option<&VecDeque<String>> {
+        self.lines.get(service_name)
+    }
 
-    if let Ok(mut guard) = store.lock() {
-        for service in services {
-            guard.insert(service.name.clone(), VecDeque::new());
+    fn push(&mut self, service_name: &str, line: String) {
+        let entry = self.lines.entry(service_name.to_string()).or_default();
+        entry.push_back(line);
+        while entry.len() > self.max_lines {
+            entry.pop_front();
         }
     }
+}
+
+pub fn initialize_log_store(services: &[Service], max_lines: usize) -> SharedLogMap {
+    let lines = services
+        .iter()
+        .map(|service| (service.name.clone(), VecDeque::new()))
+        .collect();
 
-    store
+    Arc::new(Mutex::new(LogStore { max_lines, lines }))
 }
 
 pub fn spawn_log_forwarder<R>(
@@ -54,11 +69,7 @@ pub fn record_log_line(service_name: &str, line: &str, stream_label: &str, logs:
     let formatted = format!("[svc:{}][{}] {}", service_name, level, message);
     println!("{}", formatted);
     if let Ok(mut guard) = logs.lock() {
-        let entry = guard.entry(service_name.to_string()).or_default();
-        entry.push_back(formatted);
-        while entry.len() > MAX_STORED_LOG_LINES {
-            entry.pop_front();
-        }
+        guard.push(service_name, formatted);
     }
 }
 
@@ -86,4 +97,19 @@ mod tests {
     fn returns_none_for_unexpected_format() {
         assert!(parse_service_log_line("INFO Something").is_none());
     }
+
+    #[test]
+    fn keeps_only_the_configured_number_of_lines() {
+        let logs = initialize_log_store(&[], 2);
+        for index in 0..3 {
+            record_log_line("svc", &format!("[INFO] line {index}"), "info", &logs);
+        }
+
+        let store = logs.lock().unwrap();
+        let lines: Vec<_> = store.lines("svc").unwrap().iter().cloned().collect();
+        assert_eq!(
+            lines,
+            vec!["[svc:svc][INFO] line 1", "[svc:svc][INFO] line 2"]
+        );
+    }
 }
@@ -34,6 +34,18 @@ pub fn initialize_memory_store(services: &[Service]) -> SharedMemoryMap {
     store
 }
 
+pub fn sync_memory_store(store: &SharedMemoryMap, services: &[Service]) {
+    if let Ok(mut guard) = store.lock() {
+        guard.retain(|name, _| services.iter().any(|service| &service.name == name));
+        for service in services {
+            let entry = guard.entry(service.name.clone()).or_default();
+            entry.limit_bytes = service
+                .memory_limit_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024));
+        }
+    }
+}
+
 pub fn record_memory_usage(store: &SharedMemoryMap, service_name: &str, usage_bytes: Option<u64>) {
     if let Ok(mut guard) = store.lock() {
         if let Some(entry) = guard.get_mut(service_name) {
@@ -0,0 +1,903 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+
+use crate::routes::{RoutePattern, RouteTable};
+use crate::schema::{self, SchemaViolation};
+
+const HTTP_METHODS: [&str; 8] = [
+    "GET", "PUT", "POST", "DELETE", "OPTIONS", "HEAD", "PATCH", "TRACE",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Operation {
+    pub method: String,
+    pub template: String,
+    pub operation_id: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub request_body: Option<RequestBody>,
+    pub responses: BTreeMap<String, ResponseSpec>,
+    // The whole OpenAPI document, where schema `$ref`s are looked up.
+    pub document: Arc<Value>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+impl ParameterLocation {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "path" => Some(ParameterLocation::Path),
+            "query" => Some(ParameterLocation::Query),
+            "header" => Some(ParameterLocation::Header),
+            "cookie" => Some(ParameterLocation::Cookie),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ParameterLocation::Path => "path",
+            ParameterLocation::Query => "query",
+            ParameterLocation::Header => "header",
+            ParameterLocation::Cookie => "cookie",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: String,
+    pub location: ParameterLocation,
+    pub required: bool,
+    pub schema: Option<Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestBody {
+    pub required: bool,
+    pub content: BTreeMap<String, Option<Value>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseSpec {
+    pub content: BTreeMap<String, Option<Value>>,
+}
+
+pub struct RequestParts<'a> {
+    pub path_params: &'a BTreeMap<String, String>,
+    pub query: Option<&'a str>,
+    pub headers: &'a [(String, String)],
+    pub content_type: Option<&'a str>,
+    pub body: &'a [u8],
+}
+
+impl Operation {
+    pub fn label(&self) -> String {
+        format!("{} /{}", self.method, self.template)
+    }
+
+    pub fn validate_response(
+        &self,
+        status: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Vec<SchemaViolation> {
+        if self.responses.is_empty() {
+            return Vec::new();
+        }
+
+        let exact = status.to_string();
+        let range = format!("{}XX", status / 100);
+        let spec = self
+            .responses
+            .get(&exact)
+            .or_else(|| {
+                self.responses
+                    .iter()
+                    .find(|(code, _)| code.eq_ignore_ascii_case(&range))
+                    .map(|(_, spec)| spec)
+            })
+            .or_else(|| self.responses.get("default"));
+
+        let Some(spec) = spec else {
+            return vec![SchemaViolation::new(
+                "status",
+                format!("status {status} is not declared in the operation responses"),
+            )];
+        };
+
+        if spec.content.is_empty() {
+            return Vec::new();
+        }
+
+        let Some(content_type) = content_type else {
+            return vec![SchemaViolation::new(
+                "content-type",
+                "response is missing a Content-Type header",
+            )];
+        };
+
+        let Some(media_schema) = find_media_type(&spec.content, content_type) else {
+            return vec![SchemaViolation::new(
+                "content-type",
+                format!("content type '{content_type}' is not declared for status {status}"),
+            )];
+        };
+
+        let (Some(media_schema), true) = (media_schema, is_json_media_type(content_type)) else {
+            return Vec::new();
+        };
+
+        match serde_json::from_slice::<Value>(body) {
+            Ok(payload) => schema::validate(media_schema, &self.document, &payload, "body"),
+            Err(error) => vec![SchemaViolation::new(
+                "body",
+                format!("response body is not valid JSON: {error}"),
+            )],
+        }
+    }
+
+    pub fn validate_request(&self, request: &RequestParts) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        let query_pairs: Vec<(String, String)> = request
+            .query
+            .map(|query| {
+                url::form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for parameter in &self.parameters {
+            let raw_values: Vec<&str> = match parameter.location {
+                ParameterLocation::Path => request
+                    .path_params
+                    .get(&parameter.name)
+                    .map(String::as_str)
+                    .into_iter()
+                    .collect(),
+                ParameterLocation::Query => query_pairs
+                    .iter()
+                    .filter(|(key, _)| key == &parameter.name)
+                    .map(|(_, value)| value.as_str())
+                    .collect(),
+                ParameterLocation::Header => request
+                    .headers
+                    .iter()
+                    .filter(|(key, _)| key.eq_ignore_ascii_case(&parameter.name))
+                    .map(|(_, value)| value.as_str())
+                    .collect(),
+                ParameterLocation::Cookie => continue,
+            };
+
+            let location = format!("{}.{}", parameter.location.label(), parameter.name);
+
+            if raw_values.is_empty() {
+                if parameter.required {
+                    violations.push(SchemaViolation::new(
+                        location,
+                        "required parameter is missing",
+                    ));
+                }
+                continue;
+            }
+
+            if let Some(parameter_schema) = &parameter.schema {
+                let value = coerce_parameter(&raw_values, parameter_schema, &self.document);
+                violations.extend(schema::validate(
+                    parameter_schema,
+                    &self.document,
+                    &value,
+                    &location,
+                ));
+            }
+        }
+
+        if let Some(body) = &self.request_body {
+            violations.extend(body.validate(&self.document, request.content_type, request.body));
+        }
+
+        violations
+    }
+}
+
+impl RequestBody {
+    fn validate(
+        &self,
+        document: &Value,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Vec<SchemaViolation> {
+        if body.is_empty() {
+            if self.required {
+                return vec![SchemaViolation::new("body", "request body is required")];
+            }
+            return Vec::new();
+        }
+
+        let Some(content_type) = content_type else {
+            return vec![SchemaViolation::new(
+                "body",
+                "request body is missing a Content-Type header",
+            )];
+        };
+
+        let Some(media_schema) = find_media_type(&self.content, content_type) else {
+            return vec![SchemaViolation::new(
+                "body",
+                format!("content type '{content_type}' is not accepted by this operation"),
+            )];
+        };
+
+        let (Some(media_schema), true) = (media_schema, is_json_media_type(content_type)) else {
+            return Vec::new();
+        };
+
+        match serde_json::from_slice::<Value>(body) {
+            Ok(payload) => schema::validate(media_schema, document, &payload, "body"),
+            Err(error) => vec![SchemaViolation::new(
+                "body",
+                format!("request body is not valid JSON: {error}"),
+            )],
+        }
+    }
+}
+
+pub fn find_media_type<'a, T>(
+    content: &'a BTreeMap<String, T>,
+    content_type: &str,
+) -> Option<&'a T> {
+    let actual = essence(content_type);
+    let (actual_type, _) = actual.split_once('/').unwrap_or((actual.as_str(), ""));
+
+    content
+        .iter()
+        .find(|(declared, _)| essence(declared) == actual)
+        .or_else(|| {
+            content.iter().find(|(declared, _)| {
+                essence(declared)
+                    .strip_suffix("/*")
+                    .is_some_and(|family| family == actual_type)
+            })
+        })
+        .or_else(|| {
+            content
+                .iter()
+                .find(|(declared, _)| essence(declared) == "*/*")
+        })
+        .map(|(_, value)| value)
+}
+
+pub fn is_json_media_type(content_type: &str) -> bool {
+    let essence = essence(content_type);
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn coerce_parameter(raw_values: &[&str], parameter_schema: &Value, document: &Value) -> Value {
+    let parameter_schema = schema::resolve(parameter_schema, document);
+    if schema::primary_type(parameter_schema) == Some("array") {
+        let item_schema = parameter_schema
+            .get("items")
+            .map(|items| schema::resolve(items, document))
+            .unwrap_or(&Value::Null);
+        let items: Vec<&str> = if raw_values.len() == 1 {
+            raw_values[0].split(',').collect()
+        } else {
+            raw_values.to_vec()
+        };
+        return Value::Array(
+            items
+                .into_iter()
+                .map(|item| coerce_scalar(item, item_schema))
+                .collect(),
+        );
+    }
+
+    coerce_scalar(raw_values[0], parameter_schema)
+}
+
+fn coerce_scalar(raw: &str, scalar_schema: &Value) -> Value {
+    let coerced = match schema::primary_type(scalar_schema) {
+        Some("integer") => raw.parse::<i64>().ok().map(Value::from),
+        Some("number") => raw
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        Some("boolean") => raw.parse::<bool>().ok().map(Value::Bool),
+        _ => None,
+    };
+
+    coerced.unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+// A parameter, request body or response may itself be a `$ref` into
+// `components`; those are followed while loading. Schemas keep their `$ref`s
+// and are resolved while validating, so recursive ones are fine.
+fn follow_reference<'a>(value: &'a Value, document: &'a Value) -> Result<&'a Value> {
+    let mut current = value;
+    let mut followed = Vec::new();
+    while let Some(reference) = current.get("$ref").and_then(Value::as_str) {
+        if followed.contains(&reference) {
+            bail!("reference '{reference}' refers back to itself");
+        }
+        current = find_reference(document, reference)?;
+        followed.push(reference);
+    }
+    Ok(current)
+}
+
+fn find_reference<'a>(document: &'a Value, reference: &str) -> Result<&'a Value> {
+    if !reference.starts_with('#') {
+        bail!("only local references are supported, found '{reference}'");
+    }
+    schema::lookup(document, reference)
+        .ok_or_else(|| anyhow!("reference '{reference}' does not exist"))
+}
+
+// Every `$ref` reachable from `value` must resolve, so a broken contract is
+// still reported when the service loads. Each target is visited once.
+fn check_references<'a>(
+    value: &'a Value,
+    document: &'a Value,
+    visited: &mut HashSet<&'a str>,
+) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+                let target = find_reference(document, reference)?;
+                if visited.insert(reference) {
+                    check_references(target, document, visited)?;
+                }
+            }
+            map.values()
+                .try_for_each(|entry| check_references(entry, document, visited))
+        }
+        Value::Array(items) => items
+            .iter()
+            .try_for_each(|item| check_references(item, document, visited)),
+        _ => Ok(()),
+    }
+}
+
+fn parse_parameters(raw: Option<&Value>, document: &Value) -> Result<Vec<Parameter>> {
+    let Some(raw) = raw else {
+        return Ok(Vec::new());
+    };
+
+    let Some(entries) = raw.as_array() else {
+        bail!("'parameters' must be an array");
+    };
+
+    let mut parameters = Vec::new();
+    for entry in entries {
+        let entry = follow_reference(entry, document)?;
+        let name = entry
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("parameter is missing its 'name'"))?;
+        let raw_location = entry.get("in").and_then(Value::as_str).unwrap_or_default();
+        let location = ParameterLocation::parse(raw_location).ok_or_else(|| {
+            anyhow!("parameter '{name}' declares an unknown location '{raw_location}'")
+        })?;
+        let required = location == ParameterLocation::Path
+            || entry
+                .get("required")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+
+        parameters.push(Parameter {
+            name: name.to_string(),
+            location,
+            required,
+            schema: entry.get("schema").cloned(),
+        });
+    }
+
+    Ok(parameters)
+}
+
+fn merge_parameters(shared: &[Parameter], own: Vec<Parameter>) -> Vec<Parameter> {
+    let mut merged: Vec<Parameter> = shared
+        .iter()
+        .filter(|candidate| {
+            !own.iter().any(|parameter| {
+                parameter.name == candidate.name && parameter.location == candidate.location
+            })
+        })
+        .cloned()
+        .collect();
+    merged.extend(own);
+    merged
+}
+
+fn parse_responses(
+    raw: Option<&Value>,
+    document: &Value,
+) -> Result<BTreeMap<String, ResponseSpec>> {
+    let Some(raw) = raw else {
+        return Ok(BTreeMap::new());
+    };
+
+    let Some(entries) = raw.as_object() else {
+        bail!("'responses' must be an object");
+    };
+
+    entries
+        .iter()
+        .map(|(code, definition)| {
+            let definition = follow_reference(definition, document)?;
+            Ok((
+                code.clone(),
+                ResponseSpec {
+                    content: parse_content(definition),
+                },
+            ))
+        })
+        .collect()
+}
+
+fn parse_content(definition: &Value) -> BTreeMap<String, Option<Value>> {
+    definition
+        .get("content")
+        .and_then(Value::as_object)
+        .map(|media_types| {
+            media_types
+                .iter()
+                .map(|(media_type, media)| (media_type.clone(), media.get("schema").cloned()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_request_body(raw: Option<&Value>, document: &Value) -> Result<Option<RequestBody>> {
+    let Some(raw) = raw else {
+        return Ok(None);
+    };
+
+    let resolved = follow_reference(raw, document)?;
+    let content = parse_content(resolved);
+
+    Ok(Some(RequestBody {
+        required: resolved
+            .get("required")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        content,
+    }))
+}
+
+#[derive(Clone, Debug)]
+pub struct ResolvedOperation<'a> {
+    pub operation: &'a Operation,
+    pub params: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum OperationLookup<'a> {
+    Found(ResolvedOperation<'a>),
+    MethodNotAllowed { allowed: Vec<&'a str> },
+    NotFound,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OperationTable {
+    routes: RouteTable,
+    operations: HashMap<String, BTreeMap<String, Operation>>,
+}
+
+impl OperationTable {
+    pub fn from_document(document: &Value) -> Result<Self> {
+        let paths = document
+            .get("paths")
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow!("document missing 'paths'"))?;
+
+        check_references(&document["paths"], document, &mut HashSet::new())?;
+
+        let mut table = OperationTable::default();
+        let shared = Arc::new(document.clone());
+
+        for (path_key, item) in paths {
+            let Some(item) = item.as_object() else {
+                continue;
+            };
+
+            if path_key.trim_matches('/').is_empty() {
+                continue;
+            }
+
+            let pattern = RoutePattern::parse(path_key)?;
+            let template = pattern.template().to_string();
+            let shared_parameters = parse_parameters(item.get("parameters"), document)
+                .with_context(|| format!("invalid parameters for path '{path_key}'"))?;
+            let mut methods = BTreeMap::new();
+
+            for (key, definition) in item {
+                let method = key.to_ascii_uppercase();
+                if !HTTP_METHODS.contains(&method.as_str()) {
+                    continue;
+                }
+
+                let operation_id = definition
+                    .get("operationId")
+                    .and_then(Value::as_str)
+                    .map(str::to_owned);
+                let own_parameters = parse_parameters(definition.get("parameters"), document)
+                    .with_context(|| format!("invalid parameters for {method} '{path_key}'"))?;
+                let request_body = parse_request_body(definition.get("requestBody"), document)
+                    .with_context(|| format!("invalid requestBody for {method} '{path_key}'"))?;
+                let responses = parse_responses(definition.get("responses"), document)
+                    .with_context(|| format!("invalid responses for {method} '{path_key}'"))?;
+
+                methods.insert(
+                    method.clone(),
+                    Operation {
+                        method,
+                        template: template.clone(),
+                        operation_id,
+                        parameters: merge_parameters(&shared_parameters, own_parameters),
+                        request_body,
+                        responses,
+                        document: Arc::clone(&shared),
+                    },
+                );
+            }
+
+            if methods.is_empty() {
+                continue;
+            }
+
+            table
+                .routes
+                .insert(pattern)
+                .with_context(|| format!("invalid path '{path_key}'"))?;
+            table.operations.insert(template, methods);
+        }
+
+        if table.operations.is_empty() {
+            bail!("document does not declare any operations");
+        }
+
+        Ok(table)
+    }
+
+    pub fn resolve(&self, method: &str, path: &str) -> OperationLookup<'_> {
+        let Some(matched) = self.routes.resolve(path) else {
+            return OperationLookup::NotFound;
+        };
+
+        let Some(methods) = self.operations.get(matched.template) else {
+            return OperationLookup::NotFound;
+        };
+
+        match methods.get(&method.to_ascii_uppercase()) {
+            Some(operation) => OperationLookup::Found(ResolvedOperation {
+                operation,
+                params: matched.params,
+            }),
+            None => OperationLookup::MethodNotAllowed {
+                allowed: methods.keys().map(String::as_str).collect(),
+            },
+        }
+    }
+
+    pub fn find(&self, method: &str, path: &str) -> Option<ResolvedOperation<'_>> {
+        match self.resolve(method, path) {
+            OperationLookup::Found(resolved) => Some(resolved),
+            _ => None,
+        }
+    }
+
+    pub fn operations(&self) -> impl Iterator<Item = &Operation> {
+        self.routes
+            .templates()
+            .filter_map(|template| self.operations.get(template))
+            .flat_map(|methods| methods.values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn collects_every_declared_method() {
+        let document = json!({
+            "paths": {
+                "/orders": {
+                    "get": { "operationId": "listOrders" },
+                    "post": {},
+                    "parameters": []
+                },
+                "/orders/{orderId}": {
+                    "put": {},
+                    "delete": {}
+                },
+                "/": {
+                    "get": {}
+                }
+            }
+        });
+
+        let table = OperationTable::from_document(&document).expect("operations");
+        let collected: Vec<_> = table
+            .operations()
+            .map(|operation| format!("{} /{}", operation.method, operation.template))
+            .collect();
+
+        assert_eq!(
+            collected,
+            vec![
+                "GET /orders",
+                "POST /orders",
+                "DELETE /orders/{orderId}",
+                "PUT /orders/{orderId}",
+            ]
+        );
+
+        let found = table.find("get", "orders").expect("list orders");
+        assert_eq!(found.operation.operation_id.as_deref(), Some("listOrders"));
+
+        let found = table.find("DELETE", "orders/9").expect("delete order");
+        assert_eq!(found.params["orderId"], "9");
+    }
+
+    #[test]
+    fn distinguishes_unknown_paths_from_unsupported_methods() {
+        let document = json!({
+            "paths": {
+                "/orders": { "get": {}, "post": {} }
+            }
+        });
+        let table = OperationTable::from_document(&document).expect("operations");
+
+        assert!(matches!(
+            table.resolve("GET", "customers"),
+            OperationLookup::NotFound
+        ));
+
+        match table.resolve("PATCH", "orders") {
+            OperationLookup::MethodNotAllowed { allowed } => {
+                assert_eq!(allowed, vec!["GET", "POST"]);
+            }
+            other => panic!("unexpected lookup result: {other:?}"),
+        }
+    }
+
+    fn validate(
+        operation: &Operation,
+        path: &str,
+        query: Option<&str>,
+        content_type: Option<&str>,
+        body: &str,
+    ) -> Vec<String> {
+        let table_match = BTreeMap::from([("orderId".to_string(), path.to_string())]);
+        operation
+            .validate_request(&RequestParts {
+                path_params: &table_match,
+                query,
+                headers: &[],
+                content_type,
+                body: body.as_bytes(),
+            })
+            .into_iter()
+            .map(|violation| format!("{}: {}", violation.location, violation.message))
+            .collect()
+    }
+
+    #[test]
+    fn validates_parameters_and_json_bodies() {
+        let document = json!({
+            "components": {
+                "schemas": {
+                    "Line": {
+                        "type": "object",
+                        "required": ["sku", "quantity"],
+                        "properties": {
+                            "sku": { "type": "string" },
+                            "quantity": { "type": "integer", "minimum": 1 }
+                        }
+                    }
+                }
+            },
+            "paths": {
+                "/orders/{orderId}/lines": {
+                    "parameters": [
+                        { "name": "orderId", "in": "path", "schema": { "type": "integer" } }
+                    ],
+                    "post": {
+                        "parameters": [
+                            { "name": "dryRun", "in": "query", "required": true, "schema": { "type": "boolean" } },
+                            { "name": "tags", "in": "query", "schema": { "type": "array", "items": { "type": "integer" } } }
+                        ],
+                        "requestBody": {
+                            "required": true,
+                            "content": {
+                                "application/json": { "schema": { "$ref": "#/components/schemas/Line" } }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        let table = OperationTable::from_document(&document).expect("operations");
+        let operation = table.find("POST", "orders/1/lines").unwrap().operation;
+        assert_eq!(operation.parameters.len(), 3);
+
+        let valid = validate(
+            operation,
+            "1",
+            Some("dryRun=true&tags=1,2"),
+            Some("application/json; charset=utf-8"),
+            r#"{"sku":"A-1","quantity":2}"#,
+        );
+        assert!(valid.is_empty(), "unexpected violations: {valid:?}");
+
+        let invalid = validate(
+            operation,
+            "abc",
+            Some("tags=1&tags=x"),
+            Some("application/json"),
+            r#"{"sku":"A-1","quantity":0}"#,
+        );
+        assert_eq!(
+            invalid,
+            vec![
+                "path.orderId: expected integer, found string",
+                "query.dryRun: required parameter is missing",
+                "query.tags[1]: expected integer, found string",
+                "body.quantity: must be at least 1",
+            ]
+        );
+
+        assert_eq!(
+            validate(operation, "1", Some("dryRun=false"), None, ""),
+            vec!["body: request body is required"]
+        );
+        assert_eq!(
+            validate(
+                operation,
+                "1",
+                Some("dryRun=false"),
+                Some("text/plain"),
+                "hi"
+            ),
+            vec!["body: content type 'text/plain' is not accepted by this operation"]
+        );
+    }
+
+    #[test]
+    fn validates_responses_against_declared_contract() {
+        let document = json!({
+            "paths": {
+                "/orders/{orderId}": {
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "object",
+                                            "required": ["id"],
+                                            "properties": { "id": { "type": "integer" } }
+                                        }
+                                    }
+                                }
+                            },
+                            "4XX": { "description": "client error" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let table = OperationTable::from_document(&document).expect("operations");
+        let operation = table.find("GET", "orders/1").unwrap().operation;
+        let check = |status: u16, content_type: Option<&str>, body: &str| -> Vec<String> {
+            operation
+                .validate_response(status, content_type, body.as_bytes())
+                .into_iter()
+                .map(|violation| format!("{}: {}", violation.location, violation.message))
+                .collect()
+        };
+
+        assert!(check(200, Some("application/json"), r#"{"id":1}"#).is_empty());
+        assert!(check(404, Some("text/plain"), "missing").is_empty());
+        assert_eq!(
+            check(500, None, ""),
+            vec!["status: status 500 is not declared in the operation responses"]
+        );
+        assert_eq!(
+            check(200, Some("text/html"), "<p>"),
+            vec!["content-type: content type 'text/html' is not declared for status 200"]
+        );
+        assert_eq!(
+            check(200, Some("application/json"), r#"{"id":"1"}"#),
+            vec!["body.id: expected integer, found string"]
+        );
+    }
+
+    #[test]
+    fn loads_and_validates_recursive_schemas() {
+        let document = json!({
+            "paths": {
+                "/nodes": {
+                    "post": {
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Node" }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Node": {
+                        "type": "object",
+                        "required": ["value"],
+                        "properties": {
+                            "value": { "type": "integer" },
+                            "left": { "$ref": "#/components/schemas/Node" },
+                            "right": { "$ref": "#/components/schemas/Node" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let table = OperationTable::from_document(&document).expect("recursive schema");
+        let operation = table.find("POST", "nodes").expect("post nodes").operation;
+        let check = |body: &str| validate(operation, "", None, Some("application/json"), body);
+
+        assert!(check(r#"{"value":1,"left":{"value":2,"right":{"value":3}}}"#).is_empty());
+        assert_eq!(
+            check(r#"{"value":1,"left":{"value":2,"right":{"value":"3"}}}"#),
+            vec!["body.left.right.value: expected integer, found string"]
+        );
+
+        let mut broken = document.clone();
+        broken["components"]["schemas"]["Node"]["properties"]["left"] =
+            json!({ "$ref": "#/components/schemas/Leaf" });
+        let error = OperationTable::from_document(&broken).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("reference '#/components/schemas/Leaf' does not exist"));
+    }
+
+    #[test]
+    fn fails_when_no_operations_declared() {
+        let document = json!({
+            "paths": {
+                "/health": { "summary": "no operations" }
+            }
+        });
+
+        let error = OperationTable::from_document(&document).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("does not declare any operations"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::net::TcpListener;
+
+use anyhow::{anyhow, bail, Result};
+use url::Url;
+
+use crate::config::Service;
+
+const AUTO_PORT_HOST: &str = "127.0.0.1";
+const AUTO_PORT_ATTEMPTS: usize = 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PortConflict {
+    EntryPort {
+        service: String,
+        port: u16,
+    },
+    Overlap {
+        service: String,
+        other: String,
+        ports: Vec<u16>,
+    },
+}
+
+impl fmt::Display for PortConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortConflict::EntryPort { service, port } => write!(
+                f,
+                "runner ports of service '{service}' include the runner entry port {port}"
+            ),
+            PortConflict::Overlap {
+                service,
+                other,
+                ports,
+            } => write!(
+                f,
+                "runner ports of services '{service}' and '{other}' overlap on {}",
+                join_ports(ports)
+            ),
+        }
+    }
+}
+
+pub fn join_ports(ports: &[u16]) -> String {
+    ports
+        .iter()
+        .map(|port| port.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn runner_ports(service: &Service) -> BTreeSet<u16> {
+    service
+        .runner_urls
+        .iter()
+        .filter_map(|url| url_port(url))
+        .collect()
+}
+
+fn url_port(url: &str) -> Option<u16> {
+    Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.port_or_known_default())
+}
+
+// Services whose ports have not been assigned yet have no runner URLs and are
+// therefore never part of a conflict.
+pub fn find_port_conflicts(services: &[Service], entry_port: u16) -> Vec<PortConflict> {
+    let ports: Vec<(&Service, BTreeSet<u16>)> = services
+        .iter()
+        .map(|service| (service, runner_ports(service)))
+        .collect();
+    let mut conflicts = Vec::new();
+
+    for (index, (service, own)) in ports.iter().enumerate() {
+        if own.contains(&entry_port) {
+            conflicts.push(PortConflict::EntryPort {
+                service: service.name.clone(),
+                port: entry_port,
+            });
+        }
+
+        for (other, theirs) in &ports[index + 1..] {
+            let shared: Vec<u16> = own.intersection(theirs).copied().collect();
+            if !shared.is_empty() {
+                conflicts.push(PortConflict::Overlap {
+                    service: service.name.clone(),
+                    other: other.name.clone(),
+                    ports: shared,
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+// Explicit ranges must not collide; services without a `url` get free local
+// ports, keeping the ones they already had in `previous` so a reload does not
+// restart them.
+pub fn assign_service_ports(
+    services: &mut [Service],
+    previous: &[Service],
+    entry_port: u16,
+) -> Result<()> {
+    let conflicts = find_port_conflicts(services, entry_port);
+    if !conflicts.is_empty() {
+        let messages: Vec<String> = conflicts.iter().map(ToString::to_string).collect();
+        bail!("{}", messages.join("; "));
+    }
+
+    let mut reserved: BTreeSet<u16> = services.iter().flat_map(runner_ports).collect();
+    reserved.insert(entry_port);
+
+    for service in services.iter_mut().filter(|service| service.auto_ports) {
+        let reused = previous
+            .iter()
+            .find(|existing| {
+                existing.name == service.name
+                    && existing.auto_ports
+                    && existing.runner_urls.len() == service.runner_instances
+            })
+            .map(|existing| existing.runner_urls.clone())
+            .filter(|urls| {
+                urls.iter()
+                    .all(|url| url_port(url).is_some_and(|port| !reserved.contains(&port)))
+            });
+
+        let urls = match reused {
+            Some(urls) => urls,
+            None => allocate_ports(service, &reserved)?,
+        };
+
+        reserved.extend(urls.iter().filter_map(|url| url_port(url)));
+        service.base_url = urls.first().cloned().unwrap_or_default();
+        service.runner_urls = urls;
+    }
+
+    Ok(())
+}
+
+fn allocate_ports(service: &Service, reserved: &BTreeSet<u16>) -> Result<Vec<String>> {
+    let mut picked: Vec<u16> = Vec::with_capacity(service.runner_instances);
+
+    while picked.len() < service.runner_instances {
+        let port = (0..AUTO_PORT_ATTEMPTS)
+            .filter_map(|_| TcpListener::bind((AUTO_PORT_HOST, 0)).ok())
+            .filter_map(|listener| listener.local_addr().ok())
+            .map(|address| address.port())
+            .find(|port| !reserved.contains(port) && !picked.contains(port))
+            .ok_or_else(|| {
+                anyhow!(
+                    "could not find a free local port for runner #{} of service '{}'",
+                    picked.len() + 1,
+                    service.name
+                )
+            })?;
+        picked.push(port);
+    }
+
+    Ok(picked
+        .into_iter()
+        .map(|port| format!("http://{AUTO_PORT_HOST}:{port}"))
+        .collect())
+}
+
+// Checked right before the modules are spawned, so a port taken by some other
+// process is reported instead of surfacing as a module that never gets healthy.
+pub fn ensure_ports_available(service: &Service) -> Result<()> {
+    for url in service.runner_endpoints() {
+        let Ok(parsed) = Url::parse(url) else {
+            continue;
+        };
+        let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+            continue;
+        };
+        if !matches!(host, "127.0.0.1" | "localhost" | "0.0.0.0" | "[::1]") {
+            continue;
+        }
+
+        if let Err(error) = TcpListener::bind((host.trim_matches(['[', ']']), port)) {
+            bail!(
+                "port {port} for service '{}' is not available on {host}: {error}",
+                service.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, urls: &[&str], instances: usize) -> Service {
+        Service {
+            base_url: urls.first().copied().unwrap_or_default().into(),
+            runner_urls: urls.iter().map(|url| url.to_string()).collect(),
+            auto_ports: urls.is_empty(),
+            runner_instances: instances,
+            ..Service::for_test(name)
+        }
+    }
+
+    #[test]
+    fn rejects_overlapping_explicit_ranges() {
+        let mut services = vec![
+            service(
+                "bff",
+                &[
+                    "http://127.0.0.1:15001",
+                    "http://127.0.0.1:15002",
+                    "http://127.0.0.1:15003",
+                ],
+                3,
+            ),
+            service("business", &["http://127.0.0.1:15002"], 1),
+            service("adapter", &["http://127.0.0.1:14000"], 1),
+        ];
+
+        assert_eq!(
+            find_port_conflicts(&services, 14000),
+            vec![
+                PortConflict::Overlap {
+                    service: "bff".into(),
+                    other: "business".into(),
+                    ports: vec![15002],
+                },
+                PortConflict::EntryPort {
+                    service: "adapter".into(),
+                    port: 14000,
+                },
+            ]
+        );
+
+        let error = assign_service_ports(&mut services, &[], 14000).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("services 'bff' and 'business' overlap on 15002"));
+    }
+
+    #[test]
+    fn allocates_free_ports_and_keeps_them_across_reloads() {
+        let mut services = vec![
+            service("auto", &[], 2),
+            service("fixed", &["http://127.0.0.1:15001"], 1),
+        ];
+        assign_service_ports(&mut services, &[], 14000).expect("assign ports");
+
+        let auto = &services[0];
+        let ports = runner_ports(auto);
+        assert_eq!(auto.runner_urls.len(), 2);
+        assert_eq!(ports.len(), 2);
+        assert!(!ports.contains(&15001) && !ports.contains(&14000));
+        assert_eq!(auto.base_url, auto.runner_urls[0]);
+        ensure_ports_available(auto).expect("ports are bindable");
+
+        let mut reloaded = vec![
+            service("auto", &[], 2),
+            service("fixed", &["http://127.0.0.1:15001"], 1),
+        ];
+        assign_service_ports(&mut reloaded, &services, 14000).expect("reassign ports");
+        assert_eq!(reloaded[0].runner_urls, services[0].runner_urls);
+    }
+}
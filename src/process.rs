@@ -1,55 +1,59 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, ExitStatus, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
 use sysinfo::{Pid, System};
 use url::Url;
 
-use crate::config::{self, Service};
-use crate::logs::{spawn_log_forwarder, SharedLogMap};
+use crate::config::{self, RestartMode, RestartPolicy, RuntimeConfig, Service};
+use crate::health::instance_ready;
+use crate::logs::{record_log_line, spawn_log_forwarder, SharedLogMap};
 use crate::memory::{record_memory_usage, reset_memory_entry, SharedMemoryMap};
+use crate::runtime::{module_command, module_runtime, ModuleRuntime};
+use crate::secrets::{load_secret_store, SecretStore, SecretValue};
+use crate::settings::RunnerSettings;
+
+pub fn run_module(settings: &RunnerSettings, module_name: &str) -> Result<()> {
+    let services_dir = settings.services_dir.as_path();
+    let (memory_page_limit, guest_env, runtime) = match lookup_service(services_dir, module_name) {
+        Some(service) => {
+            let secrets = load_secret_store(&settings.secrets_file)?;
+            let guest_env = build_service_env(&service, &secrets.resolve(&service)?);
+            (service.memory_page_limit(), guest_env, service.runtime)
+        }
+        None => (None, Vec::new(), RuntimeConfig::default()),
+    };
 
-fn module_directory(module_name: &str) -> PathBuf {
-    Path::new("services").join(module_name)
-}
-
-fn module_path(module_name: &str) -> Result<PathBuf> {
-    let wasm_path = module_directory(module_name).join(format!("{module_name}.wasm"));
-
-    if !wasm_path.exists() {
-        return Err(anyhow!(
-            "WebAssembly module '{}' was not found at {}",
-            module_name,
-            wasm_path.display()
-        ));
-    }
-
-    wasm_path
-        .canonicalize()
-        .with_context(|| format!("failed to canonicalize module path for '{}'", module_name))
-}
-
-pub fn run_module(module_name: &str) -> Result<()> {
-    let memory_page_limit = lookup_memory_page_limit(module_name);
-    run_module_with_output(
+    let status = run_module_with_output(
+        module_runtime(&runtime).as_ref(),
+        services_dir,
         module_name,
         memory_page_limit,
         OutputMode::Inherit,
+        &guest_env,
         None,
-        None,
-    )
+    )?;
+    match status {
+        Some(status) if !status.success() => Err(anyhow!(
+            "module '{}' exited with non-zero status {status}",
+            module_name
+        )),
+        _ => Ok(()),
+    }
 }
 
-fn lookup_memory_page_limit(module_name: &str) -> Option<u32> {
-    match config::load_services() {
+fn lookup_service(services_dir: &Path, module_name: &str) -> Option<Service> {
+    match config::load_services(services_dir) {
         Ok(services) => services
-            .iter()
-            .find(|service| service.name == module_name)
-            .and_then(|service| service.memory_page_limit()),
+            .into_iter()
+            .find(|service| service.name == module_name),
         Err(error) => {
             eprintln!(
                 "warning: could not read service configuration for '{module_name}': {error:?}"
@@ -59,53 +63,284 @@ fn lookup_memory_page_limit(module_name: &str) -> Option<u32> {
     }
 }
 
+const MODULE_EXIT_POLL_MILLIS: u64 = 200;
+const READINESS_POLL_MILLIS: u64 = 250;
+const READINESS_TIMEOUT_MILLIS: u64 = 1000;
+const STDERR_TAIL_LINES: usize = 10;
+// Bounds the wait for the stderr reader after an exit, in case a leftover
+// grandchild keeps the pipe open.
+const STDERR_DRAIN_MILLIS: u64 = 500;
+
+#[derive(Clone, Debug)]
+pub struct ModuleExit {
+    // `None` when the module was killed by a signal or could not be started.
+    pub code: Option<i32>,
+    pub description: String,
+    pub at: DateTime<Utc>,
+    // The last lines the module wrote to stderr before exiting.
+    pub stderr: Vec<String>,
+}
+
+// Only `Ready` instances receive proxied requests and scheduled calls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InstanceLifecycle {
+    #[default]
+    Starting,
+    Ready,
+    Draining,
+    Exited,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InstanceSupervision {
+    pub instance: usize,
+    pub port: Option<u16>,
+    // Only set while the module process is alive.
+    pub pid: Option<u32>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub lifecycle: InstanceLifecycle,
+    pub restarts: u32,
+    pub last_exit: Option<ModuleExit>,
+    // Too many restarts inside the policy window; the instance stays down
+    // until the service is restarted or reloaded.
+    pub circuit_open: bool,
+}
+
+impl InstanceLifecycle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstanceLifecycle::Starting => "starting",
+            InstanceLifecycle::Ready => "ready",
+            InstanceLifecycle::Draining => "draining",
+            InstanceLifecycle::Exited => "exited",
+        }
+    }
+}
+
+type SharedSupervision = Arc<Mutex<InstanceSupervision>>;
+type SharedStderrTail = Arc<Mutex<VecDeque<String>>>;
+// The running module process, left in place after a stop request so the
+// handle can wait for it and kill it once the grace period is over.
+type SharedChild = Arc<Mutex<Option<Child>>>;
+
 pub struct ServiceModuleHandle {
-    _join: JoinHandle<()>,
+    service_name: String,
+    stop_flag: Arc<AtomicBool>,
+    child: SharedChild,
+    supervision: SharedSupervision,
+    join: JoinHandle<()>,
+}
+
+impl ServiceModuleHandle {
+    pub fn service_name(&self) -> &str {
+        &self.service_name
+    }
+
+    pub fn supervision(&self) -> InstanceSupervision {
+        let mut supervision = self
+            .supervision
+            .lock()
+            .map(|supervision| supervision.clone())
+            .unwrap_or_default();
+        supervision.pid = self
+            .child
+            .lock()
+            .ok()
+            .and_then(|slot| slot.as_ref().map(Child::id));
+        supervision
+    }
+
+    // Only raises the flag; the instance thread sends SIGTERM to the module,
+    // so every handle in a batch gets the same grace period.
+    fn request_stop(&self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        if let Ok(mut supervision) = self.supervision.lock() {
+            if supervision.lifecycle != InstanceLifecycle::Exited {
+                supervision.lifecycle = InstanceLifecycle::Draining;
+            }
+        }
+    }
+
+    fn finish_stop(self, deadline: Instant) {
+        if self.join.join().is_err() {
+            eprintln!(
+                "module thread for service '{}' panicked while stopping",
+                self.service_name
+            );
+        }
+        let child = self.child.lock().ok().and_then(|mut slot| slot.take());
+        if let Some(child) = child {
+            reap_module(&self.service_name, child, deadline);
+            println!("Stopped module '{}'", self.service_name);
+        }
+    }
+}
+
+pub fn stop_module_handles(handles: Vec<ServiceModuleHandle>, grace: Duration) {
+    for handle in &handles {
+        handle.request_stop();
+    }
+    let deadline = Instant::now() + grace;
+    for handle in handles {
+        handle.finish_stop(deadline);
+    }
+}
+
+pub type SharedModuleRegistry = Arc<Mutex<HashMap<String, Vec<ServiceModuleHandle>>>>;
+
+pub fn initialize_module_registry() -> SharedModuleRegistry {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+pub fn register_service_modules(
+    registry: &SharedModuleRegistry,
+    handles: Vec<ServiceModuleHandle>,
+) {
+    let Ok(mut running) = registry.lock() else {
+        return;
+    };
+    for handle in handles {
+        running
+            .entry(handle.service_name().to_string())
+            .or_default()
+            .push(handle);
+    }
+}
+
+// Gives the service an empty entry before its modules start, so routing
+// treats it as having no ready instance rather than as not managed here.
+pub fn expect_service_modules(registry: &SharedModuleRegistry, service_name: &str) {
+    if let Ok(mut running) = registry.lock() {
+        running.entry(service_name.to_string()).or_default();
+    }
+}
+
+// Sorted by instance index, or `None` when the runner does not manage the
+// service's modules.
+pub fn service_instances(
+    registry: &SharedModuleRegistry,
+    service_name: &str,
+) -> Option<Vec<InstanceSupervision>> {
+    let running = registry.lock().ok()?;
+    let mut instances: Vec<InstanceSupervision> = running
+        .get(service_name)?
+        .iter()
+        .map(ServiceModuleHandle::supervision)
+        .collect();
+    instances.sort_by_key(|supervision| supervision.instance);
+    Some(instances)
+}
+
+// Indexes of the instances that can take traffic, or `None` when the runner
+// does not manage the service's modules.
+pub fn ready_instances(registry: &SharedModuleRegistry, service_name: &str) -> Option<Vec<usize>> {
+    let instances = service_instances(registry, service_name)?;
+    Some(
+        instances
+            .into_iter()
+            .filter(|supervision| supervision.lifecycle == InstanceLifecycle::Ready)
+            .map(|supervision| supervision.instance)
+            .collect(),
+    )
+}
+
+pub fn all_instances_ready(registry: &SharedModuleRegistry, service_name: &str) -> bool {
+    let Ok(running) = registry.lock() else {
+        return false;
+    };
+    running.get(service_name).is_some_and(|handles| {
+        !handles.is_empty()
+            && handles
+                .iter()
+                .all(|handle| handle.supervision().lifecycle == InstanceLifecycle::Ready)
+    })
+}
+
+pub fn supervision_snapshot(
+    registry: &SharedModuleRegistry,
+) -> HashMap<String, Vec<InstanceSupervision>> {
+    let Ok(running) = registry.lock() else {
+        return HashMap::new();
+    };
+    running
+        .iter()
+        .map(|(name, handles)| {
+            let mut instances: Vec<InstanceSupervision> = handles
+                .iter()
+                .map(ServiceModuleHandle::supervision)
+                .collect();
+            instances.sort_by_key(|instance| instance.instance);
+            (name.clone(), instances)
+        })
+        .collect()
+}
+
+// The handles are taken out of the registry first so the lock is not held
+// while waiting for the module threads to exit.
+pub fn stop_service_modules(registry: &SharedModuleRegistry, service_name: &str, grace: Duration) {
+    let handles = match registry.lock() {
+        Ok(mut running) => running.remove(service_name).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    stop_module_handles(handles, grace);
+}
+
+pub fn stop_all_modules(registry: &SharedModuleRegistry, grace: Duration) {
+    let handles: Vec<ServiceModuleHandle> = match registry.lock() {
+        Ok(mut running) => running.drain().flat_map(|(_, handles)| handles).collect(),
+        Err(_) => Vec::new(),
+    };
+    stop_module_handles(handles, grace);
 }
 
 pub fn start_service_modules(
     services: &[Service],
+    services_dir: &Path,
+    secrets: &SecretStore,
     logs: &SharedLogMap,
     memory: &SharedMemoryMap,
 ) -> Result<Vec<ServiceModuleHandle>> {
     let mut handles = Vec::new();
 
     for service in services {
+        let service_env = build_service_env(service, &secrets.resolve(service)?);
         let instance_count = service.runner_endpoints().len().max(1);
         for (instance_index, instance_url) in service.runner_endpoints().iter().cloned().enumerate()
         {
-            let module_name = service.name.clone();
-            let log_store = Arc::clone(logs);
-            let memory_page_limit = service.memory_page_limit();
-            let memory_store = Arc::clone(memory);
-            let instance_total = instance_count;
-
-            let handle = thread::Builder::new()
-                .name(format!("svc-{}-{}", module_name, instance_index))
+            let mut guest_env = build_instance_env(instance_index, instance_count, &instance_url);
+            guest_env.extend(service_env.iter().cloned());
+            let instance = SupervisedInstance {
+                service_name: service.name.clone(),
+                instance_index,
+                url: instance_url.clone(),
+                services_dir: services_dir.to_path_buf(),
+                memory_page_limit: service.memory_page_limit(),
+                guest_env,
+                policy: service.restart,
+                runtime: service.runtime.clone(),
+                logs: Arc::clone(logs),
+                memory: Arc::clone(memory),
+            };
+            let stop_flag = Arc::new(AtomicBool::new(false));
+            let child: SharedChild = Arc::default();
+            let supervision = Arc::new(Mutex::new(InstanceSupervision {
+                instance: instance_index,
+                port: port_from_url(&instance_url),
+                ..InstanceSupervision::default()
+            }));
+            let thread_stop_flag = Arc::clone(&stop_flag);
+            let thread_child = Arc::clone(&child);
+            let thread_supervision = Arc::clone(&supervision);
+
+            let join = thread::Builder::new()
+                .name(format!("svc-{}-{}", service.name, instance_index))
                 .spawn(move || {
-                    let output = OutputMode::Forward {
-                        service_name: module_name.clone(),
-                        logs: log_store,
-                    };
-                    let env_overrides =
-                        build_instance_env(instance_index, instance_total, &instance_url);
-                    let env_slice = if env_overrides.is_empty() {
-                        None
-                    } else {
-                        Some(env_overrides.as_slice())
-                    };
-
-                    if let Err(error) = run_module_with_output(
-                        &module_name,
-                        memory_page_limit,
-                        output,
-                        env_slice,
-                        Some(memory_store),
-                    ) {
-                        eprintln!(
-                            "service '{module_name}' (instance {instance_index}) exited with error: {error:?}"
-                        );
-                    }
+                    supervise_instance(
+                        instance,
+                        &thread_stop_flag,
+                        &thread_child,
+                        &thread_supervision,
+                    )
                 })
                 .with_context(|| {
                     format!(
@@ -114,13 +349,243 @@ pub fn start_service_modules(
                     )
                 })?;
 
-            handles.push(ServiceModuleHandle { _join: handle });
+            handles.push(ServiceModuleHandle {
+                service_name: service.name.clone(),
+                stop_flag,
+                child,
+                supervision,
+                join,
+            });
         }
     }
 
     Ok(handles)
 }
 
+struct SupervisedInstance {
+    service_name: String,
+    instance_index: usize,
+    url: String,
+    services_dir: PathBuf,
+    memory_page_limit: Option<u32>,
+    guest_env: Vec<(String, String)>,
+    policy: RestartPolicy,
+    runtime: RuntimeConfig,
+    logs: SharedLogMap,
+    memory: SharedMemoryMap,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RestartDecision {
+    Restart(Duration),
+    StayDown,
+    CircuitOpen,
+}
+
+// Runs the module until it is stopped on purpose, restarting it as the
+// service's policy allows.
+fn supervise_instance(
+    instance: SupervisedInstance,
+    stop_flag: &Arc<AtomicBool>,
+    child: &SharedChild,
+    supervision: &SharedSupervision,
+) {
+    let SupervisedInstance {
+        service_name,
+        instance_index,
+        url,
+        services_dir,
+        memory_page_limit,
+        guest_env,
+        policy,
+        runtime,
+        logs,
+        memory,
+    } = instance;
+    let runtime = module_runtime(&runtime);
+    let mut recent_restarts = VecDeque::new();
+    let update = |change: &dyn Fn(&mut InstanceSupervision)| {
+        if let Ok(mut supervision) = supervision.lock() {
+            change(&mut supervision);
+        }
+    };
+
+    loop {
+        update(&|supervision| {
+            supervision.lifecycle = InstanceLifecycle::Starting;
+            supervision.started_at = Some(Utc::now());
+        });
+        spawn_readiness_probe(url.clone(), Arc::clone(supervision), Arc::clone(stop_flag));
+        let stderr_tail = SharedStderrTail::default();
+        let output = OutputMode::Forward {
+            service_name: service_name.clone(),
+            logs: Arc::clone(&logs),
+            stop_flag: Arc::clone(stop_flag),
+            child: Arc::clone(child),
+            stderr_tail: Arc::clone(&stderr_tail),
+        };
+        let result = run_module_with_output(
+            runtime.as_ref(),
+            &services_dir,
+            &service_name,
+            memory_page_limit,
+            output,
+            &guest_env,
+            Some(Arc::clone(&memory)),
+        );
+        update(&|supervision| supervision.lifecycle = InstanceLifecycle::Exited);
+
+        let stderr = stderr_tail
+            .lock()
+            .map(|mut tail| tail.drain(..).collect())
+            .unwrap_or_default();
+        let exit = match result {
+            Ok(None) => return,
+            Ok(Some(status)) => ModuleExit {
+                code: status.code(),
+                description: status.to_string(),
+                at: Utc::now(),
+                stderr,
+            },
+            Err(error) => ModuleExit {
+                code: None,
+                description: format!("{error:#}"),
+                at: Utc::now(),
+                stderr,
+            },
+        };
+        let failed = exit.code != Some(0);
+        if stop_flag.load(Ordering::Relaxed) {
+            return;
+        }
+
+        eprintln!(
+            "service '{service_name}' (instance {instance_index}) exited: {}",
+            exit.description
+        );
+        let decision = next_restart(&policy, failed, &mut recent_restarts, Instant::now());
+        let (level, message) = match decision {
+            RestartDecision::Restart(wait) => (
+                "warn",
+                format!(
+                    "La instancia {instance_index} terminó ({}); se reinicia en {}s",
+                    exit.description,
+                    wait.as_secs()
+                ),
+            ),
+            RestartDecision::StayDown => (
+                "info",
+                format!(
+                    "La instancia {instance_index} terminó ({}); la política '{}' no la reinicia",
+                    exit.description,
+                    policy.mode.as_str()
+                ),
+            ),
+            RestartDecision::CircuitOpen => (
+                "error",
+                format!(
+                    "La instancia {instance_index} terminó ({}) tras {} reinicios en {}s; queda detenida",
+                    exit.description, policy.max_restarts, policy.window_secs
+                ),
+            ),
+        };
+        record_log_line(
+            &service_name,
+            &format!("[{}] {message}", level.to_uppercase()),
+            level,
+            &logs,
+        );
+
+        let circuit_open = decision == RestartDecision::CircuitOpen;
+        update(&|supervision| {
+            supervision.last_exit = Some(exit.clone());
+            supervision.circuit_open = circuit_open;
+        });
+        let RestartDecision::Restart(wait) = decision else {
+            if circuit_open {
+                eprintln!(
+                    "service '{service_name}' (instance {instance_index}) restarted {} times within {}s, giving up",
+                    policy.max_restarts, policy.window_secs
+                );
+            }
+            return;
+        };
+        if !wait_before_restart(wait, stop_flag) {
+            return;
+        }
+        update(&|supervision| supervision.restarts += 1);
+    }
+}
+
+// Marks the instance ready once its port accepts connections and `/health`
+// answers 200; gives up when the run ends or the instance is stopped.
+fn spawn_readiness_probe(url: String, supervision: SharedSupervision, stop_flag: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let starting = || {
+            supervision
+                .lock()
+                .is_ok_and(|supervision| supervision.lifecycle == InstanceLifecycle::Starting)
+        };
+        loop {
+            thread::sleep(Duration::from_millis(READINESS_POLL_MILLIS));
+            if stop_flag.load(Ordering::Relaxed) || !starting() {
+                return;
+            }
+            if instance_ready(&url, Duration::from_millis(READINESS_TIMEOUT_MILLIS)) {
+                if let Ok(mut supervision) = supervision.lock() {
+                    if supervision.lifecycle == InstanceLifecycle::Starting {
+                        supervision.lifecycle = InstanceLifecycle::Ready;
+                    }
+                }
+                return;
+            }
+        }
+    });
+}
+
+// `recent` holds when the restarts still inside the policy window happened.
+fn next_restart(
+    policy: &RestartPolicy,
+    failed: bool,
+    recent: &mut VecDeque<Instant>,
+    now: Instant,
+) -> RestartDecision {
+    match policy.mode {
+        RestartMode::Never => return RestartDecision::StayDown,
+        RestartMode::OnFailure if !failed => return RestartDecision::StayDown,
+        _ => {}
+    }
+
+    while recent
+        .front()
+        .is_some_and(|restart| now.duration_since(*restart) >= policy.window())
+    {
+        recent.pop_front();
+    }
+    if recent.len() >= policy.max_restarts as usize {
+        return RestartDecision::CircuitOpen;
+    }
+
+    let wait = policy.backoff(recent.len() as u32);
+    recent.push_back(now);
+    RestartDecision::Restart(wait)
+}
+
+// Returns false when the service was stopped while waiting.
+fn wait_before_restart(wait: Duration, stop_flag: &AtomicBool) -> bool {
+    let deadline = Instant::now() + wait;
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            return false;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(MODULE_EXIT_POLL_MILLIS)));
+    }
+}
+
 fn build_instance_env(
     instance_index: usize,
     total_instances: usize,
@@ -140,6 +605,25 @@ fn build_instance_env(
     vars
 }
 
+fn build_service_env(
+    service: &Service,
+    secrets: &[(String, SecretValue)],
+) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = service
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    vars.extend(
+        secrets
+            .iter()
+            .map(|(key, value)| (key.clone(), value.expose().to_string())),
+    );
+
+    vars
+}
+
 fn port_from_url(url: &str) -> Option<u16> {
     Url::parse(url)
         .ok()
@@ -151,80 +635,89 @@ enum OutputMode {
     Forward {
         service_name: String,
         logs: SharedLogMap,
+        stop_flag: Arc<AtomicBool>,
+        child: SharedChild,
+        stderr_tail: SharedStderrTail,
     },
 }
 
 fn run_module_with_output(
+    runtime: &dyn ModuleRuntime,
+    services_dir: &Path,
     module_name: &str,
     memory_page_limit: Option<u32>,
     output: OutputMode,
-    extra_env: Option<&[(String, String)]>,
+    guest_env: &[(String, String)],
     memory_store: Option<SharedMemoryMap>,
-) -> Result<()> {
-    let wasm_path = module_path(module_name)?;
-
-    let mut command = Command::new("wasmedge");
-    if let Some(limit) = memory_page_limit {
-        command.arg("--memory-page-limit");
-        command.arg(limit.to_string());
-    }
-    command.arg(&wasm_path);
-
-    if let Some(envs) = extra_env {
-        for (key, value) in envs {
-            command.env(key, value);
-        }
-    }
+) -> Result<Option<ExitStatus>> {
+    let mut command = module_command(
+        runtime,
+        services_dir,
+        module_name,
+        memory_page_limit,
+        guest_env,
+    )?;
 
     match output {
         OutputMode::Inherit => {
             command.stdout(Stdio::inherit());
             command.stderr(Stdio::inherit());
-            let status = command
-                .status()
-                .with_context(|| format!("failed to execute module '{module_name}'"))?;
-            if status.success() {
-                Ok(())
-            } else {
-                Err(anyhow!(
-                    "module '{}' exited with non-zero status {status}",
-                    module_name
-                ))
-            }
+            let status = command.status().with_context(|| {
+                format!(
+                    "failed to execute module '{module_name}' with the {} runtime",
+                    runtime.name()
+                )
+            })?;
+            Ok(Some(status))
         }
-        OutputMode::Forward { service_name, logs } => {
+        OutputMode::Forward {
+            service_name,
+            logs,
+            stop_flag: module_stop_flag,
+            child: child_slot,
+            stderr_tail,
+        } => {
             command.stdout(Stdio::piped());
             command.stderr(Stdio::piped());
 
-            let mut child = command
-                .spawn()
-                .with_context(|| format!("failed to execute module '{module_name}'"))?;
+            let mut child = command.spawn().with_context(|| {
+                format!(
+                    "failed to execute module '{module_name}' with the {} runtime",
+                    runtime.name()
+                )
+            })?;
 
+            let pid = child.id();
             if let Some(stdout) = child.stdout.take() {
                 spawn_log_forwarder(service_name.clone(), stdout, "stdout", Arc::clone(&logs));
             }
 
-            if let Some(stderr) = child.stderr.take() {
-                spawn_log_forwarder(service_name.clone(), stderr, "stderr", logs);
-            }
+            let stderr_forwarder = child.stderr.take().map(|stderr| {
+                spawn_stderr_forwarder(service_name.clone(), stderr, logs, stderr_tail)
+            });
 
             let (stop_flag, monitor_handle) = if let Some(store) = memory_store {
                 let stop_flag = Arc::new(AtomicBool::new(false));
-                let handle = spawn_memory_probe(
-                    service_name.clone(),
-                    child.id(),
-                    store,
-                    Arc::clone(&stop_flag),
-                );
+                let handle =
+                    spawn_memory_probe(service_name.clone(), pid, store, Arc::clone(&stop_flag));
                 (Some(stop_flag), Some(handle))
             } else {
                 (None, None)
             };
 
-            let status = child
-                .wait()
+            if let Ok(mut slot) = child_slot.lock() {
+                *slot = Some(child);
+            }
+            let status = wait_for_exit(&child_slot, pid, &module_stop_flag)
                 .with_context(|| format!("failed while waiting for '{module_name}'"))?;
 
+            if let (Some(_), Some(forwarder)) = (status, stderr_forwarder) {
+                let deadline = Instant::now() + Duration::from_millis(STDERR_DRAIN_MILLIS);
+                while !forwarder.is_finished() && Instant::now() < deadline {
+                    thread::sleep(Duration::from_millis(20));
+                }
+            }
+
             if let Some(flag) = stop_flag {
                 flag.store(true, Ordering::Relaxed);
             }
@@ -233,18 +726,110 @@ fn run_module_with_output(
                 let _ = handle.join();
             }
 
-            if status.success() {
-                Ok(())
-            } else {
-                Err(anyhow!(
-                    "module '{}' exited with non-zero status {status}",
-                    module_name
-                ))
+            Ok(status)
+        }
+    }
+}
+
+// Like `spawn_log_forwarder`, but also keeps the last lines so they can be
+// shown next to the exit status once the module dies.
+fn spawn_stderr_forwarder<R>(
+    service_name: String,
+    reader: R,
+    logs: SharedLogMap,
+    tail: SharedStderrTail,
+) -> JoinHandle<()>
+where
+    R: std::io::Read + Send + 'static,
+{
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    eprintln!("failed to read stderr from service '{service_name}': {error}");
+                    break;
+                }
+            };
+            record_log_line(&service_name, &line, "stderr", &logs);
+            if let Ok(mut tail) = tail.lock() {
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line.trim_end_matches('\r').to_string());
+            }
+        }
+    })
+}
+
+// Polls instead of blocking on `wait` so the module can be stopped when the
+// service is removed or restarted. On a stop request the module gets SIGTERM
+// and stays in the slot for its handle to reap.
+fn wait_for_exit(
+    child: &SharedChild,
+    pid: u32,
+    stop_flag: &AtomicBool,
+) -> Result<Option<ExitStatus>> {
+    loop {
+        {
+            let mut slot = child
+                .lock()
+                .map_err(|_| anyhow!("module process lock poisoned"))?;
+            let Some(process) = slot.as_mut() else {
+                return Ok(None);
+            };
+            if let Some(status) = process.try_wait()? {
+                slot.take();
+                return Ok(Some(status));
+            }
+        }
+
+        if stop_flag.load(Ordering::Relaxed) {
+            terminate(pid);
+            return Ok(None);
+        }
+
+        thread::sleep(Duration::from_millis(MODULE_EXIT_POLL_MILLIS));
+    }
+}
+
+// Kills the module if it is still running once the deadline passes.
+fn reap_module(service_name: &str, mut child: Child, deadline: Instant) {
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return,
+            Ok(None) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(MODULE_EXIT_POLL_MILLIS));
+            }
+            Ok(None) => {
+                eprintln!(
+                    "module for service '{service_name}' did not exit within its grace period, killing it"
+                );
+                break;
             }
+            Err(_) => break,
         }
     }
+    let _ = child.kill();
+    let _ = child.wait();
 }
 
+#[cfg(unix)]
+fn terminate(pid: u32) {
+    if let Ok(pid) = libc::pid_t::try_from(pid) {
+        // SAFETY: `kill` only sends a signal; the pid belongs to a child that
+        // has not been reaped yet, so it cannot have been reused.
+        unsafe {
+            libc::kill(pid, libc::SIGTERM);
+        }
+    }
+}
+
+// Without signals the module keeps running until its handle kills it at the
+// end of the grace period.
+#[cfg(not(unix))]
+fn terminate(_pid: u32) {}
+
 fn spawn_memory_probe(
     service_name: String,
     pid: u32,
@@ -263,16 +848,152 @@ fn spawn_memory_probe(
             system.refresh_process(pid);
 
             if let Some(process) = system.process(pid) {
-                let usage_bytes = (process.memory() as u64).saturating_mul(1024);
+                let usage_bytes = process.memory().saturating_mul(1024);
                 record_memory_usage(&store, &service_name, Some(usage_bytes));
             } else {
                 reset_memory_entry(&store, &service_name);
                 break;
             }
 
-            thread::sleep(Duration::from_secs(2));
+            // Sleeps in short steps so an exited module is reported without
+            // waiting for the next sample.
+            let next_sample = Instant::now() + Duration::from_secs(2);
+            while Instant::now() < next_sample && !stop_flag.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(MODULE_EXIT_POLL_MILLIS));
+            }
         }
 
         reset_memory_entry(&store, &service_name);
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supervises_a_module_run_by_the_native_runtime() {
+        let service = Service {
+            base_url: "http://127.0.0.1:9".into(),
+            runner_urls: vec!["http://127.0.0.1:9".into()],
+            env: [("REASON".to_string(), "boom".to_string())].into(),
+            restart: RestartPolicy {
+                mode: RestartMode::Never,
+                ..RestartPolicy::default()
+            },
+            runtime: RuntimeConfig::Native {
+                command: "sh".into(),
+                args: vec![
+                    "-c".into(),
+                    "echo \"panic: $REASON on $WR_RUNNER_PORT\" >&2; exit 3".into(),
+                ],
+            },
+            ..Service::for_test("svc")
+        };
+        let services = [service];
+        let logs = crate::logs::initialize_log_store(&services, 10);
+        let memory = crate::memory::initialize_memory_store(&services);
+        let handles = start_service_modules(
+            &services,
+            Path::new("."),
+            &SecretStore::default(),
+            &logs,
+            &memory,
+        )
+        .expect("start native module");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let exited = loop {
+            let supervision = handles[0].supervision();
+            if supervision.last_exit.is_some() || Instant::now() > deadline {
+                break supervision;
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+        stop_module_handles(handles, Duration::ZERO);
+
+        assert_eq!(exited.lifecycle, InstanceLifecycle::Exited);
+        let exit = exited.last_exit.expect("module exit");
+        assert_eq!(exit.code, Some(3));
+        assert_eq!(exit.stderr, vec!["panic: boom on 9"]);
+    }
+
+    #[test]
+    fn keeps_only_the_last_stderr_lines() {
+        let output: String = (1..=15).map(|line| format!("line {line}\n")).collect();
+        let logs = crate::logs::initialize_log_store(&[], 100);
+        let tail = SharedStderrTail::default();
+
+        spawn_stderr_forwarder(
+            "svc".into(),
+            std::io::Cursor::new(output),
+            logs,
+            Arc::clone(&tail),
+        )
+        .join()
+        .expect("forwarder");
+
+        let tail: Vec<String> = tail.lock().unwrap().iter().cloned().collect();
+        assert_eq!(tail.len(), STDERR_TAIL_LINES);
+        assert_eq!(tail.first().map(String::as_str), Some("line 6"));
+        assert_eq!(tail.last().map(String::as_str), Some("line 15"));
+    }
+
+    #[test]
+    fn restarts_with_backoff_until_the_window_is_full() {
+        let policy = RestartPolicy {
+            max_restarts: 3,
+            window_secs: 60,
+            backoff_secs: 2,
+            ..RestartPolicy::default()
+        };
+        let start = Instant::now();
+        let mut recent = VecDeque::new();
+
+        assert_eq!(
+            next_restart(&policy, false, &mut recent, start),
+            RestartDecision::StayDown
+        );
+        let waits: Vec<RestartDecision> = (0..4)
+            .map(|step| {
+                next_restart(
+                    &policy,
+                    true,
+                    &mut recent,
+                    start + Duration::from_secs(step),
+                )
+            })
+            .collect();
+        assert_eq!(
+            waits,
+            vec![
+                RestartDecision::Restart(Duration::from_secs(2)),
+                RestartDecision::Restart(Duration::from_secs(4)),
+                RestartDecision::Restart(Duration::from_secs(8)),
+                RestartDecision::CircuitOpen,
+            ]
+        );
+        // Restarts older than the window no longer count.
+        assert_eq!(
+            next_restart(&policy, true, &mut recent, start + Duration::from_secs(61)),
+            RestartDecision::Restart(Duration::from_secs(4))
+        );
+
+        let always = RestartPolicy {
+            mode: RestartMode::Always,
+            ..policy
+        };
+        assert!(matches!(
+            next_restart(&always, false, &mut VecDeque::new(), start),
+            RestartDecision::Restart(_)
+        ));
+        let never = RestartPolicy {
+            mode: RestartMode::Never,
+            ..policy
+        };
+        assert_eq!(
+            next_restart(&never, true, &mut VecDeque::new(), start),
+            RestartDecision::StayDown
+        );
+    }
+}
@@ -28,27 +28,27 @@ struct QueueInfo {
 #[derive(Default)]
 pub struct QueueRegistry {
     queues: HashMap<String, QueueInfo>,
+    // Set on shutdown so no new message is delivered to a stopping module.
+    closed: bool,
 }
 
 pub type SharedQueueRegistry = Arc<Mutex<QueueRegistry>>;
 
 impl QueueRegistry {
     fn register_listener(&mut self, queue: &str, listener: QueueSubscriber) {
-        let entry = self
-            .queues
-            .entry(queue.to_string())
-            .or_insert_with(QueueInfo::default);
+        let entry = self.queues.entry(queue.to_string()).or_default();
 
         if !entry.subscribers.contains(&listener) {
             entry.subscribers.push(listener);
         }
     }
 
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
     pub fn prepare_delivery(&mut self, queue: &str) -> (Vec<QueueSubscriber>, u64) {
-        let entry = self
-            .queues
-            .entry(queue.to_string())
-            .or_insert_with(QueueInfo::default);
+        let entry = self.queues.entry(queue.to_string()).or_default();
 
         entry.instantiated = true;
         entry.message_count = entry.message_count.saturating_add(1);
@@ -87,6 +87,20 @@ pub fn initialize_queue_registry(services: &[Service]) -> SharedQueueRegistry {
     Arc::new(Mutex::new(registry))
 }
 
+// Rebuilds every subscription from the given services while keeping queue
+// counters, so a reload never loses the message history shown on the dashboard.
+pub fn sync_queue_subscriptions(registry: &SharedQueueRegistry, services: &[Service]) {
+    if let Ok(mut guard) = registry.lock() {
+        for info in guard.queues.values_mut() {
+            info.subscribers.clear();
+        }
+
+        for service in services {
+            register_service_listeners(&mut guard, service, &service.queue_listeners);
+        }
+    }
+}
+
 fn register_service_listeners(
     registry: &mut QueueRegistry,
     service: &Service,
@@ -119,25 +133,47 @@ where
     Ok(action(&mut guard))
 }
 
+// Counts the message and hands it to every subscriber in turn; a subscriber
+// that cannot be reached is logged and skipped.
+pub fn publish_message(
+    registry: &SharedQueueRegistry,
+    queue: &str,
+    content_type: &str,
+    payload: &[u8],
+) -> Result<(usize, u64)> {
+    let (subscribers, message_count) = with_queue_registry(registry, |registry| {
+        (!registry.closed).then(|| registry.prepare_delivery(queue))
+    })?
+    .ok_or_else(|| anyhow!("queue '{queue}' is closed because the runner is shutting down"))?;
+
+    for subscriber in &subscribers {
+        let call = ureq::post(&subscriber.target_url)
+            .set("X-Rustrunner-Queue", queue)
+            .set("Content-Type", content_type);
+
+        if let Err(error) = call.send_bytes(payload) {
+            eprintln!(
+                "Failed to deliver queue '{}' event to service '{}' at {}: {}",
+                queue, subscriber.service_name, subscriber.target_url, error
+            );
+        }
+    }
+
+    Ok((subscribers.len(), message_count))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::ServiceKind;
-    use std::collections::HashSet;
 
     fn sample_service(name: &str, url: &str) -> Service {
         Service {
-            name: name.into(),
             domain: "test".into(),
             kind: ServiceKind::Adapter,
-            prefix: name.into(),
             base_url: url.into(),
             runner_urls: vec![url.into()],
-            allowed_get_endpoints: HashSet::new(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
-            memory_limit_mb: None,
-            runner_instances: 1,
+            ..Service::for_test(name)
         }
     }
 
@@ -180,4 +216,25 @@ mod tests {
         registry.prepare_delivery("queue");
         assert_eq!(registry.snapshot().len(), 1);
     }
+
+    #[test]
+    fn sync_replaces_subscribers_and_keeps_counters() {
+        let mut listener = sample_service("old", "http://localhost:1000");
+        listener.queue_listeners = vec![ServiceQueueListener {
+            queue: "events".into(),
+            path: "/hook".into(),
+        }];
+        let registry = initialize_queue_registry(std::slice::from_ref(&listener));
+        with_queue_registry(&registry, |registry| registry.prepare_delivery("events")).unwrap();
+
+        let mut replacement = sample_service("new", "http://localhost:2000");
+        replacement.queue_listeners = listener.queue_listeners.clone();
+        sync_queue_subscriptions(&registry, &[replacement]);
+
+        let (subscribers, count) =
+            with_queue_registry(&registry, |registry| registry.prepare_delivery("events")).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(subscribers.len(), 1);
+        assert_eq!(subscribers[0].service_name, "new");
+    }
 }
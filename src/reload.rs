@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use crate::catalog::{begin_catalog_write, ServiceCatalog};
+use crate::config::{self, load_services, Service};
+use crate::health::sync_health_entries;
+use crate::logs::record_log_line;
+use crate::memory::sync_memory_store;
+use crate::ports::assign_service_ports;
+use crate::process::stop_service_modules;
+use crate::queue::sync_queue_subscriptions;
+use crate::runtime_schedules::carry_runtime_schedules;
+use crate::scheduler::{apply_service_schedules, remove_service_schedules};
+use crate::secrets::load_secret_store;
+use crate::server::RunnerState;
+use crate::startup::{cancel_startup, launch_services};
+
+type Fingerprint = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ReloadPlan {
+    added: Vec<String>,
+    removed: Vec<String>,
+    restarted: Vec<String>,
+    updated: Vec<String>,
+    rescheduled: Vec<String>,
+}
+
+impl ReloadPlan {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+// Only settings that shape the running process force a restart; routing,
+// OpenAPI and queue changes are applied by swapping the catalog.
+fn requires_restart(current: &Service, next: &Service) -> bool {
+    current.runner_urls != next.runner_urls
+        || current.memory_limit_mb != next.memory_limit_mb
+        || current.env != next.env
+        || current.secrets != next.secrets
+        || current.restart != next.restart
+        || current.runtime != next.runtime
+}
+
+fn plan_reload(current: &[Service], next: &[Service]) -> ReloadPlan {
+    let mut plan = ReloadPlan::default();
+
+    for service in next {
+        match current
+            .iter()
+            .find(|existing| existing.name == service.name)
+        {
+            None => {
+                plan.added.push(service.name.clone());
+                plan.rescheduled.push(service.name.clone());
+            }
+            Some(existing) if existing != service => {
+                plan.updated.push(service.name.clone());
+                if requires_restart(existing, service) {
+                    plan.restarted.push(service.name.clone());
+                }
+                if existing.schedules != service.schedules
+                    || existing.runner_endpoints() != service.runner_endpoints()
+                {
+                    plan.rescheduled.push(service.name.clone());
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    for service in current {
+        if !next.iter().any(|candidate| candidate.name == service.name) {
+            plan.removed.push(service.name.clone());
+        }
+    }
+
+    plan
+}
+
+fn services_fingerprint(services_dir: &Path) -> Fingerprint {
+    let mut fingerprint = Fingerprint::new();
+    let Ok(names) = config::list_service_names(services_dir) else {
+        return fingerprint;
+    };
+
+    for name in names {
+        for path in [
+            config::config_path(services_dir, &name),
+            config::openapi_path(services_dir, &name),
+        ] {
+            let entry = match fs::metadata(&path) {
+                Ok(metadata) => (metadata.modified().ok(), metadata.len()),
+                Err(_) => (None, 0),
+            };
+            fingerprint.insert(path, entry);
+        }
+    }
+
+    fingerprint
+}
+
+pub fn start_service_watcher(state: &RunnerState) {
+    let interval = state.settings.reload_poll_interval_secs;
+    if interval == 0 {
+        return;
+    }
+
+    let state = state.clone();
+    thread::spawn(move || {
+        let services_dir = state.settings.services_dir.clone();
+        let mut fingerprint = services_fingerprint(&services_dir);
+
+        loop {
+            thread::sleep(Duration::from_secs(interval));
+            if state.shutdown.is_requested() {
+                return;
+            }
+
+            let next = services_fingerprint(&services_dir);
+            if next == fingerprint {
+                continue;
+            }
+            fingerprint = next;
+
+            if let Err(error) = reload_services(&state) {
+                eprintln!("Failed to reload services, keeping the current set: {error:#}");
+            }
+        }
+    });
+}
+
+fn reload_services(state: &RunnerState) -> Result<()> {
+    let settings = &state.settings;
+    let mut catalog = begin_catalog_write(&state.catalog);
+    let mut next_services = load_services(&settings.services_dir)?;
+    let current = Arc::clone(catalog.snapshot());
+    assign_service_ports(&mut next_services, current.services(), settings.entry_port)?;
+    carry_runtime_schedules(
+        &state.schedule_overrides,
+        current.services(),
+        &mut next_services,
+    );
+    let plan = plan_reload(current.services(), &next_services);
+
+    if plan.is_empty() {
+        return Ok(());
+    }
+
+    let to_start: Vec<Service> = next_services
+        .iter()
+        .filter(|service| {
+            plan.added.contains(&service.name) || plan.restarted.contains(&service.name)
+        })
+        .cloned()
+        .collect();
+
+    // The routing table and secrets are resolved before anything is stopped so
+    // an invalid change leaves the running modules untouched.
+    let next_catalog = ServiceCatalog::build(next_services.clone())?;
+    let secrets = load_secret_store(&settings.secrets_file)?;
+    for service in &to_start {
+        secrets.resolve(service)?;
+    }
+
+    sync_memory_store(&state.memory, &next_services);
+    catalog.replace(next_catalog);
+    for service in &next_services {
+        if plan.rescheduled.contains(&service.name) {
+            apply_service_schedules(&state.schedules, service);
+        }
+    }
+    for name in &plan.removed {
+        remove_service_schedules(&state.schedules, name);
+    }
+    // Stopping modules can take the whole grace period; schedule edits only
+    // need to wait for the catalog and the schedules above.
+    drop(catalog);
+
+    for name in plan.restarted.iter().chain(&plan.removed) {
+        cancel_startup(&state.startup, name);
+        stop_service_modules(
+            &state.modules,
+            name,
+            Duration::from_secs(settings.shutdown_grace_secs),
+        );
+    }
+
+    let restarted: Vec<&str> = plan.restarted.iter().map(String::as_str).collect();
+    sync_health_entries(&state.health, &next_services, &restarted);
+    sync_queue_subscriptions(&state.queues, &next_services);
+    launch_services(state, &to_start, &Arc::new(secrets));
+
+    report_reload(state, &plan);
+    Ok(())
+}
+
+fn report_reload(state: &RunnerState, plan: &ReloadPlan) {
+    println!(
+        "Reloaded services: added {:?}, removed {:?}, restarted {:?}, updated {:?}",
+        plan.added, plan.removed, plan.restarted, plan.updated
+    );
+
+    for name in &plan.added {
+        record_log_line(
+            name,
+            "[INFO] Servicio añadido por recarga en caliente",
+            "info",
+            &state.logs,
+        );
+    }
+
+    for name in &plan.updated {
+        let message = if plan.restarted.contains(name) {
+            "[INFO] Configuración recargada; servicio reiniciado"
+        } else {
+            "[INFO] Configuración recargada sin reiniciar el servicio"
+        };
+        record_log_line(name, message, "info", &state.logs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServiceSchedule;
+    use crate::cron::ScheduleTrigger;
+
+    fn service(name: &str, url: &str) -> Service {
+        Service {
+            base_url: url.into(),
+            runner_urls: vec![url.into()],
+            ..Service::for_test(name)
+        }
+    }
+
+    #[test]
+    fn plans_restarts_only_for_process_level_changes() {
+        let current = vec![
+            service("kept", "http://localhost:1001"),
+            service("moved", "http://localhost:1002"),
+            service("retuned", "http://localhost:1003"),
+            service("gone", "http://localhost:1004"),
+        ];
+
+        let mut moved = service("moved", "http://localhost:2002");
+        moved.prefix = "moved-v2".into();
+        let mut retuned = service("retuned", "http://localhost:1003");
+        retuned.prefix = "retuned-v2".into();
+        retuned.schedules = vec![ServiceSchedule {
+            endpoint: "ping".into(),
+            queue: None,
+            trigger: ScheduleTrigger::Interval { secs: 10 },
+            method: "GET".into(),
+            headers: Default::default(),
+            body: None,
+            concurrency: Default::default(),
+            catch_up: Default::default(),
+            jitter_secs: 0,
+            target: Default::default(),
+            retry: Default::default(),
+            alert_after: 3,
+            origin: Default::default(),
+        }];
+        let next = vec![
+            service("kept", "http://localhost:1001"),
+            moved,
+            retuned,
+            service("fresh", "http://localhost:1005"),
+        ];
+
+        let plan = plan_reload(&current, &next);
+        assert_eq!(plan.added, vec!["fresh"]);
+        assert_eq!(plan.removed, vec!["gone"]);
+        assert_eq!(plan.updated, vec!["moved", "retuned"]);
+        assert_eq!(plan.restarted, vec!["moved"]);
+        assert_eq!(plan.rescheduled, vec!["moved", "retuned", "fresh"]);
+
+        assert!(plan_reload(&next, &next).is_empty());
+    }
+
+    #[test]
+    fn fingerprint_tracks_config_and_openapi_files() {
+        let root = std::env::temp_dir().join(format!("rustrunner-reload-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("svc").join("config")).expect("create service dir");
+        fs::write(root.join("svc/config/service.json"), "{}").expect("write config");
+
+        let before = services_fingerprint(&root);
+        assert_eq!(before.len(), 2);
+
+        fs::write(root.join("svc/openapi.json"), "{ \"paths\": {} }").expect("write openapi");
+        let after = services_fingerprint(&root);
+        let _ = fs::remove_dir_all(&root);
+
+        assert_ne!(before, after);
+    }
+}
@@ -0,0 +1,277 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param {
+        prefix: String,
+        name: String,
+        suffix: String,
+    },
+}
+
+impl Segment {
+    fn parse(raw: &str, template: &str) -> Result<Self> {
+        let Some(open) = raw.find('{') else {
+            if raw.contains('}') {
+                bail!("path template '/{template}' has an unbalanced '}}' in segment '{raw}'");
+            }
+            return Ok(Segment::Literal(raw.to_string()));
+        };
+
+        let Some(close) = raw[open..].find('}').map(|offset| open + offset) else {
+            bail!("path template '/{template}' has an unclosed '{{' in segment '{raw}'");
+        };
+
+        let name = raw[open + 1..close].trim();
+        if name.is_empty() {
+            bail!("path template '/{template}' declares an empty parameter name");
+        }
+
+        let suffix = &raw[close + 1..];
+        if suffix.contains('{') || suffix.contains('}') {
+            bail!(
+                "path template '/{template}' declares more than one parameter in segment '{raw}'"
+            );
+        }
+
+        Ok(Segment::Param {
+            prefix: raw[..open].to_string(),
+            name: name.to_string(),
+            suffix: suffix.to_string(),
+        })
+    }
+
+    fn capture<'p>(&self, value: &'p str) -> Option<Option<&'p str>> {
+        match self {
+            Segment::Literal(literal) => (literal == value).then_some(None),
+            Segment::Param { prefix, suffix, .. } => {
+                let inner = value.strip_prefix(prefix.as_str())?;
+                let inner = inner.strip_suffix(suffix.as_str())?;
+                (!inner.is_empty()).then_some(Some(inner))
+            }
+        }
+    }
+
+    fn shape(&self) -> (&str, bool, &str) {
+        match self {
+            Segment::Literal(literal) => (literal.as_str(), false, ""),
+            Segment::Param { prefix, suffix, .. } => (prefix.as_str(), true, suffix.as_str()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoutePattern {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl RoutePattern {
+    pub fn parse(path: &str) -> Result<Self> {
+        let template = path.trim_matches('/').to_string();
+        if template.is_empty() {
+            bail!("path template cannot be empty");
+        }
+
+        let segments = template
+            .split('/')
+            .map(|segment| {
+                if segment.is_empty() {
+                    bail!("path template '/{template}' contains an empty segment");
+                }
+                Segment::parse(segment, &template)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { template, segments })
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    fn matches(&self, parts: &[&str]) -> Option<BTreeMap<String, String>> {
+        if parts.len() != self.segments.len() {
+            return None;
+        }
+
+        let mut params = BTreeMap::new();
+        for (segment, part) in self.segments.iter().zip(parts) {
+            if let Some(value) = segment.capture(part)? {
+                if let Segment::Param { name, .. } = segment {
+                    params.insert(name.clone(), value.to_string());
+                }
+            }
+        }
+
+        Some(params)
+    }
+
+    fn is_equivalent(&self, other: &RoutePattern) -> bool {
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(&other.segments)
+                .all(|(a, b)| a.shape() == b.shape())
+    }
+
+    // Literal segments take precedence over templated ones, compared from left
+    // to right, so `/accounts/me` always wins over `/accounts/{accountId}`.
+    fn precedence(&self, other: &RoutePattern) -> Ordering {
+        for (a, b) in self.segments.iter().zip(&other.segments) {
+            let rank = |segment: &Segment| match segment {
+                Segment::Literal(_) => 0,
+                Segment::Param { prefix, suffix, .. }
+                    if !prefix.is_empty() || !suffix.is_empty() =>
+                {
+                    1
+                }
+                Segment::Param { .. } => 2,
+            };
+            match rank(a).cmp(&rank(b)) {
+                Ordering::Equal => continue,
+                other => return other,
+            }
+        }
+
+        self.segments
+            .len()
+            .cmp(&other.segments.len())
+            .then_with(|| self.template.cmp(&other.template))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteMatch<'a> {
+    pub template: &'a str,
+    pub params: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RouteTable {
+    routes: Vec<RoutePattern>,
+}
+
+impl RouteTable {
+    pub fn insert(&mut self, pattern: RoutePattern) -> Result<()> {
+        if let Some(existing) = self
+            .routes
+            .iter()
+            .find(|route| route.is_equivalent(&pattern))
+        {
+            if existing.template == pattern.template {
+                return Ok(());
+            }
+            bail!(
+                "path templates '/{}' and '/{}' are ambiguous",
+                existing.template,
+                pattern.template
+            );
+        }
+
+        let position = self
+            .routes
+            .iter()
+            .position(|route| pattern.precedence(route) == Ordering::Less)
+            .unwrap_or(self.routes.len());
+        self.routes.insert(position, pattern);
+        Ok(())
+    }
+
+    pub fn resolve(&self, path: &str) -> Option<RouteMatch<'_>> {
+        let trimmed = path.trim_matches('/');
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let parts: Vec<&str> = trimmed.split('/').collect();
+        self.routes.iter().find_map(|route| {
+            route.matches(&parts).map(|params| RouteMatch {
+                template: route.template(),
+                params,
+            })
+        })
+    }
+
+    pub fn templates(&self) -> impl Iterator<Item = &str> {
+        self.routes.iter().map(RoutePattern::template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(paths: &[&str]) -> RouteTable {
+        let mut table = RouteTable::default();
+        for path in paths {
+            table
+                .insert(RoutePattern::parse(path).expect("parse pattern"))
+                .expect("insert pattern");
+        }
+        table
+    }
+
+    #[test]
+    fn matches_templated_segments_and_extracts_params() {
+        let routes = table(&["/accounts/{accountId}", "/accounts/{accountId}/holders"]);
+
+        let matched = routes.resolve("accounts/123").expect("match");
+        assert_eq!(matched.template, "accounts/{accountId}");
+        assert_eq!(matched.params["accountId"], "123");
+
+        let nested = routes.resolve("/accounts/42/holders/").expect("match");
+        assert_eq!(nested.template, "accounts/{accountId}/holders");
+        assert_eq!(nested.params["accountId"], "42");
+
+        assert!(routes.resolve("accounts").is_none());
+        assert!(routes.resolve("accounts/42/limits").is_none());
+    }
+
+    #[test]
+    fn literal_segments_win_over_templates() {
+        let routes = table(&["/accounts/{accountId}", "/accounts/me", "/{kind}/me"]);
+
+        let matched = routes.resolve("accounts/me").expect("match");
+        assert_eq!(matched.template, "accounts/me");
+        assert!(matched.params.is_empty());
+
+        assert_eq!(
+            routes.resolve("accounts/7").unwrap().template,
+            "accounts/{accountId}"
+        );
+        assert_eq!(routes.resolve("users/me").unwrap().template, "{kind}/me");
+    }
+
+    #[test]
+    fn supports_parameters_with_literal_affixes() {
+        let routes = table(&["/reports/{reportId}.json", "/reports/{reportId}"]);
+
+        let matched = routes.resolve("reports/q1.json").expect("match");
+        assert_eq!(matched.template, "reports/{reportId}.json");
+        assert_eq!(matched.params["reportId"], "q1");
+
+        assert_eq!(
+            routes.resolve("reports/q1").unwrap().template,
+            "reports/{reportId}"
+        );
+    }
+
+    #[test]
+    fn rejects_ambiguous_and_malformed_templates() {
+        let mut routes = table(&["/accounts/{accountId}"]);
+        let error = routes
+            .insert(RoutePattern::parse("/accounts/{id}").unwrap())
+            .unwrap_err();
+        assert!(error.to_string().contains("ambiguous"));
+
+        assert!(RoutePattern::parse("/accounts/{").is_err());
+        assert!(RoutePattern::parse("/accounts/{}").is_err());
+        assert!(RoutePattern::parse("/accounts//holders").is_err());
+    }
+}
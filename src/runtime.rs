@@ -0,0 +1,328 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::config::{self, RuntimeConfig};
+
+// WASM pages are 64 KiB.
+const WASM_PAGE_BYTES: u64 = 64 * 1024;
+
+// Everything that differs between the programs able to run a service's module.
+// `module_command` puts the pieces together in a fixed order: base command,
+// capability flags, memory limit, environment and finally the entry point.
+pub trait ModuleRuntime: Send + Sync {
+    fn name(&self) -> &str;
+
+    // The file that is run: the compiled `.wasm` for WASI runtimes.
+    fn entry_point(&self, services_dir: &Path, module_name: &str) -> Result<PathBuf> {
+        wasm_entry_point(services_dir, module_name)
+    }
+
+    fn base_command(&self, entry_point: &Path) -> Command;
+
+    // Flags the guest needs to serve HTTP.
+    fn capability_flags(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Returns false when the runtime cannot enforce the limit.
+    fn limit_memory(&self, command: &mut Command, pages: u32) -> bool;
+
+    fn inject_env(&self, command: &mut Command, env: &[(String, String)]);
+
+    fn finish_command(&self, command: &mut Command, entry_point: &Path) {
+        command.arg(entry_point);
+    }
+}
+
+pub fn module_runtime(config: &RuntimeConfig) -> Box<dyn ModuleRuntime> {
+    match config {
+        RuntimeConfig::WasmEdge => Box::new(WasmEdgeRuntime),
+        RuntimeConfig::Wasi {
+            command,
+            args,
+            capability_args,
+            memory_args,
+        } => Box::new(WasiCliRuntime {
+            command: command.clone(),
+            args: args.clone(),
+            capability_args: capability_args.clone(),
+            memory_args: memory_args.clone(),
+        }),
+        RuntimeConfig::Native { command, args } => Box::new(NativeRuntime {
+            command: command.clone(),
+            args: args.clone(),
+        }),
+    }
+}
+
+pub fn module_command(
+    runtime: &dyn ModuleRuntime,
+    services_dir: &Path,
+    module_name: &str,
+    memory_page_limit: Option<u32>,
+    env: &[(String, String)],
+) -> Result<Command> {
+    let entry_point = runtime.entry_point(services_dir, module_name)?;
+    let mut command = runtime.base_command(&entry_point);
+    command.args(runtime.capability_flags());
+    if let Some(pages) = memory_page_limit {
+        if !runtime.limit_memory(&mut command, pages) {
+            eprintln!(
+                "warning: the {} runtime cannot enforce memory_limit_mb for '{module_name}'",
+                runtime.name()
+            );
+        }
+    }
+    runtime.inject_env(&mut command, env);
+    runtime.finish_command(&mut command, &entry_point);
+    Ok(command)
+}
+
+fn wasm_entry_point(services_dir: &Path, module_name: &str) -> Result<PathBuf> {
+    let wasm_path = config::module_wasm_path(services_dir, module_name);
+
+    if !wasm_path.exists() {
+        return Err(anyhow!(
+            "WebAssembly module '{}' was not found at {}",
+            module_name,
+            wasm_path.display()
+        ));
+    }
+
+    wasm_path
+        .canonicalize()
+        .with_context(|| format!("failed to canonicalize module path for '{}'", module_name))
+}
+
+// WASI guests do not inherit the host environment, so every variable is
+// handed over as a flag.
+fn env_flags(command: &mut Command, env: &[(String, String)]) {
+    for (key, value) in env {
+        command.arg("--env");
+        command.arg(format!("{key}={value}"));
+    }
+}
+
+pub struct WasmEdgeRuntime;
+
+impl ModuleRuntime for WasmEdgeRuntime {
+    fn name(&self) -> &str {
+        "wasmedge"
+    }
+
+    // WasmEdge enables its socket extension by default.
+    fn base_command(&self, _entry_point: &Path) -> Command {
+        Command::new("wasmedge")
+    }
+
+    fn limit_memory(&self, command: &mut Command, pages: u32) -> bool {
+        command.arg("--memory-page-limit");
+        command.arg(pages.to_string());
+        true
+    }
+
+    fn inject_env(&self, command: &mut Command, env: &[(String, String)]) {
+        env_flags(command, env);
+    }
+}
+
+pub struct WasiCliRuntime {
+    command: String,
+    args: Vec<String>,
+    capability_args: Vec<String>,
+    memory_args: Vec<String>,
+}
+
+impl ModuleRuntime for WasiCliRuntime {
+    fn name(&self) -> &str {
+        &self.command
+    }
+
+    fn base_command(&self, _entry_point: &Path) -> Command {
+        Command::new(&self.command)
+    }
+
+    fn capability_flags(&self) -> Vec<String> {
+        self.capability_args.clone()
+    }
+
+    fn limit_memory(&self, command: &mut Command, pages: u32) -> bool {
+        let bytes = (u64::from(pages) * WASM_PAGE_BYTES).to_string();
+        for arg in &self.memory_args {
+            command.arg(
+                arg.replace("{bytes}", &bytes)
+                    .replace("{pages}", &pages.to_string()),
+            );
+        }
+        !self.memory_args.is_empty()
+    }
+
+    fn inject_env(&self, command: &mut Command, env: &[(String, String)]) {
+        env_flags(command, env);
+    }
+
+    fn finish_command(&self, command: &mut Command, entry_point: &Path) {
+        command.args(&self.args);
+        command.arg(entry_point);
+    }
+}
+
+pub struct NativeRuntime {
+    command: String,
+    args: Vec<String>,
+}
+
+impl ModuleRuntime for NativeRuntime {
+    fn name(&self) -> &str {
+        "native"
+    }
+
+    fn entry_point(&self, services_dir: &Path, module_name: &str) -> Result<PathBuf> {
+        let command = Path::new(&self.command);
+        if command.components().count() == 1 && !command.is_absolute() {
+            return Ok(command.to_path_buf());
+        }
+
+        let path = services_dir.join(module_name).join(command);
+        path.canonicalize().with_context(|| {
+            format!(
+                "native binary for service '{}' was not found at {}",
+                module_name,
+                path.display()
+            )
+        })
+    }
+
+    fn base_command(&self, entry_point: &Path) -> Command {
+        let mut command = Command::new(entry_point);
+        command.args(&self.args);
+        command
+    }
+
+    fn limit_memory(&self, _command: &mut Command, _pages: u32) -> bool {
+        false
+    }
+
+    // Host processes read real environment variables, on top of the runner's own.
+    fn inject_env(&self, command: &mut Command, env: &[(String, String)]) {
+        command.envs(env.iter().map(|(key, value)| (key, value)));
+    }
+
+    fn finish_command(&self, _command: &mut Command, _entry_point: &Path) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeRuntime;
+
+    impl ModuleRuntime for FakeRuntime {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn entry_point(&self, _services_dir: &Path, module_name: &str) -> Result<PathBuf> {
+            Ok(PathBuf::from(format!("{module_name}.fake")))
+        }
+
+        fn base_command(&self, _entry_point: &Path) -> Command {
+            Command::new("fake-runtime")
+        }
+
+        fn capability_flags(&self) -> Vec<String> {
+            vec!["--net".into()]
+        }
+
+        fn limit_memory(&self, command: &mut Command, pages: u32) -> bool {
+            command.arg(format!("--pages={pages}"));
+            true
+        }
+
+        fn inject_env(&self, command: &mut Command, env: &[(String, String)]) {
+            for (key, value) in env {
+                command.arg(format!("--var={key}:{value}"));
+            }
+        }
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn assembles_the_command_in_a_fixed_order() {
+        let env = vec![("WR_RUNNER_PORT".to_string(), "15001".to_string())];
+        let command = module_command(&FakeRuntime, Path::new("."), "svc", Some(32), &env)
+            .expect("fake command");
+
+        assert_eq!(command.get_program(), "fake-runtime");
+        assert_eq!(
+            args(&command),
+            vec![
+                "--net",
+                "--pages=32",
+                "--var=WR_RUNNER_PORT:15001",
+                "svc.fake"
+            ]
+        );
+    }
+
+    #[test]
+    fn native_and_wasi_runtimes_pass_env_their_own_way() {
+        let env = vec![("MODE".to_string(), "test".to_string())];
+        let native = module_runtime(&RuntimeConfig::Native {
+            command: "sh".into(),
+            args: vec!["-c".into(), "exit 0".into()],
+        });
+        let command =
+            module_command(native.as_ref(), Path::new("."), "svc", Some(16), &env).expect("sh");
+        assert_eq!(command.get_program(), "sh");
+        assert_eq!(args(&command), vec!["-c", "exit 0"]);
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            vec![(
+                std::ffi::OsStr::new("MODE"),
+                Some(std::ffi::OsStr::new("test"))
+            )]
+        );
+
+        let mut wasi = Command::new("wasmtime");
+        let runtime = WasiCliRuntime {
+            command: "wasmtime".into(),
+            args: vec!["--dir=.".into()],
+            capability_args: Vec::new(),
+            memory_args: vec!["-W".into(), "max-memory-size={bytes}".into()],
+        };
+        assert!(runtime.limit_memory(&mut wasi, 16));
+        runtime.inject_env(&mut wasi, &env);
+        runtime.finish_command(&mut wasi, Path::new("svc.wasm"));
+        assert_eq!(
+            args(&wasi),
+            vec![
+                "-W",
+                "max-memory-size=1048576",
+                "--env",
+                "MODE=test",
+                "--dir=.",
+                "svc.wasm"
+            ]
+        );
+
+        let mut other = Command::new("wasmer");
+        let runtime = WasiCliRuntime {
+            command: "wasmer".into(),
+            args: Vec::new(),
+            capability_args: vec!["--net".into()],
+            memory_args: Vec::new(),
+        };
+        assert_eq!(runtime.capability_flags(), vec!["--net"]);
+        assert!(!runtime.limit_memory(&mut other, 16));
+        assert_eq!(args(&other), Vec::<String>::new());
+    }
+}
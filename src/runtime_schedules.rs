@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use serde_json::Value;
+
+use crate::catalog::{begin_catalog_write, ServiceCatalog};
+use crate::config::{
+    check_service_schedules, parse_service_schedule, schedule_to_value, write_service_schedules,
+    ScheduleOrigin, Service, ServiceSchedule, SCHEDULE_KEYS,
+};
+use crate::scheduler::apply_service_schedules;
+use crate::server::RunnerState;
+
+// A new interval or cron replaces the whole trigger; a lone `timezone` only
+// moves an existing cron expression.
+const TRIGGER_KEYS: [&str; 5] = ["interval_secs", "interval", "seconds", "every_secs", "cron"];
+
+// For every service changed through the API, the schedules its service.json
+// declared when the first change was made. Runtime changes live in the
+// catalog until the runner restarts, unless they are written back.
+pub type SharedScheduleOverrides = Arc<Mutex<HashMap<String, Vec<ServiceSchedule>>>>;
+
+#[derive(Debug)]
+pub enum ScheduleEdit {
+    Create(Value),
+    Update(usize, Value),
+    Delete(usize),
+}
+
+#[derive(Debug)]
+pub enum ScheduleEditError {
+    ServiceNotFound,
+    ScheduleNotFound,
+    Invalid(anyhow::Error),
+    Failed(anyhow::Error),
+}
+
+pub fn initialize_schedule_overrides() -> SharedScheduleOverrides {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+// Validated against the service's OpenAPI exactly like service.json, then
+// swapped into the catalog and the scheduler. With `persist` the whole list is
+// written back to service.json and stops counting as a runtime change.
+pub fn edit_service_schedules(
+    state: &RunnerState,
+    service_name: &str,
+    edit: ScheduleEdit,
+    persist: bool,
+) -> Result<Vec<ServiceSchedule>, ScheduleEditError> {
+    let mut catalog = begin_catalog_write(&state.catalog);
+    let mut overrides = state
+        .schedule_overrides
+        .lock()
+        .map_err(|_| ScheduleEditError::Failed(anyhow!("schedule overrides are unavailable")))?;
+    let mut services = catalog.snapshot().services().to_vec();
+    let service = services
+        .iter_mut()
+        .find(|service| service.name == service_name)
+        .ok_or(ScheduleEditError::ServiceNotFound)?;
+    let declared = overrides
+        .get(service_name)
+        .cloned()
+        .unwrap_or_else(|| service.schedules.clone());
+
+    apply_edit(service, edit)?;
+    check_service_schedules(service).map_err(ScheduleEditError::Invalid)?;
+
+    if persist {
+        write_service_schedules(
+            &state.settings.services_dir,
+            service_name,
+            &service.schedules,
+        )
+        .map_err(ScheduleEditError::Failed)?;
+        for schedule in &mut service.schedules {
+            schedule.origin = ScheduleOrigin::Config;
+        }
+        overrides.remove(service_name);
+    } else if service.schedules == declared {
+        overrides.remove(service_name);
+    } else {
+        overrides.insert(service_name.to_string(), declared);
+    }
+
+    let service = service.clone();
+    let next_catalog = ServiceCatalog::build(services).map_err(ScheduleEditError::Failed)?;
+    catalog.replace(next_catalog);
+    apply_service_schedules(&state.schedules, &service);
+    Ok(service.schedules)
+}
+
+fn apply_edit(service: &mut Service, edit: ScheduleEdit) -> Result<(), ScheduleEditError> {
+    match edit {
+        ScheduleEdit::Create(value) => {
+            // `{"/sync": 60}` is the one-key shorthand service.json also accepts.
+            if let Value::Object(fields) = &value {
+                if fields.len() > 1
+                    || fields
+                        .keys()
+                        .any(|key| SCHEDULE_KEYS.contains(&key.as_str()))
+                {
+                    reject_unknown_keys(fields)?;
+                }
+            }
+            let mut schedule =
+                parse_service_schedule(&service.name, value).map_err(ScheduleEditError::Invalid)?;
+            schedule.origin = ScheduleOrigin::Added;
+            service.schedules.push(schedule);
+        }
+        ScheduleEdit::Update(index, patch) => {
+            let current = service
+                .schedules
+                .get(index)
+                .ok_or(ScheduleEditError::ScheduleNotFound)?;
+            let Value::Object(patch) = patch else {
+                return Err(ScheduleEditError::Invalid(anyhow!(
+                    "schedule update must be a JSON object"
+                )));
+            };
+
+            reject_unknown_keys(&patch)?;
+
+            let mut merged = schedule_to_value(current);
+            if TRIGGER_KEYS.iter().any(|key| patch.contains_key(*key)) {
+                for key in TRIGGER_KEYS.iter().chain(&["timezone"]) {
+                    merged.remove(*key);
+                }
+            }
+            // Switching between calling an endpoint and publishing to a queue
+            // drops the options that only apply to the other kind.
+            if patch.contains_key("queue") {
+                for key in ["endpoint", "path", "method", "headers", "target"] {
+                    merged.remove(key);
+                }
+            } else if patch.contains_key("endpoint") || patch.contains_key("path") {
+                merged.remove("queue");
+            }
+            merged.extend(patch);
+
+            let mut schedule = parse_service_schedule(&service.name, Value::Object(merged))
+                .map_err(ScheduleEditError::Invalid)?;
+            schedule.origin = match current.origin {
+                ScheduleOrigin::Added => ScheduleOrigin::Added,
+                _ => ScheduleOrigin::Edited,
+            };
+            service.schedules[index] = schedule;
+        }
+        ScheduleEdit::Delete(index) => {
+            if index >= service.schedules.len() {
+                return Err(ScheduleEditError::ScheduleNotFound);
+            }
+            service.schedules.remove(index);
+        }
+    }
+    Ok(())
+}
+
+// service.json ignores keys it does not know, but through the API a typo
+// would otherwise report success and change nothing.
+fn reject_unknown_keys(fields: &serde_json::Map<String, Value>) -> Result<(), ScheduleEditError> {
+    match fields
+        .keys()
+        .find(|key| !SCHEDULE_KEYS.contains(&key.as_str()))
+    {
+        Some(key) => Err(ScheduleEditError::Invalid(anyhow!(
+            "unknown schedule field '{key}'"
+        ))),
+        None => Ok(()),
+    }
+}
+
+// Runtime changes survive a reload as long as service.json still declares the
+// schedules they were made on; once the file changes them, the file wins.
+pub fn carry_runtime_schedules(
+    overrides: &SharedScheduleOverrides,
+    current: &[Service],
+    next: &mut [Service],
+) {
+    let Ok(mut overrides) = overrides.lock() else {
+        return;
+    };
+
+    overrides.retain(|name, declared| {
+        let Some(service) = next.iter_mut().find(|service| &service.name == name) else {
+            return false;
+        };
+        let Some(live) = current.iter().find(|service| &service.name == name) else {
+            return false;
+        };
+        if service.schedules != *declared {
+            eprintln!(
+                "Discarding runtime schedule changes for service '{name}': its service.json changed the schedules"
+            );
+            return false;
+        }
+
+        let from_file = std::mem::replace(&mut service.schedules, live.schedules.clone());
+        if let Err(error) = check_service_schedules(service) {
+            eprintln!("Discarding runtime schedule changes for service '{name}': {error:#}");
+            service.schedules = from_file;
+            return false;
+        }
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::config::ServiceKind;
+    use crate::cron::ScheduleTrigger;
+    use crate::openapi::OperationTable;
+
+    fn service(schedules: Vec<ServiceSchedule>) -> Service {
+        Service {
+            kind: ServiceKind::Adapter,
+            operations: OperationTable::from_document(&json!({
+                "paths": { "/sync": { "get": {}, "post": {} } }
+            }))
+            .expect("operations"),
+            schedules,
+            ..Service::for_test("sap")
+        }
+    }
+
+    #[test]
+    fn edits_schedules_and_keeps_the_origin() {
+        let declared = parse_service_schedule(
+            "sap",
+            json!({"endpoint": "/sync", "cron": "0 2 * * *", "timezone": "Europe/Madrid"}),
+        )
+        .expect("declared schedule");
+        let mut live = service(vec![declared.clone()]);
+
+        apply_edit(
+            &mut live,
+            ScheduleEdit::Update(0, json!({"interval_secs": 30})),
+        )
+        .expect("retime");
+        apply_edit(
+            &mut live,
+            ScheduleEdit::Create(
+                json!({"endpoint": "/sync", "method": "POST", "interval_secs": 5}),
+            ),
+        )
+        .expect("create");
+        apply_edit(
+            &mut live,
+            ScheduleEdit::Update(1, json!({"jitter_secs": 2})),
+        )
+        .expect("update");
+        assert_eq!(
+            live.schedules[0].trigger,
+            ScheduleTrigger::Interval { secs: 30 }
+        );
+        assert_eq!(live.schedules[0].origin, ScheduleOrigin::Edited);
+        assert_eq!(live.schedules[1].origin, ScheduleOrigin::Added);
+        assert_eq!(live.schedules[1].jitter_secs, 2);
+        assert!(matches!(
+            apply_edit(&mut live, ScheduleEdit::Delete(5)),
+            Err(ScheduleEditError::ScheduleNotFound)
+        ));
+        assert!(matches!(
+            apply_edit(
+                &mut live,
+                ScheduleEdit::Update(0, json!({"interval_secs": 0}))
+            ),
+            Err(ScheduleEditError::Invalid(_))
+        ));
+        let before = live.schedules.clone();
+        for edit in [
+            ScheduleEdit::Update(1, json!({"interval_sec": 5})),
+            ScheduleEdit::Create(json!({"endpoint": "/sync", "interval_secs": 5, "jiter_secs": 1})),
+        ] {
+            match apply_edit(&mut live, edit) {
+                Err(ScheduleEditError::Invalid(error)) => {
+                    assert!(error.to_string().starts_with("unknown schedule field"))
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+        assert_eq!(live.schedules, before);
+
+        // A reload keeps the runtime list while service.json is unchanged and
+        // drops it once the file declares something else.
+        let overrides = initialize_schedule_overrides();
+        overrides
+            .lock()
+            .unwrap()
+            .insert("sap".into(), vec![declared.clone()]);
+        let mut next = vec![service(vec![declared.clone()])];
+        carry_runtime_schedules(&overrides, &[live.clone()], &mut next);
+        assert_eq!(next[0].schedules, live.schedules);
+
+        let mut changed = declared;
+        changed.jitter_secs = 60;
+        let mut next = vec![service(vec![changed.clone()])];
+        carry_runtime_schedules(&overrides, &[live], &mut next);
+        assert_eq!(next[0].schedules, vec![changed]);
+        assert!(overrides.lock().unwrap().is_empty());
+    }
+}
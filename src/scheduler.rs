@@ -1,24 +1,187 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::config::{Service, ServiceSchedule};
+use chrono::{DateTime, Utc};
+use serde_json::json;
 
-pub const SCHEDULE_LOOP_TICK_SECS: u64 = 1;
-pub const SCHEDULE_REQUEST_TIMEOUT_SECS: u64 = 5;
+use crate::alerts::{AlertKind, AlertSink, ScheduleAlert};
+use crate::config::{CatchUpPolicy, ConcurrencyPolicy, ScheduleTarget, Service, ServiceSchedule};
+use crate::cron::ScheduleTrigger;
+use crate::health::{HealthStatus, ServiceHealth, SharedHealthMap};
+use crate::history::{
+    restore_schedules, truncate_body, PersistedSchedule, ScheduleHistoryStore, ScheduleRun,
+    MAX_RESPONSE_BODY_BYTES,
+};
+use crate::process::{ready_instances, SharedModuleRegistry};
+use crate::queue::{publish_message, SharedQueueRegistry};
+use crate::settings::RunnerSettings;
+use crate::webhook::{render_queue_message, render_schedule_request, FireContext, RenderedRequest};
 
-#[derive(Clone, Debug, Default)]
+// The timer wakes up at least this often so wall-clock jumps are noticed.
+const MAX_TIMER_SLEEP_SECS: u64 = 30;
+const PENDING_RUNS_PER_WORKER: usize = 64;
+const MAX_CATCH_UP_RUNS: usize = 10;
+const MAX_MISSED_SCAN: usize = 10_000;
+
+// One entry per webhook call: the fire time it stands for, or `None` when it
+// was launched by hand.
+type Fire = Option<DateTime<Utc>>;
+
+#[derive(Clone, Debug)]
 pub struct ScheduleState {
-    pub endpoint: String,
-    pub interval_secs: u64,
+    pub schedule: ServiceSchedule,
+    endpoints: Vec<String>,
+    // Where `round_robin` and `any_healthy` pick up on the next call.
+    cursor: usize,
+    pub generation: u64,
     pub paused: bool,
-    pub last_run: Option<Instant>,
-    pub last_status: Option<u16>,
-    pub last_error: Option<String>,
+    pub next_run: Option<DateTime<Utc>>,
+    // The fire time the armed timer stands for, before jitter. It is kept
+    // while paused so catch-up can replay it on resume.
+    pending: Option<DateTime<Utc>>,
+    // Sequence number of the live timer entry; 0 when none is armed.
+    timer: u64,
+    pub running: usize,
+    queued: Option<Vec<Fire>>,
+    // Runs in a row that failed after their last retry.
+    pub failures: u32,
+    // Newest first, capped at the store's history limit.
+    pub runs: Vec<ScheduleRun>,
+}
+
+impl ScheduleState {
+    pub fn last_run(&self) -> Option<&ScheduleRun> {
+        self.runs.first()
+    }
+
+    pub fn has_queued_run(&self) -> bool {
+        self.queued.is_some()
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn failing(&self) -> bool {
+        self.schedule.alert_after > 0 && self.failures >= self.schedule.alert_after
+    }
+
+    // `any_healthy` prefers instances that passed their last health check,
+    // falls back to the ones not checked yet, and picks nothing when every
+    // instance is down. `ready` is `None` when the runner does not manage the
+    // service's modules; otherwise only the listed instances can be picked.
+    fn pick_targets(&mut self, health: &ServiceHealth, ready: Option<&[usize]>) -> Vec<usize> {
+        let count = self.endpoints.len();
+        let is_ready = |index: &usize| ready.is_none_or(|ready| ready.contains(index));
+        let rotation: Vec<usize> = (0..count)
+            .map(|offset| (self.cursor + offset) % count)
+            .filter(is_ready)
+            .collect();
+        let chosen = match self.schedule.target {
+            ScheduleTarget::Broadcast => return (0..count).filter(is_ready).collect(),
+            ScheduleTarget::RoundRobin => rotation.first().copied(),
+            ScheduleTarget::AnyHealthy => [HealthStatus::Healthy, HealthStatus::Unknown]
+                .iter()
+                .find_map(|wanted| {
+                    rotation
+                        .iter()
+                        .copied()
+                        .find(|index| health.instance(*index) == *wanted)
+                }),
+        };
+        if let Some(index) = chosen {
+            self.cursor = index + 1;
+        }
+        chosen.into_iter().collect()
+    }
+}
+
+// One timer thread sleeps until the earliest entry in `timers` is due and
+// hands the run to a fixed pool of workers, so the number of threads no longer
+// grows with the number of schedules.
+pub struct Scheduler {
+    inner: Mutex<SchedulerInner>,
+    wakeup: Condvar,
+    jobs: SyncSender<ScheduleJob>,
+    store: ScheduleHistoryStore,
+    // Sequence of the last history written for each service.
+    saved: Mutex<HashMap<String, u64>>,
+    health: SharedHealthMap,
+    modules: SharedModuleRegistry,
+    queues: SharedQueueRegistry,
+    alerts: AlertSink,
+    request_timeout: Duration,
 }
 
-pub type SharedScheduleMap = Arc<Mutex<HashMap<String, Vec<ScheduleState>>>>;
+pub type SharedScheduler = Arc<Scheduler>;
+
+#[derive(Default)]
+struct SchedulerInner {
+    states: HashMap<String, Vec<ScheduleState>>,
+    timers: BinaryHeap<Reverse<TimerEntry>>,
+    next_timer: u64,
+    // Retries waiting for their backoff, keyed by the sequence number of
+    // their timer entry.
+    retries: HashMap<u64, ScheduleJob>,
+    // Jobs a worker has taken and not finished yet.
+    active_runs: usize,
+    // Set on shutdown: no more fires, retries or new runs.
+    stopping: bool,
+    next_history: u64,
+}
+
+// Taken with the scheduler locked and written once it is released. The
+// sequence keeps a slower write from replacing a newer history.
+struct HistorySnapshot {
+    service_name: String,
+    sequence: u64,
+    schedules: Vec<PersistedSchedule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct ScheduleSlot {
+    service_name: String,
+    index: usize,
+    generation: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct TimerEntry {
+    due: DateTime<Utc>,
+    seq: u64,
+    slot: ScheduleSlot,
+}
+
+#[derive(Debug)]
+struct ScheduleJob {
+    slot: ScheduleSlot,
+    fires: Vec<Fire>,
+    // The attempt the first fire is on; the others always start at 1.
+    attempt: u32,
+    // Set when a `broadcast` retry only calls the instances that failed.
+    only: Option<Vec<usize>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Retry {
+    after: Duration,
+    only: Option<Vec<usize>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Claim {
+    Run,
+    Queued,
+    Skipped,
+}
 
 #[derive(Debug)]
 pub enum ToggleError {
@@ -27,53 +190,371 @@ pub enum ToggleError {
     LockPoisoned,
 }
 
-pub fn start_webhook_schedulers(services: &[Service]) -> SharedScheduleMap {
-    let schedule_map: SharedScheduleMap = Arc::new(Mutex::new(HashMap::new()));
+static NEXT_SCHEDULE_GENERATION: AtomicU64 = AtomicU64::new(1);
 
-    {
-        if let Ok(mut guard) = schedule_map.lock() {
-            for service in services {
-                if service.schedules.is_empty() {
-                    continue;
-                }
+impl ScheduleJob {
+    fn new(slot: ScheduleSlot, fires: Vec<Fire>) -> Self {
+        Self {
+            slot,
+            fires,
+            attempt: 1,
+            only: None,
+        }
+    }
+}
+
+impl Scheduler {
+    fn new(
+        store: ScheduleHistoryStore,
+        health: SharedHealthMap,
+        modules: SharedModuleRegistry,
+        queues: SharedQueueRegistry,
+        alerts: AlertSink,
+        request_timeout: Duration,
+        workers: usize,
+    ) -> (SharedScheduler, Receiver<ScheduleJob>) {
+        let (jobs, receiver) = mpsc::sync_channel(workers * PENDING_RUNS_PER_WORKER);
+        let scheduler = Arc::new(Scheduler {
+            inner: Mutex::default(),
+            wakeup: Condvar::new(),
+            jobs,
+            store,
+            saved: Mutex::default(),
+            health,
+            modules,
+            queues,
+            alerts,
+            request_timeout,
+        });
+        (scheduler, receiver)
+    }
+
+    fn lock(&self) -> Option<MutexGuard<'_, SchedulerInner>> {
+        self.inner.lock().ok()
+    }
+
+    fn persist(&self, snapshot: Option<HistorySnapshot>) {
+        let Some(snapshot) = snapshot else {
+            return;
+        };
+        let Ok(mut saved) = self.saved.lock() else {
+            return;
+        };
+        if saved
+            .get(&snapshot.service_name)
+            .is_some_and(|sequence| *sequence > snapshot.sequence)
+        {
+            return;
+        }
+
+        if let Err(error) = self.store.save(&snapshot.service_name, &snapshot.schedules) {
+            eprintln!(
+                "Failed to persist schedule state for service '{}': {error:#}",
+                snapshot.service_name
+            );
+        }
+        saved.insert(snapshot.service_name, snapshot.sequence);
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, Vec<ScheduleState>> {
+        self.lock()
+            .map(|inner| inner.states.clone())
+            .unwrap_or_default()
+    }
 
-                let entries = service
-                    .schedules
-                    .iter()
-                    .map(|schedule| ScheduleState {
-                        endpoint: schedule.endpoint.clone(),
-                        interval_secs: schedule.interval_secs,
-                        paused: false,
-                        last_run: None,
-                        last_status: None,
-                        last_error: None,
-                    })
-                    .collect();
-
-                guard.insert(service.name.clone(), entries);
+    // Entries left behind by a reload, a pause or a re-arm no longer match
+    // their slot and are simply dropped.
+    fn fire_timer(&self, inner: &mut SchedulerInner, entry: TimerEntry, now: DateTime<Utc>) {
+        if let Some(job) = inner.retries.remove(&entry.seq) {
+            if inner.state_mut(&job.slot).is_some() {
+                self.dispatch(inner, job);
             }
+            return;
+        }
+        let Some(state) = inner
+            .state_mut(&entry.slot)
+            .filter(|state| state.timer == entry.seq)
+        else {
+            return;
+        };
+        let Some(scheduled) = state.pending else {
+            return;
+        };
+
+        let (fires, following) = due_fires(
+            &state.schedule.trigger,
+            scheduled,
+            now,
+            state.schedule.catch_up,
+        );
+        state.pending = following;
+        let fires: Vec<Fire> = fires.into_iter().map(Some).collect();
+        let claim = claim_state(state, fires.clone());
+        let label = state.schedule.label();
+        inner.arm(&entry.slot.service_name, entry.slot.index);
+
+        match claim {
+            Claim::Run => self.dispatch(inner, ScheduleJob::new(entry.slot, fires)),
+            Claim::Queued => {}
+            Claim::Skipped => eprintln!(
+                "Skipping scheduled webhook '{label}' for service '{}': the previous run is still in progress",
+                entry.slot.service_name
+            ),
         }
     }
 
-    for service in services {
-        for (index, schedule) in service.schedules.iter().cloned().enumerate() {
-            let shared = Arc::clone(&schedule_map);
-            let service_name = service.name.clone();
-            let base_url = service.base_url.clone();
-            thread::spawn(move || run_schedule(shared, service_name, base_url, schedule, index));
+    // Never blocks, since the caller holds the lock: when every worker is
+    // behind, the run is dropped instead of stalling the timer.
+    fn dispatch(&self, inner: &mut SchedulerInner, job: ScheduleJob) {
+        let (Err(TrySendError::Full(job)) | Err(TrySendError::Disconnected(job))) =
+            self.jobs.try_send(job)
+        else {
+            return;
+        };
+        if let Some(state) = inner.state_mut(&job.slot) {
+            state.running = state.running.saturating_sub(1);
         }
+        eprintln!(
+            "Skipping scheduled webhook #{} for service '{}': every schedule worker is busy",
+            job.slot.index, job.slot.service_name
+        );
     }
 
-    schedule_map
+    // The job keeps the slot claimed while it waits, so a `skip` schedule
+    // does not start a new run on top of one that is still being retried.
+    fn retry_later(&self, job: ScheduleJob, after: Duration) {
+        let Some(mut inner) = self.lock().filter(|inner| !inner.stopping) else {
+            return;
+        };
+        inner.next_timer += 1;
+        let seq = inner.next_timer;
+        let due = Utc::now() + chrono::Duration::from_std(after).unwrap_or_default();
+        inner.timers.push(Reverse(TimerEntry {
+            due,
+            seq,
+            slot: job.slot.clone(),
+        }));
+        inner.retries.insert(seq, job);
+        drop(inner);
+        self.wakeup.notify_all();
+    }
+}
+
+impl Scheduler {
+    // Stops the timer and drops pending retries, then waits for the runs the
+    // workers already started. Returns false if some were still running at
+    // the deadline.
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        let Some(mut inner) = self.lock() else {
+            return false;
+        };
+        inner.stopping = true;
+        inner.timers.clear();
+        inner.retries.clear();
+        self.wakeup.notify_all();
+
+        let deadline = Instant::now() + timeout;
+        while inner.active_runs > 0 {
+            let Some(wait) = deadline.checked_duration_since(Instant::now()) else {
+                return false;
+            };
+            inner = match self.wakeup.wait_timeout(inner, wait) {
+                Ok((guard, _)) => guard,
+                Err(_) => return false,
+            };
+        }
+        true
+    }
+}
+
+impl SchedulerInner {
+    fn state_mut(&mut self, slot: &ScheduleSlot) -> Option<&mut ScheduleState> {
+        self.states
+            .get_mut(&slot.service_name)
+            .and_then(|entries| entries.get_mut(slot.index))
+            .filter(|state| state.generation == slot.generation)
+    }
+
+    fn history_snapshot(&mut self, service_name: &str) -> Option<HistorySnapshot> {
+        let schedules = self
+            .states
+            .get(service_name)?
+            .iter()
+            .map(|state| PersistedSchedule {
+                method: state.schedule.method.clone(),
+                endpoint: state.schedule.endpoint.clone(),
+                queue: state.schedule.queue.clone(),
+                paused: state.paused,
+                failures: state.failures,
+                runs: state.runs.clone(),
+            })
+            .collect();
+        self.next_history += 1;
+
+        Some(HistorySnapshot {
+            service_name: service_name.to_string(),
+            sequence: self.next_history,
+            schedules,
+        })
+    }
+
+    // Pushes a fresh timer entry for the schedule; whatever entry it had
+    // before stays in the heap until it is popped and found stale.
+    fn arm(&mut self, service_name: &str, index: usize) {
+        self.next_timer += 1;
+        let seq = self.next_timer;
+        let Some(state) = self
+            .states
+            .get_mut(service_name)
+            .and_then(|entries| entries.get_mut(index))
+        else {
+            return;
+        };
+
+        state.timer = 0;
+        state.next_run = state.pending;
+        let Some(pending) = state.pending.filter(|_| !state.paused) else {
+            return;
+        };
+        let due = pending + jitter(state.schedule.jitter_secs);
+        state.timer = seq;
+        state.next_run = Some(due);
+        self.timers.push(Reverse(TimerEntry {
+            due,
+            seq,
+            slot: ScheduleSlot {
+                service_name: service_name.to_string(),
+                index,
+                generation: state.generation,
+            },
+        }));
+    }
+
+    fn forget(&mut self, service_name: &str) {
+        self.timers
+            .retain(|Reverse(entry)| entry.slot.service_name != service_name);
+        self.retries
+            .retain(|_, job| job.slot.service_name != service_name);
+    }
+}
+
+pub fn start_webhook_schedulers(
+    services: &[Service],
+    health: &SharedHealthMap,
+    modules: &SharedModuleRegistry,
+    queues: &SharedQueueRegistry,
+    alerts: AlertSink,
+    settings: &RunnerSettings,
+) -> SharedScheduler {
+    let workers = settings.schedule_workers.max(1);
+    let (scheduler, receiver) = Scheduler::new(
+        ScheduleHistoryStore::new(&settings.state_dir, settings.schedule_history_limit),
+        Arc::clone(health),
+        Arc::clone(modules),
+        Arc::clone(queues),
+        alerts,
+        Duration::from_secs(settings.schedule_request_timeout_secs),
+        workers,
+    );
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for _ in 0..workers {
+        let scheduler = Arc::clone(&scheduler);
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || run_worker(&scheduler, &receiver));
+    }
+
+    for service in services {
+        apply_service_schedules(&scheduler, service);
+    }
+
+    let timer = Arc::clone(&scheduler);
+    thread::spawn(move || run_timer(&timer));
+    scheduler
+}
+
+// Each call installs a new generation of schedule entries for the service and
+// arms their timers; runs still in flight for an older generation finish but
+// are no longer recorded. Paused flags and run history come back from the
+// state directory.
+pub fn apply_service_schedules(scheduler: &Scheduler, service: &Service) {
+    let generation = NEXT_SCHEDULE_GENERATION.fetch_add(1, Ordering::Relaxed);
+    let restored = restore_schedules(&scheduler.store.load(&service.name), &service.schedules);
+
+    let snapshot = {
+        let Some(mut inner) = scheduler.lock() else {
+            return;
+        };
+        inner.forget(&service.name);
+
+        if service.schedules.is_empty() {
+            inner.states.remove(&service.name);
+            return;
+        }
+
+        let now = Utc::now();
+        let entries: Vec<ScheduleState> = service
+            .schedules
+            .iter()
+            .zip(restored)
+            .map(|(schedule, restored)| {
+                let restored = restored.unwrap_or_default();
+                let mut runs = restored.runs;
+                runs.truncate(scheduler.store.limit());
+                let last_fire = runs.iter().find_map(|run| run.scheduled_for);
+                // The next fire time is always derived from the wall clock, so
+                // cron entries keep their local time across restarts. With
+                // catch-up enabled, counting starts from the last periodic run.
+                let pending = match (schedule.catch_up, last_fire) {
+                    (CatchUpPolicy::None, _) | (_, None) => schedule.trigger.next_after(now),
+                    (_, Some(last_fire)) => schedule.trigger.next_after(last_fire),
+                };
+                ScheduleState {
+                    schedule: schedule.clone(),
+                    endpoints: service.runner_endpoints().to_vec(),
+                    cursor: 0,
+                    generation,
+                    paused: restored.paused,
+                    next_run: None,
+                    pending,
+                    timer: 0,
+                    running: 0,
+                    queued: None,
+                    failures: restored.failures,
+                    runs,
+                }
+            })
+            .collect();
+
+        inner.states.insert(service.name.clone(), entries);
+        for index in 0..service.schedules.len() {
+            inner.arm(&service.name, index);
+        }
+        inner.history_snapshot(&service.name)
+    };
+
+    scheduler.wakeup.notify_all();
+    scheduler.persist(snapshot);
+}
+
+pub fn remove_service_schedules(scheduler: &Scheduler, service_name: &str) {
+    if let Some(mut inner) = scheduler.lock() {
+        inner.states.remove(service_name);
+        inner.forget(service_name);
+    }
 }
 
 pub fn toggle_schedule(
-    map: &SharedScheduleMap,
+    scheduler: &Scheduler,
     service_name: &str,
     index: usize,
 ) -> Result<bool, ToggleError> {
-    let mut guard = map.lock().map_err(|_| ToggleError::LockPoisoned)?;
-    let entries = guard
+    let mut inner = scheduler
+        .inner
+        .lock()
+        .map_err(|_| ToggleError::LockPoisoned)?;
+    let entries = inner
+        .states
         .get_mut(service_name)
         .ok_or(ToggleError::ServiceNotFound)?;
     let state = entries
@@ -81,7 +562,38 @@ pub fn toggle_schedule(
         .ok_or(ToggleError::ScheduleNotFound)?;
 
     state.paused = !state.paused;
-    Ok(state.paused)
+    let paused = state.paused;
+    // Without catch-up a pause simply pushes the next fire forward; otherwise
+    // the fire times missed meanwhile stay pending.
+    if !paused && state.schedule.catch_up == CatchUpPolicy::None {
+        state.pending = state.schedule.trigger.next_after(Utc::now());
+    }
+    inner.arm(service_name, index);
+    let snapshot = inner.history_snapshot(service_name);
+    drop(inner);
+
+    scheduler.wakeup.notify_all();
+    scheduler.persist(snapshot);
+    Ok(paused)
+}
+
+pub fn schedule_runs(
+    scheduler: &Scheduler,
+    service_name: &str,
+    index: usize,
+) -> Result<ScheduleState, TriggerError> {
+    let inner = scheduler
+        .inner
+        .lock()
+        .map_err(|_| TriggerError::LockPoisoned)?;
+    let entries = inner
+        .states
+        .get(service_name)
+        .ok_or(TriggerError::ServiceNotFound)?;
+    entries
+        .get(index)
+        .cloned()
+        .ok_or(TriggerError::ScheduleNotFound)
 }
 
 #[derive(Debug)]
@@ -91,172 +603,784 @@ pub enum TriggerError {
     LockPoisoned,
 }
 
-#[derive(Clone, Debug)]
-pub struct TriggerOutcome {
-    pub last_run: Option<Instant>,
-    pub last_status: Option<u16>,
-    pub last_error: Option<String>,
+#[derive(Debug)]
+pub enum ManualRun {
+    // One run per instance called; several with `broadcast`.
+    Completed(Vec<ScheduleRun>),
+    Queued,
+    Skipped,
 }
 
+// "Lanzar ahora" runs on the caller's thread so the response can carry the
+// result, but it goes through the same concurrency policy as the periodic
+// runs, so it never doubles up on a webhook that must not overlap.
 pub fn trigger_schedule_now(
-    map: &SharedScheduleMap,
+    scheduler: &Scheduler,
     service_name: &str,
     index: usize,
-    base_url: &str,
-    endpoint: &str,
-) -> Result<TriggerOutcome, TriggerError> {
-    let url = format!(
-        "{}/{}",
-        base_url.trim_end_matches('/'),
-        endpoint.trim_start_matches('/')
-    );
-
-    let result = execute_webhook(service_name, endpoint, &url);
-    let (status, error_message) = match result {
-        Ok(status) => (Some(status), None),
-        Err(error) => (None, Some(error)),
+) -> Result<ManualRun, TriggerError> {
+    let (slot, claim) = {
+        let mut inner = scheduler
+            .inner
+            .lock()
+            .map_err(|_| TriggerError::LockPoisoned)?;
+        let state = inner
+            .states
+            .get_mut(service_name)
+            .ok_or(TriggerError::ServiceNotFound)?
+            .get_mut(index)
+            .ok_or(TriggerError::ScheduleNotFound)?;
+        let slot = ScheduleSlot {
+            service_name: service_name.to_string(),
+            index,
+            generation: state.generation,
+        };
+        (slot, claim_state(state, vec![None]))
     };
 
-    let now = Instant::now();
+    match claim {
+        Claim::Queued => Ok(ManualRun::Queued),
+        Claim::Skipped => Ok(ManualRun::Skipped),
+        Claim::Run => {
+            let (runs, retry) = execute_fire(scheduler, &slot, None, 1, None)
+                .ok_or(TriggerError::ScheduleNotFound)?;
+            // Retries go through the timer; the response only carries the
+            // first attempt.
+            match retry {
+                Some(retry) => scheduler.retry_later(
+                    ScheduleJob {
+                        slot,
+                        fires: vec![None],
+                        attempt: 2,
+                        only: retry.only,
+                    },
+                    retry.after,
+                ),
+                None => {
+                    if let Some(mut inner) = scheduler.lock() {
+                        if let Some(fires) = release_run(&mut inner, &slot) {
+                            scheduler.dispatch(&mut inner, ScheduleJob::new(slot, fires));
+                        }
+                    }
+                }
+            }
+            Ok(ManualRun::Completed(runs))
+        }
+    }
+}
 
-    let mut guard = map.lock().map_err(|_| TriggerError::LockPoisoned)?;
-    let entries = guard
-        .get_mut(service_name)
-        .ok_or(TriggerError::ServiceNotFound)?;
-    let state = entries
-        .get_mut(index)
-        .ok_or(TriggerError::ScheduleNotFound)?;
+fn run_timer(scheduler: &Scheduler) {
+    let Some(mut inner) = scheduler.lock() else {
+        return;
+    };
 
-    state.last_run = Some(now);
-    state.last_status = status;
-    state.last_error = error_message.clone();
+    loop {
+        if inner.stopping {
+            return;
+        }
+        let now = Utc::now();
+        let wait = match inner.timers.peek() {
+            Some(Reverse(entry)) if entry.due <= now => None,
+            Some(Reverse(entry)) => Some((entry.due - now).to_std().unwrap_or_default()),
+            None => Some(Duration::MAX),
+        };
 
-    Ok(TriggerOutcome {
-        last_run: state.last_run,
-        last_status: state.last_status,
-        last_error: state.last_error.clone(),
-    })
+        match wait {
+            Some(wait) => {
+                let wait = wait.min(Duration::from_secs(MAX_TIMER_SLEEP_SECS));
+                inner = match scheduler.wakeup.wait_timeout(inner, wait) {
+                    Ok((guard, _)) => guard,
+                    Err(_) => return,
+                };
+            }
+            None => {
+                if let Some(Reverse(entry)) = inner.timers.pop() {
+                    scheduler.fire_timer(&mut inner, entry, now);
+                }
+            }
+        }
+    }
 }
 
-fn run_schedule(
-    schedules: SharedScheduleMap,
-    service_name: String,
-    base_url: String,
-    schedule: ServiceSchedule,
-    index: usize,
-) {
-    let interval = schedule.interval_secs.max(1);
-    let mut remaining = interval;
-
+fn run_worker(scheduler: &Scheduler, receiver: &Mutex<Receiver<ScheduleJob>>) {
     loop {
-        thread::sleep(Duration::from_secs(SCHEDULE_LOOP_TICK_SECS));
-
-        let paused = match schedules.lock() {
-            Ok(guard) => match guard.get(&service_name) {
-                Some(entries) => match entries.get(index) {
-                    Some(state) => state.paused,
-                    None => return,
-                },
-                None => return,
-            },
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
             Err(_) => return,
         };
-
-        if paused {
-            remaining = interval;
+        let Ok(job) = job else {
+            return;
+        };
+        let Some(mut inner) = scheduler.lock().filter(|inner| !inner.stopping) else {
             continue;
+        };
+        inner.active_runs += 1;
+        drop(inner);
+
+        run_fires(scheduler, job);
+
+        if let Some(mut inner) = scheduler.lock() {
+            inner.active_runs -= 1;
         }
+        scheduler.wakeup.notify_all();
+    }
+}
 
-        if remaining > SCHEDULE_LOOP_TICK_SECS {
-            remaining -= SCHEDULE_LOOP_TICK_SECS;
-            continue;
+// Collects the fire times from `first` up to `now` and picks which of them to
+// run; a schedule only replays every missed fire with `catch_up: all`.
+fn due_fires(
+    trigger: &ScheduleTrigger,
+    first: DateTime<Utc>,
+    now: DateTime<Utc>,
+    catch_up: CatchUpPolicy,
+) -> (Vec<DateTime<Utc>>, Option<DateTime<Utc>>) {
+    let mut due = vec![first];
+    let mut following = trigger.next_after(first);
+
+    for _ in 0..MAX_MISSED_SCAN {
+        match following {
+            Some(at) if at <= now => {
+                due.push(at);
+                following = trigger.next_after(at);
+            }
+            _ => break,
+        }
+    }
+    if following.is_some_and(|at| at <= now) {
+        following = trigger.next_after(now);
+    }
+
+    let keep = match catch_up {
+        CatchUpPolicy::All => MAX_CATCH_UP_RUNS,
+        CatchUpPolicy::None | CatchUpPolicy::Once => 1,
+    };
+    let fires = due.split_off(due.len().saturating_sub(keep));
+    (fires, following)
+}
+
+fn jitter(max_secs: u64) -> chrono::Duration {
+    if max_secs == 0 {
+        return chrono::Duration::zero();
+    }
+    let random = RandomState::new().build_hasher().finish();
+    let millis = random % (max_secs.saturating_mul(1000) + 1);
+    chrono::Duration::milliseconds(i64::try_from(millis).unwrap_or(i64::MAX))
+}
+
+fn claim_state(state: &mut ScheduleState, fires: Vec<Fire>) -> Claim {
+    if state.running == 0 || state.schedule.concurrency == ConcurrencyPolicy::Allow {
+        state.running += 1;
+        return Claim::Run;
+    }
+
+    match state.schedule.concurrency {
+        // Only the latest request waits; older queued fires are superseded.
+        ConcurrencyPolicy::Queue => {
+            state.queued = Some(fires);
+            Claim::Queued
+        }
+        _ => Claim::Skipped,
+    }
+}
+
+// Hands back whatever was queued while the run was in flight, keeping the
+// slot claimed, or frees the slot when nothing is waiting.
+fn release_run(inner: &mut SchedulerInner, slot: &ScheduleSlot) -> Option<Vec<Fire>> {
+    let state = inner.state_mut(slot)?;
+    if let Some(queued) = state.queued.take() {
+        return Some(queued);
+    }
+    state.running = state.running.saturating_sub(1);
+    None
+}
+
+// A failed attempt with retries left parks the remaining fires behind its
+// retry, so they still run in order.
+fn run_fires(scheduler: &Scheduler, job: ScheduleJob) {
+    let ScheduleJob {
+        slot,
+        mut fires,
+        mut attempt,
+        mut only,
+    } = job;
+    loop {
+        let mut remaining = fires.into_iter();
+        while let Some(fire) = remaining.next() {
+            let Some((_, retry)) = execute_fire(scheduler, &slot, fire, attempt, only.take())
+            else {
+                return;
+            };
+            if let Some(retry) = retry {
+                let job = ScheduleJob {
+                    slot,
+                    fires: std::iter::once(fire).chain(remaining).collect(),
+                    attempt: attempt + 1,
+                    only: retry.only,
+                };
+                scheduler.retry_later(job, retry.after);
+                return;
+            }
+            attempt = 1;
+        }
+        match scheduler
+            .lock()
+            .and_then(|mut inner| release_run(&mut inner, &slot))
+        {
+            Some(queued) => fires = queued,
+            None => return,
         }
+    }
+}
+
+fn execute_fire(
+    scheduler: &Scheduler,
+    slot: &ScheduleSlot,
+    fire: Fire,
+    attempt: u32,
+    only: Option<Vec<usize>>,
+) -> Option<(Vec<ScheduleRun>, Option<Retry>)> {
+    let health = scheduler
+        .health
+        .lock()
+        .ok()
+        .and_then(|map| map.get(&slot.service_name).cloned())
+        .unwrap_or_default();
+    let ready = ready_instances(&scheduler.modules, &slot.service_name);
+    let (targets, schedule, previous_run) = {
+        let mut inner = scheduler.lock()?;
+        let state = inner.state_mut(slot)?;
+        let picked = match (only, &state.schedule.queue) {
+            (_, Some(_)) => Vec::new(),
+            (Some(only), None) => only
+                .into_iter()
+                .filter(|index| ready.as_ref().is_none_or(|ready| ready.contains(index)))
+                .collect(),
+            (None, None) => state.pick_targets(&health, ready.as_deref()),
+        };
+        let targets: Vec<(usize, String)> = picked
+            .into_iter()
+            .filter_map(|index| Some((index, state.endpoints.get(index)?.clone())))
+            .collect();
+        (
+            targets,
+            state.schedule.clone(),
+            state.last_run().map(ScheduleRun::fired_at),
+        )
+    };
 
-        remaining = interval;
+    let context = FireContext {
+        service: &slot.service_name,
+        fired_at: fire.unwrap_or_else(Utc::now),
+        previous_run,
+    };
+    let mut runs: Vec<ScheduleRun> = match &schedule.queue {
+        Some(queue) => vec![publish_schedule_message(
+            &scheduler.queues,
+            &slot.service_name,
+            queue,
+            &schedule,
+            &context,
+        )],
+        None => targets
+            .iter()
+            .map(|(index, base_url)| {
+                let request = render_schedule_request(base_url, &schedule, &context);
+                let mut run = execute_webhook(
+                    &slot.service_name,
+                    &schedule.endpoint,
+                    &request,
+                    scheduler.request_timeout,
+                );
+                run.instance = Some(*index);
+                run
+            })
+            .collect(),
+    };
+    if runs.is_empty() {
+        eprintln!(
+            "Skipping scheduled webhook '{} /{}' for service '{}': no runner instance is healthy and ready",
+            schedule.method, schedule.endpoint, slot.service_name
+        );
+        runs.push(ScheduleRun {
+            scheduled_for: None,
+            started_at: Utc::now(),
+            duration_ms: 0,
+            status: None,
+            error: Some("no healthy and ready runner instance".to_string()),
+            response_body: None,
+            instance: None,
+            attempt: 1,
+        });
+    }
 
-        let url = format!(
-            "{}/{}",
-            base_url.trim_end_matches('/'),
-            schedule.endpoint.trim_start_matches('/')
+    let mut inner = scheduler.lock()?;
+    let state = inner.state_mut(slot)?;
+    for run in &mut runs {
+        run.scheduled_for = fire;
+        run.attempt = attempt;
+        record_run(state, run.clone(), scheduler.store.limit());
+    }
+    let (retry, alert) = settle_attempt(state, attempt, &runs);
+    if let Some(retry) = &retry {
+        eprintln!(
+            "Retrying scheduled webhook '{}' for service '{}' in {}s (attempt {} of {})",
+            schedule.label(),
+            slot.service_name,
+            retry.after.as_secs(),
+            attempt + 1,
+            schedule.retry.max_attempts
         );
+    }
+    let alert = alert.map(|kind| {
+        let last = runs.iter().find(|run| run.failed()).unwrap_or(&runs[0]);
+        ScheduleAlert {
+            kind,
+            service: slot.service_name.clone(),
+            index: slot.index,
+            method: schedule.method.clone(),
+            endpoint: schedule.endpoint.clone(),
+            queue: schedule.queue.clone(),
+            failures: state.failures,
+            status: last.status,
+            error: last.error.clone(),
+            at: Utc::now(),
+        }
+    });
+    let snapshot = inner.history_snapshot(&slot.service_name);
+    drop(inner);
 
-        let result = execute_webhook(&service_name, &schedule.endpoint, &url);
+    scheduler.persist(snapshot);
 
-        let (status, error_message) = match result {
-            Ok(status) => (Some(status), None),
-            Err(error) => (None, Some(error)),
+    if let Some(alert) = alert {
+        scheduler.alerts.send(&alert);
+    }
+    Some((runs, retry))
+}
+
+// Decides what follows an attempt: another one after the backoff while
+// attempts are left, otherwise the outcome moves the failure streak and
+// reports when the schedule starts or stops failing.
+fn settle_attempt(
+    state: &mut ScheduleState,
+    attempt: u32,
+    runs: &[ScheduleRun],
+) -> (Option<Retry>, Option<AlertKind>) {
+    let failed: Vec<&ScheduleRun> = runs.iter().filter(|run| run.failed()).collect();
+    let policy = state.schedule.retry;
+    if !failed.is_empty() && attempt < policy.max_attempts {
+        // `broadcast` only calls again the instances that failed.
+        let only = (state.schedule.target == ScheduleTarget::Broadcast)
+            .then(|| failed.iter().filter_map(|run| run.instance).collect())
+            .filter(|only: &Vec<usize>| !only.is_empty());
+        let retry = Retry {
+            after: policy.backoff(attempt),
+            only,
         };
+        return (Some(retry), None);
+    }
 
-        if let Ok(mut guard) = schedules.lock() {
-            if let Some(entries) = guard.get_mut(&service_name) {
-                if let Some(state) = entries.get_mut(index) {
-                    state.last_run = Some(Instant::now());
-                    state.last_status = status;
-                    state.last_error = error_message;
-                }
-            }
-        } else {
-            return;
+    let was_failing = state.failing();
+    state.failures = if failed.is_empty() {
+        0
+    } else {
+        state.failures.saturating_add(1)
+    };
+    let alert = match (was_failing, state.failing()) {
+        (false, true) => Some(AlertKind::Failing),
+        (true, false) => Some(AlertKind::Recovered),
+        _ => None,
+    };
+    (None, alert)
+}
+
+fn record_run(state: &mut ScheduleState, run: ScheduleRun, limit: usize) {
+    state.runs.insert(0, run);
+    state.runs.truncate(limit);
+}
+
+// Goes through the same registry path as `POST /__runner__/queues/<queue>`
+// and is recorded with the 202 and summary that endpoint answers.
+fn publish_schedule_message(
+    queues: &SharedQueueRegistry,
+    service_name: &str,
+    queue: &str,
+    schedule: &ServiceSchedule,
+    context: &FireContext,
+) -> ScheduleRun {
+    let started_at = Utc::now();
+    let clock = Instant::now();
+    let message = render_queue_message(schedule, context);
+
+    let (status, response_body, error) = match publish_message(
+        queues,
+        queue,
+        &message.content_type,
+        &message.payload,
+    ) {
+        Ok((subscribers, message_count)) => {
+            let summary = json!({
+                "queue": queue,
+                "subscribers": subscribers,
+                "message_count": message_count,
+            });
+            (Some(202), Some(summary.to_string()), None)
+        }
+        Err(error) => {
+            eprintln!(
+                    "Failed to publish scheduled message to queue '{queue}' for service '{service_name}': {error:#}"
+                );
+            (None, None, Some(format!("{error:#}")))
         }
+    };
+
+    ScheduleRun {
+        scheduled_for: None,
+        started_at,
+        duration_ms: u64::try_from(clock.elapsed().as_millis()).unwrap_or(u64::MAX),
+        status,
+        error,
+        response_body,
+        instance: None,
+        attempt: 1,
     }
 }
 
-fn execute_webhook(service_name: &str, endpoint: &str, url: &str) -> Result<u16, String> {
-    match ureq::get(url)
-        .timeout(Duration::from_secs(SCHEDULE_REQUEST_TIMEOUT_SECS))
-        .call()
-    {
-        Ok(response) => Ok(response.status()),
+fn execute_webhook(
+    service_name: &str,
+    endpoint: &str,
+    request: &RenderedRequest,
+    request_timeout: Duration,
+) -> ScheduleRun {
+    let started_at = Utc::now();
+    let clock = Instant::now();
+
+    let mut call = ureq::request(&request.method, &request.url).timeout(request_timeout);
+    for (name, value) in &request.headers {
+        call = call.set(name, value);
+    }
+    if let Some(content_type) = &request.content_type {
+        call = call.set("Content-Type", content_type);
+    }
+
+    let result = if request.body.is_empty() {
+        call.call()
+    } else {
+        call.send_bytes(&request.body)
+    };
+
+    let (status, response_body, error) = match result {
+        Ok(response) => (Some(response.status()), read_body(response), None),
         Err(ureq::Error::Status(status, response)) => {
-            let _ = response.into_string();
             if status >= 400 {
                 eprintln!(
-                    "Scheduled webhook '/{endpoint}' for service '{service_name}' returned HTTP {status}"
+                    "Scheduled webhook '{} /{endpoint}' for service '{service_name}' returned HTTP {status}",
+                    request.method
                 );
             }
-            Ok(status)
+            (Some(status), read_body(response), None)
         }
         Err(error) => {
             eprintln!(
-                "Failed to execute scheduled webhook '/{endpoint}' for service '{service_name}': {error}"
+                "Failed to execute scheduled webhook '{} /{endpoint}' for service '{service_name}': {error}",
+                request.method
             );
-            Err(error.to_string())
+            (None, None, Some(error.to_string()))
         }
+    };
+
+    ScheduleRun {
+        scheduled_for: None,
+        started_at,
+        duration_ms: u64::try_from(clock.elapsed().as_millis()).unwrap_or(u64::MAX),
+        status,
+        error,
+        response_body,
+        instance: None,
+        attempt: 1,
     }
 }
 
+// Only the start of the body is kept; one more byte than the limit tells
+// `truncate_body` whether anything was cut.
+fn read_body(response: ureq::Response) -> Option<String> {
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_RESPONSE_BODY_BYTES as u64 + 1)
+        .read_to_end(&mut body)
+        .ok()?;
+    (!body.is_empty()).then(|| truncate_body(&body))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{RetryPolicy, DEFAULT_ALERT_AFTER};
 
-    #[test]
-    fn toggle_schedule_flips_state() {
-        let map: SharedScheduleMap = Arc::new(Mutex::new(HashMap::new()));
-        {
-            let mut guard = map.lock().unwrap();
-            guard.insert(
-                "svc".into(),
-                vec![ScheduleState {
-                    endpoint: "ping".into(),
-                    interval_secs: 5,
-                    paused: false,
-                    last_run: None,
-                    last_status: None,
-                    last_error: None,
-                }],
-            );
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    fn state() -> ScheduleState {
+        ScheduleState {
+            schedule: ServiceSchedule {
+                endpoint: "ping".into(),
+                queue: None,
+                trigger: ScheduleTrigger::Interval { secs: 60 },
+                method: "GET".into(),
+                headers: Default::default(),
+                body: None,
+                concurrency: ConcurrencyPolicy::Skip,
+                catch_up: CatchUpPolicy::None,
+                jitter_secs: 0,
+                target: Default::default(),
+                retry: Default::default(),
+                alert_after: DEFAULT_ALERT_AFTER,
+                origin: Default::default(),
+            },
+            endpoints: vec!["http://127.0.0.1:9".into()],
+            cursor: 0,
+            generation: 0,
+            paused: false,
+            next_run: None,
+            pending: None,
+            timer: 0,
+            running: 0,
+            queued: None,
+            failures: 0,
+            runs: Vec::new(),
         }
+    }
 
-        assert_eq!(toggle_schedule(&map, "svc", 0).unwrap(), true);
-        assert_eq!(toggle_schedule(&map, "svc", 0).unwrap(), false);
+    fn scheduler(name: &str) -> (std::path::PathBuf, SharedScheduler, Receiver<ScheduleJob>) {
+        let dir = std::env::temp_dir().join(format!(
+            "rustrunner-scheduler-{}-{name}",
+            std::process::id()
+        ));
+        let store = ScheduleHistoryStore::new(&dir, 3);
+        let health = Arc::new(Mutex::new(HashMap::new()));
+        let (scheduler, receiver) = Scheduler::new(
+            store,
+            health,
+            crate::process::initialize_module_registry(),
+            crate::queue::initialize_queue_registry(&[]),
+            AlertSink::default(),
+            Duration::from_secs(1),
+            1,
+        );
+        (dir, scheduler, receiver)
+    }
+
+    #[test]
+    fn toggle_schedule_flips_and_persists_state() {
+        let (dir, scheduler, _receiver) = scheduler("toggle");
+        scheduler
+            .lock()
+            .unwrap()
+            .states
+            .insert("svc".into(), vec![state()]);
+
+        assert!(toggle_schedule(&scheduler, "svc", 0).unwrap());
+        assert!(scheduler.store.load("svc")[0].paused);
+        assert_eq!(scheduler.lock().unwrap().states["svc"][0].timer, 0);
+        assert!(!toggle_schedule(&scheduler, "svc", 0).unwrap());
+        assert!(!scheduler.store.load("svc")[0].paused);
+        assert_eq!(scheduler.lock().unwrap().timers.len(), 1);
+
+        // A history taken before the last toggle is written late and dropped.
+        let stale = scheduler.lock().unwrap().history_snapshot("svc");
+        assert!(toggle_schedule(&scheduler, "svc", 0).unwrap());
+        scheduler.persist(stale);
+        assert!(scheduler.store.load("svc")[0].paused);
+
+        let mut state = scheduler.snapshot()["svc"][0].clone();
+        for second in 0..5 {
+            let run = ScheduleRun {
+                scheduled_for: None,
+                started_at: at(second),
+                duration_ms: 1,
+                status: Some(200),
+                error: None,
+                response_body: None,
+                instance: None,
+                attempt: 1,
+            };
+            record_run(&mut state, run, scheduler.store.limit());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+        let kept: Vec<i64> = state
+            .runs
+            .iter()
+            .map(|run| run.started_at.timestamp())
+            .collect();
+        assert_eq!(kept, vec![4, 3, 2]);
     }
 
     #[test]
     fn toggle_schedule_errors_when_missing() {
-        let map: SharedScheduleMap = Arc::new(Mutex::new(HashMap::new()));
+        let (_, scheduler, _receiver) = scheduler("missing");
         assert!(matches!(
-            toggle_schedule(&map, "svc", 0),
+            toggle_schedule(&scheduler, "svc", 0),
             Err(ToggleError::ServiceNotFound)
         ));
     }
+
+    #[test]
+    fn timer_hands_due_runs_to_the_worker_queue() {
+        let (_, scheduler, receiver) = scheduler("timer");
+        let mut inner = scheduler.lock().unwrap();
+        let mut entry = state();
+        entry.pending = Some(at(60));
+        inner.states.insert("svc".into(), vec![entry]);
+        inner.arm("svc", 0);
+
+        let Reverse(first) = inner.timers.pop().unwrap();
+        assert_eq!(first.due, at(60));
+        scheduler.fire_timer(&mut inner, first.clone(), at(61));
+        let job = receiver.try_recv().expect("job queued for the workers");
+        assert_eq!(job.fires, vec![Some(at(60))]);
+        assert_eq!(inner.states["svc"][0].running, 1);
+        assert_eq!(inner.timers.peek().unwrap().0.due, at(120));
+
+        // A stale entry and the entry of a paused schedule are both ignored.
+        scheduler.fire_timer(&mut inner, first, at(61));
+        inner.states.get_mut("svc").unwrap()[0].paused = true;
+        inner.arm("svc", 0);
+        let Reverse(second) = inner.timers.pop().unwrap();
+        scheduler.fire_timer(&mut inner, second, at(121));
+        assert!(receiver.try_recv().is_err());
+        assert!(inner.timers.is_empty());
+    }
+
+    #[test]
+    fn picks_runner_instances_by_target() {
+        let mut slot = state();
+        slot.endpoints = vec!["a".into(), "b".into(), "c".into()];
+        let health = ServiceHealth {
+            instances: vec![
+                HealthStatus::Unhealthy,
+                HealthStatus::Unknown,
+                HealthStatus::Healthy,
+            ],
+            ..Default::default()
+        };
+
+        let picks: Vec<Vec<usize>> = (0..4).map(|_| slot.pick_targets(&health, None)).collect();
+        assert_eq!(picks, vec![vec![0], vec![1], vec![2], vec![0]]);
+
+        slot.schedule.target = ScheduleTarget::AnyHealthy;
+        assert_eq!(slot.pick_targets(&health, None), vec![2]);
+        assert_eq!(slot.pick_targets(&health, None), vec![2]);
+        let down = ServiceHealth {
+            instances: vec![HealthStatus::Unhealthy; 3],
+            ..Default::default()
+        };
+        assert!(slot.pick_targets(&down, None).is_empty());
+        let starting = ServiceHealth::default();
+        assert_eq!(slot.pick_targets(&starting, None), vec![0]);
+
+        slot.schedule.target = ScheduleTarget::Broadcast;
+        assert_eq!(slot.pick_targets(&down, None), vec![0, 1, 2]);
+        assert_eq!(slot.pick_targets(&down, Some(&[0, 2])), vec![0, 2]);
+
+        slot.schedule.target = ScheduleTarget::RoundRobin;
+        slot.cursor = 0;
+        assert_eq!(slot.pick_targets(&health, Some(&[1, 2])), vec![1]);
+        assert_eq!(slot.pick_targets(&health, Some(&[1, 2])), vec![2]);
+        assert_eq!(slot.pick_targets(&health, Some(&[1, 2])), vec![1]);
+        assert!(slot.pick_targets(&health, Some(&[])).is_empty());
+    }
+
+    #[test]
+    fn applies_catch_up_and_concurrency_policies() {
+        let trigger = ScheduleTrigger::Interval { secs: 60 };
+
+        let (fires, next) = due_fires(&trigger, at(60), at(250), CatchUpPolicy::All);
+        assert_eq!(fires, vec![at(60), at(120), at(180), at(240)]);
+        assert_eq!(next, Some(at(300)));
+        let (fires, next) = due_fires(&trigger, at(60), at(250), CatchUpPolicy::Once);
+        assert_eq!(fires, vec![at(240)]);
+        assert_eq!(next, Some(at(300)));
+        let (fires, _) = due_fires(&trigger, at(60), at(61), CatchUpPolicy::None);
+        assert_eq!(fires, vec![at(60)]);
+
+        let mut slot = state();
+        assert_eq!(claim_state(&mut slot, vec![None]), Claim::Run);
+        assert_eq!(claim_state(&mut slot, vec![None]), Claim::Skipped);
+        slot.schedule.concurrency = ConcurrencyPolicy::Queue;
+        assert_eq!(claim_state(&mut slot, vec![Some(at(60))]), Claim::Queued);
+        assert_eq!(claim_state(&mut slot, vec![Some(at(120))]), Claim::Queued);
+        assert_eq!(slot.queued, Some(vec![Some(at(120))]));
+        slot.schedule.concurrency = ConcurrencyPolicy::Allow;
+        assert_eq!(claim_state(&mut slot, vec![None]), Claim::Run);
+        assert_eq!(slot.running, 2);
+
+        assert!(jitter(0).is_zero());
+        assert!(jitter(3) <= chrono::Duration::seconds(3));
+    }
+
+    #[test]
+    fn retries_failed_calls_and_alerts_on_a_failure_streak() {
+        let run = |status: u16, instance: usize| ScheduleRun {
+            scheduled_for: None,
+            started_at: at(0),
+            duration_ms: 1,
+            status: Some(status),
+            error: None,
+            response_body: None,
+            instance: Some(instance),
+            attempt: 1,
+        };
+        let mut slot = state();
+        slot.schedule.retry = RetryPolicy {
+            max_attempts: 3,
+            backoff_secs: 2,
+            max_backoff_secs: 3,
+        };
+        slot.schedule.alert_after = 2;
+
+        let (retry, alert) = settle_attempt(&mut slot, 1, &[run(503, 0)]);
+        assert_eq!(retry.unwrap().after, Duration::from_secs(2));
+        assert_eq!(alert, None);
+        let (retry, _) = settle_attempt(&mut slot, 2, &[run(503, 0)]);
+        assert_eq!(retry.unwrap().after, Duration::from_secs(3));
+        assert_eq!(settle_attempt(&mut slot, 3, &[run(503, 0)]), (None, None));
+        assert_eq!(slot.failures, 1);
+        assert_eq!(
+            settle_attempt(&mut slot, 3, &[run(500, 0)]),
+            (None, Some(AlertKind::Failing))
+        );
+        assert!(slot.failing());
+        assert_eq!(
+            settle_attempt(&mut slot, 1, &[run(404, 0)]),
+            (None, Some(AlertKind::Recovered))
+        );
+        assert_eq!(slot.failures, 0);
+
+        slot.schedule.target = ScheduleTarget::Broadcast;
+        let (retry, _) = settle_attempt(&mut slot, 1, &[run(200, 0), run(502, 1)]);
+        assert_eq!(retry.unwrap().only, Some(vec![1]));
+
+        // The retry waits in the timer heap and then goes to the workers.
+        let (_, scheduler, receiver) = scheduler("retry");
+        scheduler
+            .lock()
+            .unwrap()
+            .states
+            .insert("svc".into(), vec![state()]);
+        let slot = ScheduleSlot {
+            service_name: "svc".into(),
+            index: 0,
+            generation: 0,
+        };
+        let mut job = ScheduleJob::new(slot, vec![Some(at(60))]);
+        job.attempt = 2;
+        scheduler.retry_later(job, Duration::ZERO);
+        let mut inner = scheduler.lock().unwrap();
+        let Reverse(entry) = inner.timers.pop().unwrap();
+        scheduler.fire_timer(&mut inner, entry, Utc::now());
+        let job = receiver.try_recv().expect("retry queued for the workers");
+        assert_eq!((job.attempt, job.fires), (2, vec![Some(at(60))]));
+        assert!(inner.retries.is_empty());
+    }
 }
@@ -0,0 +1,518 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaViolation {
+    pub location: String,
+    pub message: String,
+}
+
+impl SchemaViolation {
+    pub fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+// `$ref`s are looked up in `document` as they are reached, so recursive
+// schemas only go as deep as the value being checked.
+pub fn validate(
+    schema: &Value,
+    document: &Value,
+    value: &Value,
+    location: &str,
+) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    validate_into(schema, document, &[], value, location, &mut violations);
+    violations
+}
+
+// Follows `$ref`s until a schema that is not one; a dangling or looping
+// reference is returned as is.
+pub fn resolve<'a>(schema: &'a Value, document: &'a Value) -> &'a Value {
+    let mut current = schema;
+    let mut followed = Vec::new();
+    while let Some(reference) = current.get("$ref").and_then(Value::as_str) {
+        if followed.contains(&reference) {
+            break;
+        }
+        let Some(target) = lookup(document, reference) else {
+            break;
+        };
+        followed.push(reference);
+        current = target;
+    }
+    current
+}
+
+pub fn lookup<'a>(document: &'a Value, reference: &str) -> Option<&'a Value> {
+    document.pointer(reference.strip_prefix('#')?)
+}
+
+pub fn primary_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(kind)) => Some(kind.as_str()),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null"),
+        _ => None,
+    }
+}
+
+// `pending` holds the references followed since the last step into the
+// value; meeting one of them again means the schema loops without checking
+// anything new.
+fn validate_into<'a>(
+    schema: &'a Value,
+    document: &'a Value,
+    pending: &[&'a str],
+    value: &Value,
+    location: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if pending.contains(&reference) {
+            return;
+        }
+        let Some(target) = lookup(document, reference) else {
+            out.push(SchemaViolation::new(
+                location,
+                format!("schema reference '{reference}' cannot be resolved"),
+            ));
+            return;
+        };
+        let mut pending = pending.to_vec();
+        pending.push(reference);
+        validate_into(target, document, &pending, value, location, out);
+        return;
+    }
+
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            out.push(SchemaViolation::new(location, "no value is allowed here"));
+            return;
+        }
+        Value::Object(map) => map,
+        _ => return,
+    };
+
+    if value.is_null() && schema.get("nullable").and_then(Value::as_bool) == Some(true) {
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(kind) => vec![kind.as_str()],
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+
+        if !allowed.is_empty() && !allowed.iter().any(|kind| matches_type(kind, value)) {
+            out.push(SchemaViolation::new(
+                location,
+                format!(
+                    "expected {}, found {}",
+                    allowed.join(" or "),
+                    describe_type(value)
+                ),
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            out.push(SchemaViolation::new(
+                location,
+                format!("value {value} is not one of the allowed values"),
+            ));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            out.push(SchemaViolation::new(
+                location,
+                format!("value must be {expected}"),
+            ));
+        }
+    }
+
+    match value {
+        Value::String(text) => validate_string(schema, text, location, out),
+        Value::Number(_) => validate_number(schema, value, location, out),
+        Value::Array(items) => validate_array(schema, document, items, location, out),
+        Value::Object(fields) => validate_object(schema, document, fields, location, out),
+        _ => {}
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            validate_into(sub, document, pending, value, location, out);
+        }
+    }
+
+    if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+        if !any
+            .iter()
+            .any(|sub| is_valid_at(sub, document, pending, value, location))
+        {
+            out.push(SchemaViolation::new(
+                location,
+                "value does not match any of the allowed schemas",
+            ));
+        }
+    }
+
+    if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+        let matches = one
+            .iter()
+            .filter(|sub| is_valid_at(sub, document, pending, value, location))
+            .count();
+        if matches != 1 {
+            out.push(SchemaViolation::new(
+                location,
+                format!("value must match exactly one schema, matched {matches}"),
+            ));
+        }
+    }
+}
+
+fn is_valid_at<'a>(
+    schema: &'a Value,
+    document: &'a Value,
+    pending: &[&'a str],
+    value: &Value,
+    location: &str,
+) -> bool {
+    let mut violations = Vec::new();
+    validate_into(schema, document, pending, value, location, &mut violations);
+    violations.is_empty()
+}
+
+fn validate_string(
+    schema: &serde_json::Map<String, Value>,
+    text: &str,
+    location: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let length = text.chars().count() as u64;
+
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if length < min {
+            out.push(SchemaViolation::new(
+                location,
+                format!("must be at least {min} characters long"),
+            ));
+        }
+    }
+
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if length > max {
+            out.push(SchemaViolation::new(
+                location,
+                format!("must be at most {max} characters long"),
+            ));
+        }
+    }
+}
+
+fn validate_number(
+    schema: &serde_json::Map<String, Value>,
+    value: &Value,
+    location: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let Some(number) = value.as_f64() else {
+        return;
+    };
+
+    let exclusive_flag = |key: &str| schema.get(key).and_then(Value::as_bool) == Some(true);
+
+    if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+        let exclusive = exclusive_flag("exclusiveMinimum");
+        if number < min || (exclusive && number == min) {
+            let relation = if exclusive {
+                "greater than"
+            } else {
+                "at least"
+            };
+            out.push(SchemaViolation::new(
+                location,
+                format!("must be {relation} {min}"),
+            ));
+        }
+    }
+
+    if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+        let exclusive = exclusive_flag("exclusiveMaximum");
+        if number > max || (exclusive && number == max) {
+            let relation = if exclusive { "less than" } else { "at most" };
+            out.push(SchemaViolation::new(
+                location,
+                format!("must be {relation} {max}"),
+            ));
+        }
+    }
+
+    if let Some(min) = schema.get("exclusiveMinimum").and_then(Value::as_f64) {
+        if number <= min {
+            out.push(SchemaViolation::new(
+                location,
+                format!("must be greater than {min}"),
+            ));
+        }
+    }
+
+    if let Some(max) = schema.get("exclusiveMaximum").and_then(Value::as_f64) {
+        if number >= max {
+            out.push(SchemaViolation::new(
+                location,
+                format!("must be less than {max}"),
+            ));
+        }
+    }
+}
+
+fn validate_array(
+    schema: &serde_json::Map<String, Value>,
+    document: &Value,
+    items: &[Value],
+    location: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let count = items.len() as u64;
+
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if count < min {
+            out.push(SchemaViolation::new(
+                location,
+                format!("must contain at least {min} items"),
+            ));
+        }
+    }
+
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if count > max {
+            out.push(SchemaViolation::new(
+                location,
+                format!("must contain at most {max} items"),
+            ));
+        }
+    }
+
+    if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true) {
+        let mut seen = HashSet::new();
+        if !items.iter().all(|item| seen.insert(item.to_string())) {
+            out.push(SchemaViolation::new(location, "items must be unique"));
+        }
+    }
+
+    if let Some(item_schema) = schema.get("items") {
+        for (index, item) in items.iter().enumerate() {
+            let item_location = format!("{location}[{index}]");
+            validate_into(item_schema, document, &[], item, &item_location, out);
+        }
+    }
+}
+
+fn validate_object(
+    schema: &serde_json::Map<String, Value>,
+    document: &Value,
+    fields: &serde_json::Map<String, Value>,
+    location: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !fields.contains_key(name) {
+                out.push(SchemaViolation::new(
+                    join_location(location, name),
+                    "required property is missing",
+                ));
+            }
+        }
+    }
+
+    for (name, field) in fields {
+        let field_location = join_location(location, name);
+        match properties.and_then(|props| props.get(name)) {
+            Some(field_schema) => {
+                validate_into(field_schema, document, &[], field, &field_location, out)
+            }
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => out.push(SchemaViolation::new(
+                    field_location,
+                    "property is not allowed",
+                )),
+                Some(extra @ Value::Object(_)) => {
+                    validate_into(extra, document, &[], field, &field_location, out)
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+fn join_location(location: &str, name: &str) -> String {
+    if location.is_empty() {
+        name.to_string()
+    } else {
+        format!("{location}.{name}")
+    }
+}
+
+fn matches_type(kind: &str, value: &Value) -> bool {
+    match kind {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn describe_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn is_valid(schema: &Value, value: &Value) -> bool {
+        validate(schema, &Value::Null, value, "").is_empty()
+    }
+
+    #[test]
+    fn reports_nested_violations_with_locations() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "lines"],
+            "additionalProperties": false,
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "lines": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "object",
+                        "required": ["sku"],
+                        "properties": { "sku": { "type": "string", "minLength": 3 } }
+                    }
+                }
+            }
+        });
+
+        let violations = validate(
+            &schema,
+            &Value::Null,
+            &json!({ "id": 0, "lines": [{ "sku": "ab" }, {}], "extra": true }),
+            "body",
+        );
+
+        let rendered: Vec<_> = violations
+            .iter()
+            .map(|violation| format!("{}: {}", violation.location, violation.message))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "body.extra: property is not allowed",
+                "body.id: must be at least 1",
+                "body.lines[0].sku: must be at least 3 characters long",
+                "body.lines[1].sku: required property is missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn handles_type_unions_nullable_and_combinators() {
+        assert!(is_valid(&json!({ "type": "integer" }), &json!(3)));
+        assert!(!is_valid(&json!({ "type": "integer" }), &json!(3.5)));
+        assert!(is_valid(
+            &json!({ "type": ["string", "null"] }),
+            &json!(null)
+        ));
+        assert!(is_valid(
+            &json!({ "type": "string", "nullable": true }),
+            &json!(null)
+        ));
+        assert!(!is_valid(
+            &json!({ "type": "string", "enum": ["a", "b"] }),
+            &json!("c")
+        ));
+        assert!(is_valid(
+            &json!({ "oneOf": [{ "type": "string" }, { "type": "integer" }] }),
+            &json!(1)
+        ));
+        assert!(!is_valid(
+            &json!({ "anyOf": [{ "type": "string" }, { "type": "boolean" }] }),
+            &json!(1)
+        ));
+        assert!(!is_valid(
+            &json!({ "type": "number", "minimum": 0, "exclusiveMinimum": true }),
+            &json!(0)
+        ));
+    }
+
+    #[test]
+    fn follows_recursive_references_as_deep_as_the_value() {
+        let document = json!({
+            "components": {
+                "schemas": {
+                    "Node": {
+                        "type": "object",
+                        "required": ["value"],
+                        "properties": {
+                            "value": { "type": "integer" },
+                            "next": { "$ref": "#/components/schemas/Node" }
+                        }
+                    },
+                    "Loop": { "allOf": [{ "$ref": "#/components/schemas/Loop" }] }
+                }
+            }
+        });
+        let node = json!({ "$ref": "#/components/schemas/Node" });
+
+        let list = json!({ "value": 1, "next": { "value": 2, "next": { "value": "3" } } });
+        let rendered: Vec<_> = validate(&node, &document, &list, "body")
+            .iter()
+            .map(|violation| format!("{}: {}", violation.location, violation.message))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec!["body.next.next.value: expected integer, found string"]
+        );
+
+        let looping = json!({ "$ref": "#/components/schemas/Loop" });
+        assert!(validate(&looping, &document, &json!(1), "body").is_empty());
+        assert_eq!(
+            resolve(&looping, &document),
+            &document["components"]["schemas"]["Loop"]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::config::Service;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretValue(String);
+
+impl SecretValue {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretValue(<redacted>)")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SecretStore {
+    path: PathBuf,
+    values: HashMap<String, SecretValue>,
+}
+
+impl SecretStore {
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn resolve(&self, service: &Service) -> Result<Vec<(String, SecretValue)>> {
+        service
+            .secrets
+            .iter()
+            .map(|(variable, key)| {
+                let value = self.values.get(key).ok_or_else(|| {
+                    anyhow!(
+                        "secret '{}' required by service '{}' for {} is not defined in {}",
+                        key,
+                        service.name,
+                        variable,
+                        self.path.display()
+                    )
+                })?;
+                Ok((variable.clone(), value.clone()))
+            })
+            .collect()
+    }
+}
+
+pub fn load_secret_store(path: &Path) -> Result<SecretStore> {
+    let mut store = SecretStore {
+        path: path.to_path_buf(),
+        values: HashMap::new(),
+    };
+
+    if !path.exists() {
+        return Ok(store);
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read secrets file at {}", path.display()))?;
+    let table: toml::Table = toml::from_str(&contents)
+        .with_context(|| format!("failed to parse secrets file at {}", path.display()))?;
+
+    flatten_secrets("", &table, &mut store.values)
+        .with_context(|| format!("invalid secrets file at {}", path.display()))?;
+
+    Ok(store)
+}
+
+// Nested tables are addressed with dotted keys, so `[sap] password = "…"`
+// is referenced from service.json as `sap.password`.
+fn flatten_secrets(
+    prefix: &str,
+    table: &toml::Table,
+    values: &mut HashMap<String, SecretValue>,
+) -> Result<()> {
+    for (key, value) in table {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            toml::Value::String(text) => {
+                values.insert(full_key, SecretValue(text.clone()));
+            }
+            toml::Value::Table(nested) => flatten_secrets(&full_key, nested, values)?,
+            _ => bail!("secret '{full_key}' must be a string"),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServiceKind;
+
+    fn service_with_secrets(secrets: &[(&str, &str)]) -> Service {
+        Service {
+            kind: ServiceKind::Adapter,
+            secrets: secrets
+                .iter()
+                .map(|(variable, key)| (variable.to_string(), key.to_string()))
+                .collect(),
+            ..Service::for_test("svc")
+        }
+    }
+
+    #[test]
+    fn resolves_flat_and_nested_keys_without_leaking_values() {
+        let path =
+            std::env::temp_dir().join(format!("rustrunner-{}-secrets.toml", std::process::id()));
+        fs::write(&path, "token = \"abc\"\n[sap]\npassword = \"s3cr3t\"\n").expect("write secrets");
+        let store = load_secret_store(&path).expect("load secrets");
+        let _ = fs::remove_file(&path);
+
+        let resolved = store
+            .resolve(&service_with_secrets(&[
+                ("SAP_PASSWORD", "sap.password"),
+                ("API_TOKEN", "token"),
+            ]))
+            .expect("resolve secrets");
+
+        assert_eq!(resolved[0].0, "API_TOKEN");
+        assert_eq!(resolved[1].1.expose(), "s3cr3t");
+        assert!(!format!("{resolved:?}").contains("s3cr3t"));
+        assert!(!format!("{store:?}").contains("abc"));
+    }
+
+    #[test]
+    fn reports_missing_secrets() {
+        let store = load_secret_store(Path::new("does-not-exist.toml")).expect("empty store");
+        let error = store
+            .resolve(&service_with_secrets(&[("SAP_PASSWORD", "sap.password")]))
+            .unwrap_err();
+        assert!(error.to_string().contains("sap.password"));
+    }
+}
@@ -1,47 +1,67 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
 use std::collections::{BTreeMap, HashMap};
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 use std::sync::Mutex;
+use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
 use tiny_http::{Header, Method, Request, Response, Server};
 
-use crate::config::Service;
+#[cfg(test)]
+use crate::catalog::ServiceCatalog;
+use crate::catalog::{current_catalog, SharedServiceCatalog};
 #[cfg(test)]
 use crate::config::ServiceKind;
+use crate::config::{
+    schedule_to_value, CatchUpPolicy, ConcurrencyPolicy, ScheduleOrigin, ScheduleTarget, Service,
+    ServiceSchedule,
+};
+use crate::contracts::{record_contract_violations, ContractSnapshot, SharedContractLog};
+use crate::cron::ScheduleTrigger;
 use crate::health::{HealthStatus, SharedHealthMap};
+use crate::history::ScheduleRun;
 use crate::logs::SharedLogMap;
 use crate::memory::{ServiceMemorySnapshot, SharedMemoryMap};
-use crate::queue::{with_queue_registry, QueueSnapshot, SharedQueueRegistry};
-use crate::scheduler::{self, ScheduleState, SharedScheduleMap, ToggleError, TriggerError};
+use crate::openapi::{Operation, OperationLookup, RequestParts};
+use crate::process::{
+    ready_instances, service_instances, supervision_snapshot, InstanceLifecycle,
+    InstanceSupervision, SharedModuleRegistry,
+};
+use crate::queue::{publish_message, with_queue_registry, QueueSnapshot, SharedQueueRegistry};
+use crate::runtime_schedules::{
+    edit_service_schedules, ScheduleEdit, ScheduleEditError, SharedScheduleOverrides,
+};
+use crate::scheduler::{
+    self, ManualRun, ScheduleState, SharedScheduler, ToggleError, TriggerError,
+};
+use crate::schema::SchemaViolation;
+use crate::settings::RunnerSettings;
+use crate::shutdown::{stop_runner, SharedShutdown};
+use crate::startup::{startup_snapshot, SharedStartupMap, StartupPhase, StartupSnapshot};
 use crate::stats::{record_http_status, SharedStats};
 use crate::templates;
 use serde_json::json;
 
-const ENTRY_PORT: u16 = 14000;
-
+#[derive(Default)]
 struct RoundRobinRouter {
     counters: Mutex<HashMap<String, usize>>,
 }
 
 impl RoundRobinRouter {
-    fn new(services: &[Service]) -> Self {
-        let mut counters = HashMap::new();
-        for service in services {
-            counters.insert(service.name.clone(), 0);
-        }
-        Self {
-            counters: Mutex::new(counters),
-        }
-    }
-
-    fn next_base_url(&self, service: &Service) -> String {
+    // `ready` lists the instances that may take traffic; `None` means the
+    // runner does not manage the service, so every endpoint is a candidate.
+    fn next_base_url(&self, service: &Service, ready: Option<&[usize]>) -> Option<String> {
         let endpoints = service.runner_endpoints();
-        if endpoints.len() <= 1 {
-            return endpoints
-                .first()
-                .cloned()
-                .unwrap_or_else(|| service.base_url.clone());
+        let candidates: Vec<&String> = match ready {
+            Some(ready) => ready
+                .iter()
+                .filter_map(|index| endpoints.get(*index))
+                .collect(),
+            None => endpoints.iter().collect(),
+        };
+        if candidates.len() <= 1 {
+            return candidates.first().map(|url| url.to_string());
         }
 
         let index = match self.counters.lock() {
@@ -54,56 +74,90 @@ impl RoundRobinRouter {
             Err(_) => 0,
         };
 
-        let position = index % endpoints.len();
-        endpoints
-            .get(position)
-            .cloned()
-            .unwrap_or_else(|| service.base_url.clone())
+        Some(candidates[index % candidates.len()].to_string())
     }
 }
 
-pub fn run_server(
-    services: &[Service],
-    health: &SharedHealthMap,
-    logs: &SharedLogMap,
-    schedules: &SharedScheduleMap,
-    stats: &SharedStats,
-    queues: &SharedQueueRegistry,
-    memory: &SharedMemoryMap,
-) -> Result<()> {
-    let server = Server::http(("0.0.0.0", ENTRY_PORT)).map_err(|error| {
-        anyhow!(
-            "failed to bind entrypoint to port {}: {}",
-            ENTRY_PORT,
-            error
-        )
-    })?;
+// How often the request loop checks for a shutdown while idle.
+const SHUTDOWN_POLL_MILLIS: u64 = 200;
+// Largest module response `read_upstream_reply` buffers; past it the client
+// gets a 502 instead of the reply.
+const MAX_UPSTREAM_BODY_BYTES: u64 = 10 * 1024 * 1024;
+// Largest request body proxied to a module; past it the client gets a 413.
+const MAX_REQUEST_BODY_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct RunnerState {
+    pub catalog: SharedServiceCatalog,
+    pub health: SharedHealthMap,
+    pub logs: SharedLogMap,
+    pub schedules: SharedScheduler,
+    pub schedule_overrides: SharedScheduleOverrides,
+    pub stats: SharedStats,
+    pub queues: SharedQueueRegistry,
+    pub memory: SharedMemoryMap,
+    pub contracts: SharedContractLog,
+    pub startup: SharedStartupMap,
+    pub modules: SharedModuleRegistry,
+    pub shutdown: SharedShutdown,
+    pub settings: RunnerSettings,
+}
+
+pub fn run_server(state: &RunnerState) -> Result<()> {
+    let port = state.settings.entry_port;
+    let server = Server::http(("0.0.0.0", port))
+        .map_err(|error| anyhow!("failed to bind entrypoint to port {}: {}", port, error))?;
 
-    println!("Runner listening on http://{}:{}", "0.0.0.0", ENTRY_PORT);
-    let router = RoundRobinRouter::new(services);
+    println!("Runner listening on http://0.0.0.0:{}", port);
+    let router = RoundRobinRouter::default();
+    let context = RequestContext {
+        state,
+        router: &router,
+    };
 
-    for request in server.incoming_requests() {
-        if let Err(error) = handle_request(
-            services, health, logs, schedules, stats, queues, memory, &router, request,
-        ) {
+    while !state.shutdown.is_requested() {
+        let request = match server.recv_timeout(Duration::from_millis(SHUTDOWN_POLL_MILLIS)) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(error) => {
+                eprintln!("Failed to accept request: {error}");
+                continue;
+            }
+        };
+        let _in_flight = state.shutdown.track_request();
+        if let Err(error) = handle_request(&context, request) {
             eprintln!("Failed to handle request: {:#}", error);
         }
     }
 
+    // Dropping the server closes the listening socket before the modules go.
+    drop(server);
+    stop_runner(state);
     Ok(())
 }
 
-fn handle_request(
-    services: &[Service],
-    health: &SharedHealthMap,
-    logs: &SharedLogMap,
-    schedules: &SharedScheduleMap,
-    stats: &SharedStats,
-    queues: &SharedQueueRegistry,
-    memory: &SharedMemoryMap,
-    router: &RoundRobinRouter,
-    request: Request,
-) -> Result<()> {
+#[derive(Clone, Copy)]
+struct RequestContext<'a> {
+    state: &'a RunnerState,
+    router: &'a RoundRobinRouter,
+}
+
+fn handle_request(context: &RequestContext, mut request: Request) -> Result<()> {
+    let RequestContext { state, router } = *context;
+    let RunnerState {
+        catalog,
+        logs,
+        schedules,
+        stats,
+        queues,
+        contracts,
+        startup,
+        settings,
+        modules,
+        ..
+    } = state;
+    let catalog = current_catalog(catalog);
+    let services = catalog.services();
     let full_path = request.url().to_owned();
     let (path, query) = match full_path.split_once('?') {
         Some((path, query)) => (path, Some(query)),
@@ -112,6 +166,22 @@ fn handle_request(
 
     let trimmed_path = path.trim_start_matches('/');
 
+    if let Some(rest) = trimmed_path.strip_prefix("__runner__/services/") {
+        let segments: Vec<&str> = rest
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        match (request.method(), segments.as_slice()) {
+            (Method::Post, [service_name, "schedules"]) => {
+                return handle_schedule_edit(state, request, service_name, None, query);
+            }
+            (Method::Patch | Method::Delete, [service_name, "schedules", index]) => {
+                return handle_schedule_edit(state, request, service_name, Some(index), query);
+            }
+            _ => {}
+        }
+    }
+
     if request.method() == &Method::Post {
         if let Some(queue_name) = trimmed_path.strip_prefix("__runner__/queues/") {
             return handle_queue_publish(queues, request, queue_name);
@@ -120,51 +190,123 @@ fn handle_request(
         if let Some(rest) = trimmed_path.strip_prefix("__runner__/services/") {
             return handle_internal_service_control(services, schedules, request, rest);
         }
-
-        let response = Response::from_string("not found").with_status_code(404);
-        request.respond(response)?;
-        return Ok(());
     }
 
-    if request.method() != &Method::Get {
-        let response = Response::from_string("method not allowed").with_status_code(405);
-        request.respond(response)?;
-        return Ok(());
-    }
+    if request.method() == &Method::Get {
+        if trimmed_path == "health" {
+            let response = Response::from_string("ok").with_status_code(200);
+            request.respond(response)?;
+            return Ok(());
+        }
 
-    if trimmed_path == "health" {
-        let response = Response::from_string("ok").with_status_code(200);
-        request.respond(response)?;
-        return Ok(());
-    }
+        if trimmed_path.is_empty() {
+            let response = render_homepage(services, state);
+            request.respond(response)?;
+            return Ok(());
+        }
 
-    if trimmed_path.is_empty() {
-        let response = render_homepage(services, health, queues, schedules, memory);
-        request.respond(response)?;
-        return Ok(());
-    }
+        if trimmed_path == "__runner__/stats" {
+            return handle_stats_request(stats, request);
+        }
 
-    if trimmed_path == "__runner__/stats" {
-        return handle_stats_request(stats, request);
-    }
+        if trimmed_path == "__runner__/contracts" {
+            return handle_contracts_request(contracts, request);
+        }
 
-    if let Some(rest) = trimmed_path.strip_prefix("__runner__/services/") {
-        return handle_internal_service_request(services, logs, request, rest);
+        if trimmed_path == "__runner__/startup" {
+            return handle_startup_request(startup, request);
+        }
+
+        if let Some(rest) = trimmed_path.strip_prefix("__runner__/services/") {
+            return handle_internal_service_request(
+                services, logs, schedules, modules, settings, request, rest,
+            );
+        }
     }
 
-    let Some((service, endpoint_path)) = resolve_service_route(services, trimmed_path) else {
+    if trimmed_path == "__runner__" || trimmed_path.starts_with("__runner__/") {
         let response = Response::from_string("not found").with_status_code(404);
         request.respond(response)?;
         return Ok(());
-    };
+    }
 
-    if !service.supports(request.method(), &endpoint_path) {
+    let Some((service, endpoint_path)) = catalog.resolve_route(trimmed_path) else {
         let response = Response::from_string("not found").with_status_code(404);
         request.respond(response)?;
         return Ok(());
+    };
+
+    let resolved = match service.resolve_operation(request.method(), &endpoint_path) {
+        OperationLookup::Found(resolved) => resolved,
+        OperationLookup::MethodNotAllowed { allowed } => {
+            let mut response = Response::from_string("method not allowed").with_status_code(405);
+            if let Ok(header) = Header::from_bytes(b"Allow", allowed.join(", ").as_bytes()) {
+                response = response.with_header(header);
+            }
+            request.respond(response)?;
+            return Ok(());
+        }
+        OperationLookup::NotFound => {
+            let response = Response::from_string("not found").with_status_code(404);
+            request.respond(response)?;
+            return Ok(());
+        }
+    };
+
+    let content_type = header_value(&request, "Content-Type");
+    let route_template = resolved.operation.template.clone();
+    let body = match read_request_body(request.as_reader()) {
+        Ok(Some(body)) => body,
+        Ok(None) => {
+            record_http_status(stats, &service.name, &route_template, 413);
+            let response = Response::from_string("payload too large").with_status_code(413);
+            request.respond(response)?;
+            return Ok(());
+        }
+        Err(error) => {
+            eprintln!(
+                "Failed to read request body for service '{}': {}",
+                service.name, error
+            );
+            let response = Response::from_string("invalid payload").with_status_code(400);
+            request.respond(response)?;
+            return Ok(());
+        }
+    };
+
+    if service.validate_requests {
+        let headers: Vec<(String, String)> = request
+            .headers()
+            .iter()
+            .map(|header| (header.field.to_string(), header.value.to_string()))
+            .collect();
+        let violations = resolved.operation.validate_request(&RequestParts {
+            path_params: &resolved.params,
+            query,
+            headers: &headers,
+            content_type: content_type.as_deref(),
+            body: &body,
+        });
+
+        if !violations.is_empty() {
+            record_http_status(stats, &service.name, &route_template, 400);
+            let response = build_validation_error(service, resolved.operation, &violations);
+            request.respond(response)?;
+            return Ok(());
+        }
     }
 
-    let selected_base = router.next_base_url(service);
+    let ready = ready_instances(modules, &service.name);
+    let Some(selected_base) = router.next_base_url(service, ready.as_deref()) else {
+        record_http_status(stats, &service.name, &route_template, 503);
+        let response = Response::from_string("no ready instance").with_status_code(503);
+        if let Ok(header) = Header::from_bytes(b"Retry-After", b"1") {
+            request.respond(response.with_header(header))?;
+        } else {
+            request.respond(response)?;
+        }
+        return Ok(());
+    };
     let mut target_url = format!("{}/{}", selected_base.trim_end_matches('/'), endpoint_path);
 
     if let Some(query) = query {
@@ -172,27 +314,102 @@ fn handle_request(
         target_url.push_str(query);
     }
 
-    match ureq::request(request.method().as_str(), &target_url).call() {
-        Ok(response) => {
-            let status = response.status();
-            record_http_status(stats, &service.name, &endpoint_path, status as u16);
-            let response = build_response(response)?;
-            request.respond(response)?;
-        }
-        Err(ureq::Error::Status(_, response)) => {
-            let status = response.status();
-            record_http_status(stats, &service.name, &endpoint_path, status as u16);
-            let response = build_response(response)?;
+    let mut upstream = ureq::request(request.method().as_str(), &target_url);
+    if let Some(ref content_type) = content_type {
+        upstream = upstream.set("Content-Type", content_type);
+    }
+
+    let result = if body.is_empty() {
+        upstream.call()
+    } else {
+        upstream.send_bytes(&body)
+    };
+
+    let upstream_response = match result {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(error) => {
+            eprintln!("Error contacting service '{}': {}", service.name, error);
+            record_http_status(stats, &service.name, &route_template, 502);
+            let response = Response::from_string("upstream error").with_status_code(502);
             request.respond(response)?;
+            return Ok(());
         }
+    };
+
+    let reply = match read_upstream_reply(upstream_response) {
+        Ok(reply) => reply,
         Err(error) => {
-            eprintln!("Error contacting service '{}': {}", service.name, error);
-            record_http_status(stats, &service.name, &endpoint_path, 502);
+            eprintln!(
+                "Error reading response from service '{}': {error:#}",
+                service.name
+            );
+            record_http_status(stats, &service.name, &route_template, 502);
             let response = Response::from_string("upstream error").with_status_code(502);
             request.respond(response)?;
+            return Ok(());
         }
+    };
+    record_http_status(stats, &service.name, &route_template, reply.status);
+
+    if service.monitor_responses {
+        let violations = resolved.operation.validate_response(
+            reply.status,
+            reply.content_type.as_deref(),
+            &reply.body,
+        );
+        if !violations.is_empty() {
+            eprintln!(
+                "Service '{}' broke the contract of {} ({} violations)",
+                service.name,
+                resolved.operation.label(),
+                violations.len()
+            );
+            record_contract_violations(
+                contracts,
+                &service.name,
+                &resolved.operation.label(),
+                reply.status,
+                &violations,
+            );
+        }
+    }
+
+    request.respond(build_response(reply))?;
+    Ok(())
+}
+
+fn handle_contracts_request(contracts: &SharedContractLog, request: Request) -> Result<()> {
+    let snapshot = match contracts.lock() {
+        Ok(log) => log.snapshot(SystemTime::now()),
+        Err(_) => {
+            let response = Response::from_string("contracts unavailable").with_status_code(503);
+            request.respond(response)?;
+            return Ok(());
+        }
+    };
+
+    let body = serde_json::to_string(&snapshot)
+        .map_err(|error| anyhow!("failed to serialize contract snapshot: {error}"))?;
+
+    let mut response = Response::from_string(body).with_status_code(200);
+    if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json; charset=utf-8") {
+        response = response.with_header(header);
     }
 
+    request.respond(response)?;
+    Ok(())
+}
+
+fn handle_startup_request(startup: &SharedStartupMap, request: Request) -> Result<()> {
+    let body = serde_json::to_string(&startup_snapshot(startup))
+        .map_err(|error| anyhow!("failed to serialize startup snapshot: {error}"))?;
+
+    let mut response = Response::from_string(body).with_status_code(200);
+    if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json; charset=utf-8") {
+        response = response.with_header(header);
+    }
+
+    request.respond(response)?;
     Ok(())
 }
 
@@ -221,6 +438,9 @@ fn handle_stats_request(stats: &SharedStats, request: Request) -> Result<()> {
 fn handle_internal_service_request(
     services: &[Service],
     logs: &SharedLogMap,
+    schedules: &SharedScheduler,
+    modules: &SharedModuleRegistry,
+    settings: &RunnerSettings,
     request: Request,
     rest: &str,
 ) -> Result<()> {
@@ -253,7 +473,7 @@ fn handle_internal_service_request(
                 return Ok(());
             }
             let body = match logs.lock() {
-                Ok(store) => match store.get(service_name) {
+                Ok(store) => match store.lines(service_name) {
                     Some(lines) if !lines.is_empty() => {
                         lines.iter().cloned().collect::<Vec<_>>().join("\n")
                     }
@@ -281,7 +501,7 @@ fn handle_internal_service_request(
                 request.respond(response)?;
                 return Ok(());
             }
-            let path = crate::config::openapi_path(service_name);
+            let path = crate::config::openapi_path(&settings.services_dir, service_name);
             match std::fs::read_to_string(&path) {
                 Ok(contents) => {
                     let response = Response::from_string(contents).with_status_code(200);
@@ -299,10 +519,101 @@ fn handle_internal_service_request(
                 }
             }
         }
-        "schedules" => {
-            let response = Response::from_string("method not allowed").with_status_code(405);
+        "instances" => {
+            if !remaining.is_empty() {
+                let response = Response::from_string("not found").with_status_code(404);
+                request.respond(response)?;
+                return Ok(());
+            }
+            let Some(service) = services.iter().find(|service| service.name == service_name) else {
+                let response = Response::from_string("not found").with_status_code(404);
+                request.respond(response)?;
+                return Ok(());
+            };
+            let instances = service_instances(modules, service_name);
+            let payload = json!({
+                "service": service_name,
+                "configured": service.runner_count(),
+                "runtime": service.runtime.as_str(),
+                "managed": instances.is_some(),
+                "instances": instances
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|instance| describe_instance(service, instance))
+                    .collect::<Vec<_>>(),
+            });
+            let mut response = Response::from_string(payload.to_string()).with_status_code(200);
+            if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json") {
+                response = response.with_header(header);
+            }
             request.respond(response)?;
         }
+        "schedules" => match remaining.as_slice() {
+            [] => {
+                let schedules = services
+                    .iter()
+                    .find(|service| service.name == service_name)
+                    .map(|service| describe_schedules(&service.schedules))
+                    .unwrap_or_default();
+                let payload = json!({ "service": service_name, "schedules": schedules });
+                let mut response = Response::from_string(payload.to_string()).with_status_code(200);
+                if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json") {
+                    response = response.with_header(header);
+                }
+                request.respond(response)?;
+            }
+            [index, "runs"] => {
+                let Ok(index) = index.parse::<usize>() else {
+                    let response =
+                        Response::from_string("invalid schedule index").with_status_code(400);
+                    request.respond(response)?;
+                    return Ok(());
+                };
+                match scheduler::schedule_runs(schedules, service_name, index) {
+                    Ok(state) => {
+                        let payload = json!({
+                            "service": service_name,
+                            "index": index,
+                            "method": state
+                                .schedule
+                                .queue
+                                .is_none()
+                                .then_some(&state.schedule.method),
+                            "endpoint": state
+                                .schedule
+                                .queue
+                                .is_none()
+                                .then(|| format!("/{}", state.schedule.endpoint)),
+                            "queue": state.schedule.queue,
+                            "paused": state.paused,
+                            "failing": state.failing(),
+                            "consecutive_failures": state.failures,
+                            "runs": state.runs,
+                        });
+                        let mut response =
+                            Response::from_string(payload.to_string()).with_status_code(200);
+                        if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json")
+                        {
+                            response = response.with_header(header);
+                        }
+                        request.respond(response)?;
+                    }
+                    Err(TriggerError::ServiceNotFound | TriggerError::ScheduleNotFound) => {
+                        let response = Response::from_string("not found").with_status_code(404);
+                        request.respond(response)?;
+                    }
+                    Err(TriggerError::LockPoisoned) => {
+                        let response = Response::from_string("schedule controller unavailable")
+                            .with_status_code(503);
+                        request.respond(response)?;
+                    }
+                }
+            }
+            _ => {
+                let response = Response::from_string("method not allowed").with_status_code(405);
+                request.respond(response)?;
+            }
+        },
         _ => {
             let response = Response::from_string("not found").with_status_code(404);
             request.respond(response)?;
@@ -314,7 +625,7 @@ fn handle_internal_service_request(
 
 fn handle_internal_service_control(
     services: &[Service],
-    schedules: &SharedScheduleMap,
+    schedules: &SharedScheduler,
     request: Request,
     rest: &str,
 ) -> Result<()> {
@@ -344,7 +655,7 @@ fn handle_internal_service_control(
     }
 
     let remaining: Vec<_> = segments.collect();
-    handle_schedule_request(services, service_name, schedules, request, &remaining)
+    handle_schedule_request(service_name, schedules, request, &remaining)
 }
 
 fn handle_queue_publish(
@@ -360,11 +671,8 @@ fn handle_queue_publish(
         return Ok(());
     }
 
-    let content_type = request
-        .headers()
-        .iter()
-        .find(|header| header.field.equiv("Content-Type"))
-        .map(|header| header.value.to_string());
+    let content_type =
+        header_value(&request, "Content-Type").unwrap_or_else(|| "application/json".to_string());
 
     let mut payload = Vec::new();
     if let Err(error) = request.as_reader().read_to_end(&mut payload) {
@@ -375,36 +683,19 @@ fn handle_queue_publish(
     }
 
     let (subscribers, message_count) =
-        match with_queue_registry(queues, |registry| registry.prepare_delivery(queue_name)) {
+        match publish_message(queues, queue_name, &content_type, &payload) {
             Ok(result) => result,
             Err(error) => {
-                eprintln!("Failed to access queue registry: {error}");
+                eprintln!("Failed to publish to queue '{queue_name}': {error:#}");
                 let response = Response::from_string("queue unavailable").with_status_code(500);
                 request.respond(response)?;
                 return Ok(());
             }
         };
 
-    for subscriber in &subscribers {
-        let mut call = ureq::post(&subscriber.target_url).set("X-Rustrunner-Queue", queue_name);
-
-        if let Some(ref ct) = content_type {
-            call = call.set("Content-Type", ct);
-        } else {
-            call = call.set("Content-Type", "application/json");
-        }
-
-        if let Err(error) = call.send_bytes(&payload) {
-            eprintln!(
-                "Failed to deliver queue '{}' event to service '{}' at {}: {}",
-                queue_name, subscriber.service_name, subscriber.target_url, error
-            );
-        }
-    }
-
     let response_body = json!({
         "queue": queue_name,
-        "subscribers": subscribers.len(),
+        "subscribers": subscribers,
         "message_count": message_count,
     })
     .to_string();
@@ -418,10 +709,125 @@ fn handle_queue_publish(
     Ok(())
 }
 
+fn handle_schedule_edit(
+    state: &RunnerState,
+    mut request: Request,
+    service_name: &str,
+    index: Option<&str>,
+    query: Option<&str>,
+) -> Result<()> {
+    let index = match index.map(str::parse::<usize>).transpose() {
+        Ok(index) => index,
+        Err(_) => {
+            let response = Response::from_string("invalid schedule index").with_status_code(400);
+            request.respond(response)?;
+            return Ok(());
+        }
+    };
+
+    let body = if request.method() == &Method::Delete {
+        serde_json::Value::Null
+    } else {
+        let mut payload = String::new();
+        let parsed = request
+            .as_reader()
+            .read_to_string(&mut payload)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(serde_json::from_str(&payload)?));
+        match parsed {
+            Ok(body) => body,
+            Err(error) => {
+                let response = Response::from_string(format!("invalid schedule payload: {error}"))
+                    .with_status_code(400);
+                request.respond(response)?;
+                return Ok(());
+            }
+        }
+    };
+
+    let edit = match (index, request.method()) {
+        (None, _) => ScheduleEdit::Create(body),
+        (Some(index), Method::Patch) => ScheduleEdit::Update(index, body),
+        (Some(index), _) => ScheduleEdit::Delete(index),
+    };
+    let created = matches!(edit, ScheduleEdit::Create(_));
+    let persist = query.is_some_and(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .any(|(key, value)| key == "persist" && matches!(value.as_ref(), "1" | "true"))
+    });
+
+    let response = match edit_service_schedules(state, service_name, edit, persist) {
+        Ok(schedules) => {
+            let mut payload = json!({
+                "service": service_name,
+                "persisted": persist,
+                "schedules": describe_schedules(&schedules),
+            });
+            if created {
+                payload["index"] = json!(schedules.len().saturating_sub(1));
+            }
+            let mut response = Response::from_string(payload.to_string())
+                .with_status_code(if created { 201 } else { 200 });
+            if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json") {
+                response = response.with_header(header);
+            }
+            response
+        }
+        Err(ScheduleEditError::ServiceNotFound | ScheduleEditError::ScheduleNotFound) => {
+            Response::from_string("not found").with_status_code(404)
+        }
+        Err(ScheduleEditError::Invalid(error)) => {
+            Response::from_string(format!("{error:#}")).with_status_code(400)
+        }
+        Err(ScheduleEditError::Failed(error)) => {
+            eprintln!("Failed to change the schedules of service '{service_name}': {error:#}");
+            Response::from_string("schedule change failed").with_status_code(500)
+        }
+    };
+    request.respond(response)?;
+    Ok(())
+}
+
+fn describe_schedules(schedules: &[ServiceSchedule]) -> Vec<serde_json::Value> {
+    schedules
+        .iter()
+        .enumerate()
+        .map(|(index, schedule)| {
+            let mut entry = schedule_to_value(schedule);
+            entry.insert("index".into(), json!(index));
+            entry.insert("origin".into(), json!(schedule.origin.as_str()));
+            serde_json::Value::Object(entry)
+        })
+        .collect()
+}
+
+fn describe_instance(service: &Service, instance: &InstanceSupervision) -> serde_json::Value {
+    let running = instance.lifecycle != InstanceLifecycle::Exited;
+    json!({
+        "instance": instance.instance,
+        "url": service.runner_endpoints().get(instance.instance),
+        "port": instance.port,
+        "pid": instance.pid,
+        "state": instance.lifecycle.as_str(),
+        "started_at": instance.started_at,
+        "uptime_secs": instance
+            .started_at
+            .filter(|_| running)
+            .map(|started| (Utc::now() - started).num_seconds().max(0)),
+        "restarts": instance.restarts,
+        "circuit_open": instance.circuit_open,
+        "last_exit": instance.last_exit.as_ref().map(|exit| json!({
+            "code": exit.code,
+            "description": exit.description,
+            "at": exit.at,
+            "stderr": exit.stderr,
+        })),
+    })
+}
+
 fn handle_schedule_request(
-    services: &[Service],
     service_name: &str,
-    schedules: &SharedScheduleMap,
+    schedules: &SharedScheduler,
     request: Request,
     remaining: &[&str],
 ) -> Result<()> {
@@ -460,58 +866,53 @@ fn handle_schedule_request(
                 request.respond(response)?;
             }
         },
-        "run" => {
-            let Some(service) = services.iter().find(|svc| svc.name == service_name) else {
-                let response = Response::from_string("not found").with_status_code(404);
+        "run" => match scheduler::trigger_schedule_now(schedules, service_name, index) {
+            Ok(outcome) => {
+                let (status_code, payload) = match outcome {
+                    ManualRun::Completed(runs) => {
+                        let state = scheduler::schedule_runs(schedules, service_name, index).ok();
+                        let instances = state.as_ref().map_or(1, ScheduleState::instance_count);
+                        let status_text: Vec<String> = runs
+                            .iter()
+                            .map(|run| describe_run_result(Some(run), instances))
+                            .collect();
+                        let payload = json!({
+                            "status_text": status_text.join(" · "),
+                            "time_text": describe_run_time(runs.first()),
+                            "history_html": state.map(|state| render_schedule_history(&state)).unwrap_or_default(),
+                        });
+                        (200, payload)
+                    }
+                    ManualRun::Queued => (
+                        202,
+                        json!({
+                            "status_text": "En cola: se lanzará al terminar la ejecución en curso.",
+                        }),
+                    ),
+                    ManualRun::Skipped => (
+                        409,
+                        json!({
+                            "status_text": "Omitido: ya hay una ejecución en curso.",
+                        }),
+                    ),
+                };
+                let mut response =
+                    Response::from_string(payload.to_string()).with_status_code(status_code);
+                if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json") {
+                    response = response.with_header(header);
+                }
                 request.respond(response)?;
-                return Ok(());
-            };
-
-            let Some(schedule_config) = service.schedules.get(index) else {
+            }
+            Err(TriggerError::ServiceNotFound | TriggerError::ScheduleNotFound) => {
                 let response = Response::from_string("not found").with_status_code(404);
                 request.respond(response)?;
-                return Ok(());
-            };
-
-            match scheduler::trigger_schedule_now(
-                schedules,
-                service_name,
-                index,
-                &service.base_url,
-                &schedule_config.endpoint,
-            ) {
-                Ok(outcome) => {
-                    let status_text = match (&outcome.last_error, outcome.last_status) {
-                        (Some(error), _) => format!("Último error: {error}"),
-                        (None, Some(status)) => format!("Último HTTP: {status}"),
-                        (None, None) => "Aún no se ha ejecutado.".to_string(),
-                    };
-                    let time_text = match outcome.last_run {
-                        Some(instant) => describe_elapsed("Última ejecución", instant),
-                        None => "Pendiente de la primera ejecución".to_string(),
-                    };
-                    let payload = json!({
-                        "status_text": status_text,
-                        "time_text": time_text,
-                    });
-                    let mut response =
-                        Response::from_string(payload.to_string()).with_status_code(200);
-                    if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json") {
-                        response = response.with_header(header);
-                    }
-                    request.respond(response)?;
-                }
-                Err(TriggerError::ServiceNotFound | TriggerError::ScheduleNotFound) => {
-                    let response = Response::from_string("not found").with_status_code(404);
-                    request.respond(response)?;
-                }
-                Err(TriggerError::LockPoisoned) => {
-                    let response = Response::from_string("schedule controller unavailable")
-                        .with_status_code(503);
-                    request.respond(response)?;
-                }
             }
-        }
+            Err(TriggerError::LockPoisoned) => {
+                let response =
+                    Response::from_string("schedule controller unavailable").with_status_code(503);
+                request.respond(response)?;
+            }
+        },
         _ => {
             let response = Response::from_string("not found").with_status_code(404);
             request.respond(response)?;
@@ -524,21 +925,23 @@ fn handle_schedule_request(
 fn render_domain_sections(
     services: &[Service],
     health: &SharedHealthMap,
-    schedules: &SharedScheduleMap,
+    schedules: &SharedScheduler,
     memory: &SharedMemoryMap,
+    modules: &SharedModuleRegistry,
 ) -> String {
     let health_snapshot = health.lock().map(|map| map.clone()).unwrap_or_default();
-    let schedule_snapshot = schedules.lock().map(|map| map.clone()).unwrap_or_default();
+    let schedule_snapshot = schedules.snapshot();
     let memory_snapshot = memory.lock().map(|map| map.clone()).unwrap_or_default();
+    let supervision = supervision_snapshot(modules);
     let mut groups: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
 
     for service in services {
         let health_info = health_snapshot
             .get(&service.name)
-            .copied()
+            .cloned()
             .unwrap_or_default();
         let status_badge = render_status_badge(health_info.status);
-        let last_checked = match health_info.last_checked {
+        let mut last_checked = match health_info.last_checked {
             Some(instant) => {
                 let seconds = instant.elapsed().as_secs();
                 match seconds {
@@ -549,6 +952,13 @@ fn render_domain_sections(
             }
             None => "Última verificación pendiente".to_string(),
         };
+        if health_info.instances.len() > 1 {
+            last_checked.push_str(&format!(
+                " · {}/{} instancias sanas",
+                health_info.healthy_instances(),
+                health_info.instances.len()
+            ));
+        }
         let schedule_section =
             build_schedule_section(&service.name, schedule_snapshot.get(&service.name));
         let memory_info = memory_snapshot
@@ -556,12 +966,12 @@ fn render_domain_sections(
             .copied()
             .unwrap_or_default();
         let memory_section = render_memory_section(&memory_info);
-
         let card = render_service_card(
             service,
             status_badge.as_str(),
             last_checked.as_str(),
             memory_section.as_str(),
+            supervision.get(&service.name).map(Vec::as_slice),
             schedule_section.as_str(),
         );
         groups
@@ -644,10 +1054,23 @@ fn render_service_card(
     status_badge: &str,
     last_checked: &str,
     memory_section: &str,
+    instances: Option<&[InstanceSupervision]>,
     schedule_section: &str,
 ) -> String {
     let kind_label = service.kind.label();
-    let runner_count = service.runner_count();
+    // Services whose modules run elsewhere only have the configured count.
+    let runner_count = match instances {
+        Some(instances) => format!(
+            "{}/{}",
+            instances
+                .iter()
+                .filter(|instance| instance.pid.is_some())
+                .count(),
+            service.runner_count()
+        ),
+        None => service.runner_count().to_string(),
+    };
+    let process_section = instances.map(render_process_section).unwrap_or_default();
 
     format!(
         concat!(
@@ -663,6 +1086,7 @@ fn render_service_card(
             "          <span>Prefijo: <code class=\"text-slate-200\">{prefix}</code></span>",
             "          <span>Base URL: <code class=\"text-slate-200\">{base_url}</code></span>",
             "          <span>Copias activas: <span class=\"text-slate-200\">{runner_count}</span></span>",
+            "          <span>Runtime: <code class=\"text-slate-200\">{runtime}</code></span>",
             "        </div>",
             "      </div>",
             "      <div class=\"flex flex-col items-start gap-3 sm:items-end\">",
@@ -675,6 +1099,7 @@ fn render_service_card(
             "    </div>",
             "    <p class=\"text-xs text-slate-500\">{last_checked}</p>",
             "    {memory_section}",
+            "    {process_section}",
             "    {schedule_section}",
             "  </div>",
             "</li>"
@@ -685,9 +1110,11 @@ fn render_service_card(
         prefix = escape_html(&service.prefix),
         base_url = escape_html(&service.base_url),
         runner_count = runner_count,
+        runtime = service.runtime.as_str(),
         status_badge = status_badge,
         last_checked = escape_html(last_checked),
         memory_section = memory_section,
+        process_section = process_section,
         schedule_section = schedule_section
     )
 }
@@ -818,6 +1245,153 @@ fn render_queue_table(snapshot: &[QueueSnapshot]) -> String {
     )
 }
 
+fn render_startup_section(snapshot: &[StartupSnapshot]) -> String {
+    if snapshot.is_empty() {
+        return "<p class=\"text-sm text-slate-400\">No hay servicios en arranque.</p>".to_string();
+    }
+
+    let ready = snapshot
+        .iter()
+        .filter(|entry| entry.phase == StartupPhase::Ready)
+        .count();
+    let percent = ready * 100 / snapshot.len();
+    let mut rows = String::new();
+
+    for entry in snapshot {
+        let badge_class = match entry.phase {
+            StartupPhase::Waiting => "border-slate-600 bg-slate-800/70 text-slate-300",
+            StartupPhase::Starting => "border-amber-400/40 bg-amber-500/10 text-amber-200",
+            StartupPhase::Ready => "border-emerald-400/40 bg-emerald-500/10 text-emerald-200",
+            StartupPhase::Failed => "border-rose-400/40 bg-rose-500/10 text-rose-200",
+        };
+        let detail = if !entry.waiting_on.is_empty() {
+            format!("Esperando a {}", entry.waiting_on.join(", "))
+        } else {
+            entry.detail.clone().unwrap_or_default()
+        };
+        rows.push_str(&format!(
+            concat!(
+                "<tr class=\"border-b border-slate-800/60 last:border-b-0\">",
+                "  <td class=\"px-4 py-3 text-slate-400\">{order}</td>",
+                "  <td class=\"whitespace-nowrap px-4 py-3 font-medium text-slate-200\"><code>{service}</code></td>",
+                "  <td class=\"px-4 py-3\"><span class=\"inline-flex rounded-full border px-3 py-1 text-xs font-semibold {badge_class}\">{phase}</span></td>",
+                "  <td class=\"px-4 py-3 text-slate-400\">{detail}</td>",
+                "  <td class=\"px-4 py-3 text-right text-slate-500\">{elapsed}</td>",
+                "</tr>"
+            ),
+            order = entry.order + 1,
+            service = escape_html(&entry.service),
+            badge_class = badge_class,
+            phase = escape_html(entry.phase.label()),
+            detail = escape_html(&detail),
+            elapsed = escape_html(&format!("hace {} s", entry.elapsed_secs)),
+        ));
+    }
+
+    format!(
+        concat!(
+            "<p class=\"text-sm text-slate-300\">{ready} de {total} servicios listos</p>",
+            "<div class=\"mt-2 h-2 w-full rounded-full bg-slate-800/80\">",
+            "  <div class=\"h-full rounded-full bg-emerald-400\" style=\"width: {percent}%\"></div>",
+            "</div>",
+            "<div class=\"mt-4 overflow-hidden rounded-xl border border-slate-800/80\">",
+            "  <table class=\"min-w-full divide-y divide-slate-800/80 text-sm\">",
+            "    <thead class=\"bg-slate-900/80 text-slate-300\">",
+            "      <tr>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">#</th>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">Servicio</th>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">Estado</th>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">Detalle</th>",
+            "        <th class=\"px-4 py-3 text-right font-semibold uppercase tracking-wider\">Último cambio</th>",
+            "      </tr>",
+            "    </thead>",
+            "    <tbody>{rows}</tbody>",
+            "  </table>",
+            "</div>"
+        ),
+        ready = ready,
+        total = snapshot.len(),
+        percent = percent,
+        rows = rows
+    )
+}
+
+fn render_contract_section(contracts: &SharedContractLog) -> String {
+    let snapshot = match contracts.lock() {
+        Ok(log) => log.snapshot(SystemTime::now()),
+        Err(_) => {
+            return "<p class=\"text-sm text-rose-300\">No se pudo obtener el registro de contratos en este momento.</p>".to_string();
+        }
+    };
+
+    if snapshot.services.is_empty() {
+        return "<p class=\"text-sm text-slate-400\">No se han detectado incumplimientos de contrato.</p>"
+            .to_string();
+    }
+
+    render_contract_table(&snapshot)
+}
+
+fn render_contract_table(snapshot: &ContractSnapshot) -> String {
+    let mut rows = String::new();
+
+    for service in &snapshot.services {
+        for endpoint in &service.endpoints {
+            let latest = endpoint
+                .recent
+                .first()
+                .map(|record| {
+                    format!(
+                        "HTTP {} · {}: {}",
+                        record.status, record.location, record.message
+                    )
+                })
+                .unwrap_or_default();
+            let seen = endpoint
+                .last_seen
+                .map(|seen| {
+                    describe_elapsed_secs("Último", snapshot.generated_at.saturating_sub(seen))
+                })
+                .unwrap_or_default();
+
+            rows.push_str(&format!(
+                concat!(
+                    "<tr class=\"border-b border-slate-800/60 last:border-b-0\">",
+                    "  <td class=\"whitespace-nowrap px-4 py-3 font-medium text-slate-200\">{}</td>",
+                    "  <td class=\"whitespace-nowrap px-4 py-3 text-slate-300\"><code>{}</code></td>",
+                    "  <td class=\"px-4 py-3 text-right text-rose-200\">{}</td>",
+                    "  <td class=\"px-4 py-3 text-slate-300\">{}<p class=\"text-xs text-slate-500\">{}</p></td>",
+                    "</tr>"
+                ),
+                escape_html(&service.service),
+                escape_html(&endpoint.endpoint),
+                endpoint.total,
+                escape_html(&latest),
+                escape_html(&seen)
+            ));
+        }
+    }
+
+    format!(
+        concat!(
+            "<div class=\"overflow-hidden rounded-xl border border-slate-800/80\">",
+            "  <table class=\"min-w-full divide-y divide-slate-800/80 text-sm\">",
+            "    <thead class=\"bg-slate-900/80 text-slate-300\">",
+            "      <tr>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">Servicio</th>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">Operación</th>",
+            "        <th class=\"px-4 py-3 text-right font-semibold uppercase tracking-wider\">Incumplimientos</th>",
+            "        <th class=\"px-4 py-3 text-left font-semibold uppercase tracking-wider\">Último detalle</th>",
+            "      </tr>",
+            "    </thead>",
+            "    <tbody class=\"bg-slate-950/30\">{}</tbody>",
+            "  </table>",
+            "</div>"
+        ),
+        rows
+    )
+}
+
 fn build_schedule_section(service_name: &str, entries: Option<&Vec<ScheduleState>>) -> String {
     let Some(entries) = entries else {
         return concat!(
@@ -842,26 +1416,28 @@ fn build_schedule_section(service_name: &str, entries: Option<&Vec<ScheduleState
     let mut items = String::new();
 
     for (index, state) in entries.iter().enumerate() {
-        let endpoint_display = format!("/{}", state.endpoint);
+        let endpoint_display = match &state.schedule.queue {
+            Some(queue) => format!("cola {queue}"),
+            None => format!("/{}", state.schedule.endpoint),
+        };
         let state_label = if state.paused {
-            "⏸️ Pausado"
+            "⏸️ Pausado".to_string()
+        } else if state.failing() {
+            format!("⚠️ Fallando ({} seguidas)", state.failures)
         } else {
-            "▶️ En ejecución"
+            "▶️ En ejecución".to_string()
         };
         let button_label = if state.paused { "Reanudar" } else { "Pausar" };
         let paused_attr = if state.paused { "true" } else { "false" };
 
-        let status_text = if let Some(error) = &state.last_error {
-            format!("Último error: {error}")
-        } else if let Some(status) = state.last_status {
-            format!("Último HTTP: {status}")
-        } else {
-            "Aún no se ha ejecutado.".to_string()
-        };
-
-        let time_text = match state.last_run {
-            Some(instant) => describe_elapsed("Última ejecución", instant),
-            None => "Pendiente de la primera ejecución".to_string(),
+        let status_text = describe_run_result(state.last_run(), state.instance_count());
+        let time_text = describe_run_time(state.last_run());
+        let next_text = describe_next_run(state);
+        let policy_text = describe_schedule_policy(state);
+        let origin_badge = match state.schedule.origin {
+            ScheduleOrigin::Config => "",
+            ScheduleOrigin::Added => "<span class=\"schedule-item__origin rounded-full border border-amber-500/40 bg-amber-500/10 px-2 py-0.5 text-[10px] font-semibold uppercase tracking-wide text-amber-200\" title=\"No está en service.json: se perderá al reiniciar el runner\">Añadido en caliente</span>",
+            ScheduleOrigin::Edited => "<span class=\"schedule-item__origin rounded-full border border-amber-500/40 bg-amber-500/10 px-2 py-0.5 text-[10px] font-semibold uppercase tracking-wide text-amber-200\" title=\"Difiere de service.json: se perderá al reiniciar el runner\">Modificado en caliente</span>",
         };
 
         items.push_str(&format!(
@@ -869,8 +1445,8 @@ fn build_schedule_section(service_name: &str, entries: Option<&Vec<ScheduleState
                 "<li class=\"schedule-item rounded-lg border border-slate-800/80 bg-slate-900/50 p-4\" data-service=\"{service}\" data-index=\"{index}\">",
                 "  <div class=\"schedule-item__header flex flex-wrap gap-3 sm:items-center sm:justify-between\">",
                 "    <div class=\"schedule-item__info flex min-w-[200px] flex-col gap-1\">",
-                "      <span class=\"font-medium text-slate-200\"><code>{endpoint}</code></span>",
-                "      <span class=\"schedule-item__meta text-sm text-slate-400\">Cada {interval}s · <span class=\"schedule-item__state font-semibold text-slate-200\">{state_label}</span></span>",
+                "      <span class=\"flex flex-wrap items-center gap-2 font-medium text-slate-200\"><code>{endpoint}</code>{origin_badge}</span>",
+                "      <span class=\"schedule-item__meta text-sm text-slate-400\">{trigger} · <span class=\"schedule-item__state font-semibold text-slate-200\">{state_label}</span></span>",
                 "    </div>",
                 "    <div class=\"schedule-item__actions flex flex-wrap gap-2\">",
                 "      <button type=\"button\" class=\"schedule-run inline-flex items-center justify-center rounded-lg border border-emerald-500/40 bg-emerald-500/10 px-3 py-1.5 text-xs font-semibold uppercase tracking-wide text-emerald-200 transition hover:bg-emerald-500/20 focus:outline-none focus:ring focus:ring-emerald-500/40\" data-service=\"{service}\" data-index=\"{index}\">Lanzar ahora</button>",
@@ -880,18 +1456,25 @@ fn build_schedule_section(service_name: &str, entries: Option<&Vec<ScheduleState
                 "  <div class=\"schedule-item__details mt-3 flex flex-col gap-1 text-xs text-slate-400\">",
                 "    <span class=\"schedule-item__result\">{status_text}</span>",
                 "    <span class=\"schedule-item__time\">{time_text}</span>",
+                "    <span class=\"schedule-item__next\">{next_text}</span>",
+                "    <span class=\"schedule-item__policy\">{policy_text}</span>",
                 "  </div>",
+                "  {history}",
                 "</li>"
             ),
             service = escape_html(service_name),
             index = index,
             endpoint = escape_html(&endpoint_display),
-            interval = state.interval_secs,
+            origin_badge = origin_badge,
+            trigger = describe_trigger(&state.schedule.trigger),
             state_label = state_label,
             paused = paused_attr,
             button_label = button_label,
             status_text = escape_html(&status_text),
-            time_text = escape_html(&time_text)
+            time_text = escape_html(&time_text),
+            next_text = escape_html(&next_text),
+            policy_text = escape_html(&policy_text),
+            history = render_schedule_history(state)
         ));
     }
 
@@ -906,6 +1489,157 @@ fn build_schedule_section(service_name: &str, entries: Option<&Vec<ScheduleState
     )
 }
 
+fn describe_run_result(run: Option<&ScheduleRun>, instances: usize) -> String {
+    let text = match run {
+        Some(ScheduleRun {
+            error: Some(error), ..
+        }) => format!("Último error: {error}"),
+        Some(ScheduleRun {
+            status: Some(status),
+            ..
+        }) => format!("Último HTTP: {status}"),
+        _ => return "Aún no se ha ejecutado.".to_string(),
+    };
+    match run.and_then(|run| run.instance) {
+        Some(instance) if instances > 1 => format!("{text} (instancia #{instance})"),
+        _ => text,
+    }
+}
+
+fn describe_run_time(run: Option<&ScheduleRun>) -> String {
+    match run {
+        Some(run) => describe_elapsed_since("Última ejecución", run.started_at),
+        None => "Pendiente de la primera ejecución".to_string(),
+    }
+}
+
+fn render_schedule_history(state: &ScheduleState) -> String {
+    if state.runs.is_empty() {
+        return "<div class=\"schedule-item__history\"></div>".to_string();
+    }
+
+    let timezone = state.schedule.trigger.timezone();
+    let rows: String = state
+        .runs
+        .iter()
+        .map(|run| {
+            let mut outcome = match (&run.error, run.status) {
+                (Some(error), _) => format!("Error: {error}"),
+                (None, Some(status)) => format!("HTTP {status}"),
+                (None, None) => "Sin respuesta".to_string(),
+            };
+            if run.attempt > 1 {
+                outcome.push_str(&format!(" (intento {})", run.attempt));
+            }
+            format!(
+                concat!(
+                    "<tr class=\"border-t border-slate-800/60\">",
+                    "<td class=\"py-1 pr-3 whitespace-nowrap\">{started}</td>",
+                    "<td class=\"py-1 pr-3 whitespace-nowrap\">{duration} ms</td>",
+                    "<td class=\"py-1 pr-3 whitespace-nowrap\">{instance}</td>",
+                    "<td class=\"py-1 pr-3\">{outcome}</td>",
+                    "<td class=\"py-1\"><code class=\"break-all text-slate-500\">{body}</code></td>",
+                    "</tr>"
+                ),
+                started = run
+                    .started_at
+                    .with_timezone(&timezone)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                duration = run.duration_ms,
+                instance = run
+                    .instance
+                    .map(|instance| format!("#{instance}"))
+                    .unwrap_or_else(|| "—".to_string()),
+                outcome = escape_html(&outcome),
+                body = escape_html(run.response_body.as_deref().unwrap_or("")),
+            )
+        })
+        .collect();
+
+    format!(
+        concat!(
+            "<details class=\"schedule-item__history mt-3 text-xs text-slate-400\">",
+            "<summary class=\"cursor-pointer text-slate-300\">Historial ({count} ejecuciones, hora {timezone})</summary>",
+            "<table class=\"mt-2 w-full text-left\"><tbody>{rows}</tbody></table>",
+            "</details>"
+        ),
+        count = state.runs.len(),
+        timezone = timezone.name(),
+        rows = rows
+    )
+}
+
+fn describe_schedule_policy(state: &ScheduleState) -> String {
+    let concurrency = match state.schedule.concurrency {
+        ConcurrencyPolicy::Skip => "omite disparos mientras hay una ejecución en curso",
+        ConcurrencyPolicy::Queue => "encola un disparo mientras hay una ejecución en curso",
+        ConcurrencyPolicy::Allow => "permite ejecuciones simultáneas",
+    };
+    let catch_up = match state.schedule.catch_up {
+        CatchUpPolicy::None => "sin recuperación",
+        CatchUpPolicy::Once => "recupera una vez lo perdido",
+        CatchUpPolicy::All => "recupera cada ejecución perdida",
+    };
+    let mut text = format!("Política: {concurrency} · {catch_up}");
+    if state.instance_count() > 1 && state.schedule.queue.is_none() {
+        let target = match state.schedule.target {
+            ScheduleTarget::RoundRobin => "reparte las llamadas entre las instancias",
+            ScheduleTarget::AnyHealthy => "llama a una instancia sana",
+            ScheduleTarget::Broadcast => "llama a todas las instancias",
+        };
+        text.push_str(&format!(" · {target}"));
+    }
+    if state.schedule.jitter_secs > 0 {
+        text.push_str(&format!(
+            " · retraso aleatorio de hasta {}s",
+            state.schedule.jitter_secs
+        ));
+    }
+    if state.schedule.retry.max_attempts > 1 {
+        text.push_str(&format!(
+            " · hasta {} intentos, reintenta a los {}s",
+            state.schedule.retry.max_attempts, state.schedule.retry.backoff_secs
+        ));
+    }
+    if state.running > 0 {
+        text.push_str(&format!(" · {} en curso", state.running));
+    }
+    if state.has_queued_run() {
+        text.push_str(" · 1 en cola");
+    }
+    text
+}
+
+fn describe_trigger(trigger: &ScheduleTrigger) -> String {
+    match trigger {
+        ScheduleTrigger::Interval { secs } => format!("Cada {secs}s"),
+        ScheduleTrigger::Cron {
+            expression,
+            timezone,
+        } => format!(
+            "Cron <code>{}</code> ({})",
+            escape_html(expression),
+            timezone.name()
+        ),
+    }
+}
+
+// Shown in the schedule's own time zone so cron entries read as configured.
+fn describe_next_run(state: &ScheduleState) -> String {
+    if state.paused {
+        return "Próxima ejecución: en pausa".to_string();
+    }
+    match state.next_run {
+        Some(next) => format!(
+            "Próxima ejecución: {} ({})",
+            next.with_timezone(&state.schedule.trigger.timezone())
+                .format("%Y-%m-%d %H:%M:%S"),
+            state.schedule.trigger.timezone().name()
+        ),
+        None => "Próxima ejecución: sin fecha prevista".to_string(),
+    }
+}
+
 fn render_memory_section(snapshot: &ServiceMemorySnapshot) -> String {
     let description = match (snapshot.usage_bytes, snapshot.limit_bytes) {
         (Some(usage), Some(limit)) if limit > 0 => {
@@ -960,8 +1694,104 @@ fn render_memory_section(snapshot: &ServiceMemorySnapshot) -> String {
     )
 }
 
+fn render_process_section(instances: &[InstanceSupervision]) -> String {
+    if instances.is_empty() {
+        return String::new();
+    }
+
+    let rows: String = instances
+        .iter()
+        .map(|instance| {
+            let state = match instance.lifecycle {
+                InstanceLifecycle::Starting => "⏳ Iniciando",
+                InstanceLifecycle::Ready => "🟢 Lista",
+                InstanceLifecycle::Draining => "🟠 Deteniéndose",
+                InstanceLifecycle::Exited if instance.circuit_open => {
+                    "⛔ Detenida: demasiados reinicios"
+                }
+                InstanceLifecycle::Exited => "⚪️ Detenida",
+            };
+            let mut details = Vec::new();
+            if let Some(pid) = instance.pid {
+                details.push(format!("PID {pid}"));
+            }
+            if let Some(port) = instance.port {
+                details.push(format!("puerto {port}"));
+            }
+            if let (Some(started), true) = (
+                instance.started_at,
+                instance.lifecycle != InstanceLifecycle::Exited,
+            ) {
+                details.push(describe_elapsed_since("Arrancó", started));
+            }
+            details.push(format!("Reinicios: {}", instance.restarts));
+            let (exit, stderr) = match &instance.last_exit {
+                Some(exit) => {
+                    let code = exit
+                        .code
+                        .map(|code| format!("código {code}"))
+                        .unwrap_or_else(|| exit.description.clone());
+                    let stderr = if exit.stderr.is_empty() {
+                        String::new()
+                    } else {
+                        format!(
+                            concat!(
+                                "<details class=\"mt-1\">",
+                                "  <summary class=\"cursor-pointer text-slate-500\">Últimas líneas de stderr</summary>",
+                                "  <pre class=\"mt-1 overflow-x-auto rounded-lg bg-slate-950/70 p-2 text-rose-200\">{lines}</pre>",
+                                "</details>"
+                            ),
+                            lines = escape_html(&exit.stderr.join("\n"))
+                        )
+                    };
+                    (
+                        format!("{} ({code})", describe_elapsed_since("Terminó", exit.at)),
+                        stderr,
+                    )
+                }
+                None => ("Sin salidas".to_string(), String::new()),
+            };
+            format!(
+                concat!(
+                    "<li>",
+                    "  <div class=\"flex flex-wrap justify-between gap-2\">",
+                    "    <span class=\"text-slate-200\">Instancia {index} · {state}</span>",
+                    "    <span class=\"text-slate-500\">{details}</span>",
+                    "  </div>",
+                    "  <p class=\"text-slate-500\">Última salida: {exit}</p>",
+                    "  {stderr}",
+                    "</li>"
+                ),
+                index = instance.instance,
+                state = state,
+                details = escape_html(&details.join(" · ")),
+                exit = escape_html(&exit),
+                stderr = stderr
+            )
+        })
+        .collect();
+
+    format!(
+        concat!(
+            "<div class=\"rounded-2xl border border-slate-800/80 bg-slate-950/40 p-4\">",
+            "  <p class=\"text-xs font-semibold uppercase tracking-wide text-slate-400\">Procesos</p>",
+            "  <ul class=\"mt-2 space-y-2 text-xs\">{rows}</ul>",
+            "</div>"
+        ),
+        rows = rows
+    )
+}
+
 fn describe_elapsed(prefix: &str, instant: Instant) -> String {
-    let seconds = instant.elapsed().as_secs();
+    describe_elapsed_secs(prefix, instant.elapsed().as_secs())
+}
+
+fn describe_elapsed_since(prefix: &str, at: DateTime<Utc>) -> String {
+    let seconds = (Utc::now() - at).num_seconds().max(0);
+    describe_elapsed_secs(prefix, seconds.unsigned_abs())
+}
+
+fn describe_elapsed_secs(prefix: &str, seconds: u64) -> String {
     match seconds {
         0 => format!("{prefix} hace menos de un segundo"),
         1 => format!("{prefix} hace 1 segundo"),
@@ -1002,49 +1832,102 @@ fn escape_html(input: &str) -> String {
     escaped
 }
 
-fn resolve_service_route<'a>(
-    services: &'a [Service],
-    trimmed_path: &str,
-) -> Option<(&'a Service, String)> {
-    for service in services {
-        if let Some(rest) = trimmed_path.strip_prefix(&service.prefix) {
-            let endpoint_path = rest.trim_start_matches('/');
-            if endpoint_path.is_empty() {
-                continue;
-            }
-            return Some((service, endpoint_path.to_string()));
-        }
+fn build_validation_error(
+    service: &Service,
+    operation: &Operation,
+    violations: &[SchemaViolation],
+) -> Response<Cursor<Vec<u8>>> {
+    let details: Vec<_> = violations
+        .iter()
+        .map(|violation| {
+            json!({
+                "location": violation.location,
+                "message": violation.message,
+            })
+        })
+        .collect();
+    let payload = json!({
+        "error": "request_validation_failed",
+        "service": service.name,
+        "operation": operation.label(),
+        "violations": details,
+    });
+
+    let mut response = Response::from_string(payload.to_string()).with_status_code(400);
+    if let Ok(header) = Header::from_bytes(b"Content-Type", b"application/json; charset=utf-8") {
+        response = response.with_header(header);
     }
-    None
+    response
+}
+
+fn header_value(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
 }
 
-fn build_response(upstream: ureq::Response) -> Result<Response<Cursor<Vec<u8>>>> {
+// `None` when the body is larger than `MAX_REQUEST_BODY_BYTES`.
+fn read_request_body(reader: &mut dyn Read) -> std::io::Result<Option<Vec<u8>>> {
+    let mut body = Vec::new();
+    reader
+        .take(MAX_REQUEST_BODY_BYTES + 1)
+        .read_to_end(&mut body)?;
+    Ok((body.len() as u64 <= MAX_REQUEST_BODY_BYTES).then_some(body))
+}
+
+struct UpstreamReply {
+    status: u16,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+fn read_upstream_reply(upstream: ureq::Response) -> Result<UpstreamReply> {
     let status = upstream.status();
     let content_type = upstream
         .header("Content-Type")
         .map(|value| value.to_owned());
-    let body = upstream
-        .into_string()
+    let mut body = Vec::new();
+    upstream
+        .into_reader()
+        .take(MAX_UPSTREAM_BODY_BYTES + 1)
+        .read_to_end(&mut body)
         .map_err(|error| anyhow!("failed to read upstream response body: {error}"))?;
+    if body.len() as u64 > MAX_UPSTREAM_BODY_BYTES {
+        bail!("upstream response body is larger than {MAX_UPSTREAM_BODY_BYTES} bytes");
+    }
+
+    Ok(UpstreamReply {
+        status,
+        content_type,
+        body,
+    })
+}
 
-    let mut response = Response::from_string(body).with_status_code(status);
+fn build_response(reply: UpstreamReply) -> Response<Cursor<Vec<u8>>> {
+    let mut response = Response::from_data(reply.body).with_status_code(reply.status);
 
-    if let Some(content_type) = content_type {
+    if let Some(content_type) = reply.content_type {
         if let Ok(header) = Header::from_bytes(b"Content-Type", content_type.as_bytes()) {
             response = response.with_header(header);
         }
     }
 
-    Ok(response)
+    response
 }
 
-fn render_homepage(
-    services: &[Service],
-    health: &SharedHealthMap,
-    queues: &SharedQueueRegistry,
-    schedules: &SharedScheduleMap,
-    memory: &SharedMemoryMap,
-) -> Response<Cursor<Vec<u8>>> {
+fn render_homepage(services: &[Service], state: &RunnerState) -> Response<Cursor<Vec<u8>>> {
+    let RunnerState {
+        health,
+        queues,
+        schedules,
+        memory,
+        modules,
+        contracts,
+        startup,
+        ..
+    } = state;
     let service_section = if services.is_empty() {
         concat!(
             "<section class=\"rounded-2xl border border-slate-800 bg-slate-900/60 p-6 shadow-glow shadow-slate-950/30\">",
@@ -1053,16 +1936,20 @@ fn render_homepage(
         )
         .to_string()
     } else {
-        render_domain_sections(services, health, schedules, memory)
+        render_domain_sections(services, health, schedules, memory, modules)
     };
 
+    let startup_section = render_startup_section(&startup_snapshot(startup));
     let queue_section = render_queue_section(queues);
+    let contract_section = render_contract_section(contracts);
 
     let html = templates::render(
         templates::DASHBOARD,
         &[
+            ("startup_section", startup_section.as_str()),
             ("service_section", service_section.as_str()),
             ("queue_section", queue_section.as_str()),
+            ("contract_section", contract_section.as_str()),
         ],
     );
 
@@ -1076,25 +1963,30 @@ fn render_homepage(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alerts::AlertSink;
+    use crate::catalog::initialize_service_catalog;
+    use crate::config::{RestartMode, RestartPolicy, RuntimeConfig};
+    use crate::contracts::initialize_contract_log;
+    use crate::logs::initialize_log_store;
+    use crate::memory::initialize_memory_store;
+    use crate::openapi::OperationTable;
+    use crate::process::{
+        initialize_module_registry, register_service_modules, start_service_modules,
+    };
+    use crate::queue::initialize_queue_registry;
+    use crate::runtime_schedules::initialize_schedule_overrides;
+    use crate::secrets::SecretStore;
+    use crate::shutdown::initialize_shutdown;
+    use crate::startup::initialize_startup_map;
+    use crate::stats::initialize_stats_store;
+    use std::path::Path;
 
     #[test]
     fn resolve_service_route_matches_prefix() {
-        let service = Service {
-            name: "svc".into(),
-            domain: "demo".into(),
-            kind: ServiceKind::Business,
-            prefix: "svc".into(),
-            base_url: "http://localhost:1234".into(),
-            runner_urls: vec!["http://localhost:1234".into()],
-            allowed_get_endpoints: Default::default(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
-            memory_limit_mb: None,
-            runner_instances: 1,
-        };
-        let services = vec![service];
+        let service = Service::for_test("svc");
+        let catalog = ServiceCatalog::build(vec![service]).expect("catalog");
 
-        let result = resolve_service_route(&services, "svc/ping");
+        let result = catalog.resolve_route("svc/ping");
         assert!(result.is_some());
         let (service, endpoint) = result.unwrap();
         assert_eq!(service.name, "svc");
@@ -1104,20 +1996,176 @@ mod tests {
     #[test]
     fn resolve_service_route_ignores_empty_endpoint() {
         let service = Service {
-            name: "svc".into(),
-            domain: "demo".into(),
             kind: ServiceKind::Adapter,
-            prefix: "svc".into(),
-            base_url: "http://localhost:1234".into(),
-            runner_urls: vec!["http://localhost:1234".into()],
-            allowed_get_endpoints: Default::default(),
-            queue_listeners: Vec::new(),
-            schedules: Vec::new(),
-            memory_limit_mb: None,
-            runner_instances: 1,
+            ..Service::for_test("svc")
+        };
+        let catalog = ServiceCatalog::build(vec![service]).expect("catalog");
+
+        assert!(catalog.resolve_route("svc/").is_none());
+    }
+
+    #[test]
+    fn router_only_picks_ready_instances() {
+        let service = Service {
+            base_url: "http://localhost:1000".into(),
+            runner_urls: vec![
+                "http://localhost:1000".into(),
+                "http://localhost:1001".into(),
+                "http://localhost:1002".into(),
+            ],
+            runner_instances: 3,
+            ..Service::for_test("svc")
+        };
+        let router = RoundRobinRouter::default();
+
+        let picks: Vec<Option<String>> = (0..3)
+            .map(|_| router.next_base_url(&service, Some(&[0, 2])))
+            .collect();
+        assert_eq!(
+            picks,
+            vec![
+                Some("http://localhost:1000".to_string()),
+                Some("http://localhost:1002".to_string()),
+                Some("http://localhost:1000".to_string()),
+            ]
+        );
+        assert_eq!(router.next_base_url(&service, Some(&[])), None);
+        assert_eq!(
+            router.next_base_url(&service, None),
+            Some("http://localhost:1000".to_string())
+        );
+    }
+
+    #[test]
+    fn upstream_reply_is_capped() {
+        let small = ureq::Response::new(200, "OK", "pong").expect("response");
+        assert_eq!(read_upstream_reply(small).expect("reply").body, b"pong");
+
+        let body = "x".repeat(MAX_UPSTREAM_BODY_BYTES as usize + 1);
+        let large = ureq::Response::new(200, "OK", &body).expect("response");
+        assert!(read_upstream_reply(large).is_err());
+    }
+
+    #[test]
+    fn request_body_is_capped() {
+        let mut small: &[u8] = b"{}";
+        assert_eq!(read_request_body(&mut small).unwrap(), Some(b"{}".to_vec()));
+
+        let body = vec![b'x'; MAX_REQUEST_BODY_BYTES as usize + 1];
+        assert_eq!(read_request_body(&mut body.as_slice()).unwrap(), None);
+    }
+
+    // The module behind `routes_requests_to_a_natively_run_module`: the test
+    // binary runs itself with only this test selected. Answers every path,
+    // `/health` included, with the method and path it received.
+    #[test]
+    #[ignore]
+    fn echo_module() {
+        let Ok(port) = std::env::var("WR_RUNNER_PORT") else {
+            return;
+        };
+        let server = Server::http(("127.0.0.1", port.parse::<u16>().expect("port"))).expect("bind");
+        for request in server.incoming_requests() {
+            let body = format!("{} {}", request.method(), request.url());
+            let _ = request.respond(Response::from_string(body));
+        }
+    }
+
+    #[test]
+    fn routes_requests_to_a_natively_run_module() {
+        let free_port = || {
+            std::net::TcpListener::bind("127.0.0.1:0")
+                .and_then(|listener| listener.local_addr())
+                .expect("free port")
+                .port()
+        };
+        let module_url = format!("http://127.0.0.1:{}", free_port());
+        let test_binary = std::env::current_exe().expect("test binary");
+        let service = Service {
+            base_url: module_url.clone(),
+            runner_urls: vec![module_url],
+            operations: OperationTable::from_document(&json!({
+                "paths": { "/ping": { "get": {} } }
+            }))
+            .expect("operations"),
+            restart: RestartPolicy {
+                mode: RestartMode::Never,
+                ..RestartPolicy::default()
+            },
+            runtime: RuntimeConfig::Native {
+                command: test_binary.display().to_string(),
+                args: ["server::tests::echo_module", "--exact", "--ignored"]
+                    .map(String::from)
+                    .into(),
+            },
+            ..Service::for_test("svc")
         };
         let services = vec![service];
+        let settings = RunnerSettings {
+            entry_port: free_port(),
+            shutdown_drain_secs: 1,
+            shutdown_grace_secs: 1,
+            ..RunnerSettings::default()
+        };
+
+        let logs = initialize_log_store(&services, 10);
+        let memory = initialize_memory_store(&services);
+        let modules = initialize_module_registry();
+        let handles = start_service_modules(
+            &services,
+            Path::new("."),
+            &SecretStore::default(),
+            &logs,
+            &memory,
+        )
+        .expect("start modules");
+        register_service_modules(&modules, handles);
+        let health = SharedHealthMap::default();
+        let queues = initialize_queue_registry(&services);
+        let schedules = scheduler::start_webhook_schedulers(
+            &services,
+            &health,
+            &modules,
+            &queues,
+            AlertSink::default(),
+            &settings,
+        );
+        let state = RunnerState {
+            catalog: initialize_service_catalog(ServiceCatalog::build(services).expect("catalog")),
+            health,
+            logs,
+            schedules,
+            schedule_overrides: initialize_schedule_overrides(),
+            stats: initialize_stats_store(),
+            queues,
+            memory,
+            contracts: initialize_contract_log(),
+            startup: initialize_startup_map(),
+            modules,
+            shutdown: initialize_shutdown(),
+            settings,
+        };
+        let server = std::thread::spawn({
+            let state = state.clone();
+            move || run_server(&state)
+        });
+
+        // The gateway answers 503 until the readiness probe sees the module.
+        let url = format!("http://127.0.0.1:{}/svc/ping", state.settings.entry_port);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let body = loop {
+            match ureq::get(&url).call() {
+                Ok(response) => break response.into_string().expect("body"),
+                Err(_) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(error) => panic!("the module never answered through the gateway: {error}"),
+            }
+        };
+        assert_eq!(body, "GET /ping");
 
-        assert!(resolve_service_route(&services, "svc/").is_none());
+        state.shutdown.request();
+        server.join().expect("server thread").expect("server");
+        assert_eq!(ready_instances(&state.modules, "svc"), None);
     }
 }
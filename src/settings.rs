@@ -0,0 +1,487 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+
+pub const DEFAULT_CONFIG_FILE: &str = "rustrunner.toml";
+pub const CONFIG_ENV_VAR: &str = "RUSTRUNNER_CONFIG";
+
+pub const DEFAULT_ENTRY_PORT: u16 = 14000;
+pub const DEFAULT_SERVICES_DIR: &str = "services";
+pub const DEFAULT_SECRETS_FILE: &str = "secrets.toml";
+pub const DEFAULT_HEALTH_POLL_INTERVAL_SECS: u64 = 5;
+pub const DEFAULT_HEALTH_REQUEST_TIMEOUT_SECS: u64 = 2;
+pub const DEFAULT_SCHEDULE_REQUEST_TIMEOUT_SECS: u64 = 5;
+pub const DEFAULT_MAX_STORED_LOG_LINES: usize = 200;
+pub const DEFAULT_RELOAD_POLL_INTERVAL_SECS: u64 = 2;
+pub const DEFAULT_STARTUP_TIMEOUT_SECS: u64 = 60;
+pub const DEFAULT_STATE_DIR: &str = ".rustrunner";
+pub const DEFAULT_SCHEDULE_HISTORY_LIMIT: usize = 20;
+pub const DEFAULT_SCHEDULE_WORKERS: usize = 4;
+pub const DEFAULT_SHUTDOWN_DRAIN_SECS: u64 = 10;
+pub const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunnerSettings {
+    pub entry_port: u16,
+    pub services_dir: PathBuf,
+    pub secrets_file: PathBuf,
+    pub health_poll_interval_secs: u64,
+    pub health_request_timeout_secs: u64,
+    pub schedule_request_timeout_secs: u64,
+    pub max_stored_log_lines: usize,
+    pub reload_poll_interval_secs: u64,
+    pub startup_timeout_secs: u64,
+    pub state_dir: PathBuf,
+    pub schedule_history_limit: usize,
+    pub schedule_workers: usize,
+    pub alert_webhook_url: Option<String>,
+    pub alert_queue: Option<String>,
+    pub shutdown_drain_secs: u64,
+    pub shutdown_grace_secs: u64,
+}
+
+impl Default for RunnerSettings {
+    fn default() -> Self {
+        Self {
+            entry_port: DEFAULT_ENTRY_PORT,
+            services_dir: PathBuf::from(DEFAULT_SERVICES_DIR),
+            secrets_file: PathBuf::from(DEFAULT_SECRETS_FILE),
+            health_poll_interval_secs: DEFAULT_HEALTH_POLL_INTERVAL_SECS,
+            health_request_timeout_secs: DEFAULT_HEALTH_REQUEST_TIMEOUT_SECS,
+            schedule_request_timeout_secs: DEFAULT_SCHEDULE_REQUEST_TIMEOUT_SECS,
+            max_stored_log_lines: DEFAULT_MAX_STORED_LOG_LINES,
+            reload_poll_interval_secs: DEFAULT_RELOAD_POLL_INTERVAL_SECS,
+            startup_timeout_secs: DEFAULT_STARTUP_TIMEOUT_SECS,
+            state_dir: PathBuf::from(DEFAULT_STATE_DIR),
+            schedule_history_limit: DEFAULT_SCHEDULE_HISTORY_LIMIT,
+            schedule_workers: DEFAULT_SCHEDULE_WORKERS,
+            alert_webhook_url: None,
+            alert_queue: None,
+            shutdown_drain_secs: DEFAULT_SHUTDOWN_DRAIN_SECS,
+            shutdown_grace_secs: DEFAULT_SHUTDOWN_GRACE_SECS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsOverrides {
+    pub entry_port: Option<u16>,
+    pub services_dir: Option<PathBuf>,
+    pub secrets_file: Option<PathBuf>,
+    pub health_poll_interval_secs: Option<u64>,
+    pub health_request_timeout_secs: Option<u64>,
+    pub schedule_request_timeout_secs: Option<u64>,
+    pub max_stored_log_lines: Option<usize>,
+    pub reload_poll_interval_secs: Option<u64>,
+    pub startup_timeout_secs: Option<u64>,
+    pub state_dir: Option<PathBuf>,
+    pub schedule_history_limit: Option<usize>,
+    pub schedule_workers: Option<usize>,
+    pub alert_webhook_url: Option<String>,
+    pub alert_queue: Option<String>,
+    pub shutdown_drain_secs: Option<u64>,
+    pub shutdown_grace_secs: Option<u64>,
+}
+
+struct SettingKey {
+    file_key: &'static str,
+    env_var: &'static str,
+    cli_flag: &'static str,
+}
+
+const SETTING_KEYS: [SettingKey; 16] = [
+    SettingKey {
+        file_key: "entry_port",
+        env_var: "RUSTRUNNER_ENTRY_PORT",
+        cli_flag: "--port",
+    },
+    SettingKey {
+        file_key: "services_dir",
+        env_var: "RUSTRUNNER_SERVICES_DIR",
+        cli_flag: "--services-dir",
+    },
+    SettingKey {
+        file_key: "secrets_file",
+        env_var: "RUSTRUNNER_SECRETS_FILE",
+        cli_flag: "--secrets-file",
+    },
+    SettingKey {
+        file_key: "health_poll_interval_secs",
+        env_var: "RUSTRUNNER_HEALTH_POLL_INTERVAL_SECS",
+        cli_flag: "--health-interval",
+    },
+    SettingKey {
+        file_key: "health_request_timeout_secs",
+        env_var: "RUSTRUNNER_HEALTH_REQUEST_TIMEOUT_SECS",
+        cli_flag: "--health-timeout",
+    },
+    SettingKey {
+        file_key: "schedule_request_timeout_secs",
+        env_var: "RUSTRUNNER_SCHEDULE_REQUEST_TIMEOUT_SECS",
+        cli_flag: "--schedule-timeout",
+    },
+    SettingKey {
+        file_key: "max_stored_log_lines",
+        env_var: "RUSTRUNNER_MAX_STORED_LOG_LINES",
+        cli_flag: "--max-log-lines",
+    },
+    SettingKey {
+        file_key: "reload_poll_interval_secs",
+        env_var: "RUSTRUNNER_RELOAD_POLL_INTERVAL_SECS",
+        cli_flag: "--reload-interval",
+    },
+    SettingKey {
+        file_key: "startup_timeout_secs",
+        env_var: "RUSTRUNNER_STARTUP_TIMEOUT_SECS",
+        cli_flag: "--startup-timeout",
+    },
+    SettingKey {
+        file_key: "state_dir",
+        env_var: "RUSTRUNNER_STATE_DIR",
+        cli_flag: "--state-dir",
+    },
+    SettingKey {
+        file_key: "schedule_history_limit",
+        env_var: "RUSTRUNNER_SCHEDULE_HISTORY_LIMIT",
+        cli_flag: "--schedule-history",
+    },
+    SettingKey {
+        file_key: "schedule_workers",
+        env_var: "RUSTRUNNER_SCHEDULE_WORKERS",
+        cli_flag: "--schedule-workers",
+    },
+    SettingKey {
+        file_key: "alert_webhook_url",
+        env_var: "RUSTRUNNER_ALERT_WEBHOOK_URL",
+        cli_flag: "--alert-webhook",
+    },
+    SettingKey {
+        file_key: "alert_queue",
+        env_var: "RUSTRUNNER_ALERT_QUEUE",
+        cli_flag: "--alert-queue",
+    },
+    SettingKey {
+        file_key: "shutdown_drain_secs",
+        env_var: "RUSTRUNNER_SHUTDOWN_DRAIN_SECS",
+        cli_flag: "--shutdown-drain",
+    },
+    SettingKey {
+        file_key: "shutdown_grace_secs",
+        env_var: "RUSTRUNNER_SHUTDOWN_GRACE_SECS",
+        cli_flag: "--shutdown-grace",
+    },
+];
+
+impl SettingsOverrides {
+    pub fn is_setting_flag(flag: &str) -> bool {
+        SETTING_KEYS.iter().any(|key| key.cli_flag == flag)
+    }
+
+    pub fn set_from_flag(&mut self, flag: &str, value: &str) -> Result<()> {
+        let key = SETTING_KEYS
+            .iter()
+            .find(|key| key.cli_flag == flag)
+            .ok_or_else(|| anyhow!("unknown setting flag '{flag}'"))?;
+        self.set(key.file_key, value)
+            .with_context(|| format!("invalid value for '{flag}'"))
+    }
+
+    fn from_env<F>(lookup: F) -> Result<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut overrides = SettingsOverrides::default();
+        for key in &SETTING_KEYS {
+            if let Some(value) = lookup(key.env_var) {
+                overrides.set(key.file_key, &value).with_context(|| {
+                    format!("invalid value in environment variable {}", key.env_var)
+                })?;
+            }
+        }
+        Ok(overrides)
+    }
+
+    fn set(&mut self, file_key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        match file_key {
+            "entry_port" => self.entry_port = Some(parse_number(value)?),
+            "services_dir" => {
+                if value.is_empty() {
+                    bail!("services directory cannot be empty");
+                }
+                self.services_dir = Some(PathBuf::from(value));
+            }
+            "secrets_file" => {
+                if value.is_empty() {
+                    bail!("secrets file cannot be empty");
+                }
+                self.secrets_file = Some(PathBuf::from(value));
+            }
+            "health_poll_interval_secs" => {
+                self.health_poll_interval_secs = Some(parse_number(value)?)
+            }
+            "health_request_timeout_secs" => {
+                self.health_request_timeout_secs = Some(parse_number(value)?)
+            }
+            "schedule_request_timeout_secs" => {
+                self.schedule_request_timeout_secs = Some(parse_number(value)?)
+            }
+            "max_stored_log_lines" => self.max_stored_log_lines = Some(parse_number(value)?),
+            "reload_poll_interval_secs" => {
+                self.reload_poll_interval_secs = Some(parse_number(value)?)
+            }
+            "startup_timeout_secs" => self.startup_timeout_secs = Some(parse_number(value)?),
+            "state_dir" => {
+                if value.is_empty() {
+                    bail!("state directory cannot be empty");
+                }
+                self.state_dir = Some(PathBuf::from(value));
+            }
+            "schedule_history_limit" => self.schedule_history_limit = Some(parse_number(value)?),
+            "schedule_workers" => self.schedule_workers = Some(parse_number(value)?),
+            "alert_webhook_url" => self.alert_webhook_url = Some(value.to_string()),
+            "alert_queue" => self.alert_queue = Some(value.to_string()),
+            "shutdown_drain_secs" => self.shutdown_drain_secs = Some(parse_number(value)?),
+            "shutdown_grace_secs" => self.shutdown_grace_secs = Some(parse_number(value)?),
+            other => bail!("unknown setting '{other}'"),
+        }
+        Ok(())
+    }
+
+    fn apply(&self, settings: &mut RunnerSettings) {
+        if let Some(port) = self.entry_port {
+            settings.entry_port = port;
+        }
+        if let Some(dir) = &self.services_dir {
+            settings.services_dir = dir.clone();
+        }
+        if let Some(path) = &self.secrets_file {
+            settings.secrets_file = path.clone();
+        }
+        if let Some(interval) = self.health_poll_interval_secs {
+            settings.health_poll_interval_secs = interval;
+        }
+        if let Some(timeout) = self.health_request_timeout_secs {
+            settings.health_request_timeout_secs = timeout;
+        }
+        if let Some(timeout) = self.schedule_request_timeout_secs {
+            settings.schedule_request_timeout_secs = timeout;
+        }
+        if let Some(lines) = self.max_stored_log_lines {
+            settings.max_stored_log_lines = lines;
+        }
+        if let Some(interval) = self.reload_poll_interval_secs {
+            settings.reload_poll_interval_secs = interval;
+        }
+        if let Some(timeout) = self.startup_timeout_secs {
+            settings.startup_timeout_secs = timeout;
+        }
+        if let Some(dir) = &self.state_dir {
+            settings.state_dir = dir.clone();
+        }
+        if let Some(limit) = self.schedule_history_limit {
+            settings.schedule_history_limit = limit;
+        }
+        if let Some(workers) = self.schedule_workers {
+            settings.schedule_workers = workers;
+        }
+        // An empty value turns off a destination set at a lower level.
+        if let Some(url) = &self.alert_webhook_url {
+            settings.alert_webhook_url = Some(url.trim().to_string()).filter(|url| !url.is_empty());
+        }
+        if let Some(queue) = &self.alert_queue {
+            settings.alert_queue = Some(queue.trim().to_string()).filter(|queue| !queue.is_empty());
+        }
+        if let Some(secs) = self.shutdown_drain_secs {
+            settings.shutdown_drain_secs = secs;
+        }
+        if let Some(secs) = self.shutdown_grace_secs {
+            settings.shutdown_grace_secs = secs;
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("'{value}' is not a valid number"))
+}
+
+pub fn load_settings(
+    config_file: Option<&Path>,
+    cli_overrides: &SettingsOverrides,
+) -> Result<RunnerSettings> {
+    resolve_settings(config_file, cli_overrides, |name| std::env::var(name).ok())
+}
+
+fn resolve_settings<F>(
+    config_file: Option<&Path>,
+    cli_overrides: &SettingsOverrides,
+    env_lookup: F,
+) -> Result<RunnerSettings>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut settings = RunnerSettings::default();
+
+    let explicit_file = config_file
+        .map(Path::to_path_buf)
+        .or_else(|| env_lookup(CONFIG_ENV_VAR).map(PathBuf::from));
+
+    let file_overrides = match explicit_file {
+        Some(path) => Some(read_settings_file(&path)?),
+        None => {
+            let default_path = Path::new(DEFAULT_CONFIG_FILE);
+            if default_path.exists() {
+                Some(read_settings_file(default_path)?)
+            } else {
+                None
+            }
+        }
+    };
+
+    if let Some(overrides) = file_overrides {
+        overrides.apply(&mut settings);
+    }
+
+    SettingsOverrides::from_env(&env_lookup)?.apply(&mut settings);
+    cli_overrides.apply(&mut settings);
+
+    validate_settings(&settings)?;
+    Ok(settings)
+}
+
+fn read_settings_file(path: &Path) -> Result<SettingsOverrides> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read runner configuration at {}", path.display()))?;
+
+    toml::from_str(&contents)
+        .with_context(|| format!("failed to parse runner configuration at {}", path.display()))
+}
+
+fn validate_settings(settings: &RunnerSettings) -> Result<()> {
+    if settings.entry_port == 0 {
+        bail!("entry_port must be greater than zero");
+    }
+
+    if settings.health_poll_interval_secs == 0 {
+        bail!("health_poll_interval_secs must be greater than zero");
+    }
+
+    if settings.health_request_timeout_secs == 0 {
+        bail!("health_request_timeout_secs must be greater than zero");
+    }
+
+    if settings.schedule_request_timeout_secs == 0 {
+        bail!("schedule_request_timeout_secs must be greater than zero");
+    }
+
+    if settings.max_stored_log_lines == 0 {
+        bail!("max_stored_log_lines must be greater than zero");
+    }
+
+    if settings.startup_timeout_secs == 0 {
+        bail!("startup_timeout_secs must be greater than zero");
+    }
+
+    if settings.schedule_history_limit == 0 {
+        bail!("schedule_history_limit must be greater than zero");
+    }
+
+    if settings.schedule_workers == 0 {
+        bail!("schedule_workers must be greater than zero");
+    }
+
+    if let Some(url) = &settings.alert_webhook_url {
+        let parsed = url::Url::parse(url)
+            .map_err(|error| anyhow!("alert_webhook_url '{url}' is not a valid URL: {error}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            bail!("alert_webhook_url must use http or https");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::env;
+
+    fn temp_config(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("rustrunner-{}-{name}.toml", std::process::id()));
+        fs::write(&path, contents).expect("write config");
+        path
+    }
+
+    #[test]
+    fn resolves_file_env_and_cli_with_increasing_precedence() {
+        let path = temp_config(
+            "precedence",
+            "entry_port = 15000\nservices_dir = \"catalog\"\nhealth_poll_interval_secs = 30\n",
+        );
+        let env = HashMap::from([
+            ("RUSTRUNNER_ENTRY_PORT", "16000"),
+            ("RUSTRUNNER_MAX_STORED_LOG_LINES", "50"),
+            ("RUSTRUNNER_ALERT_QUEUE", "ops.alerts"),
+            ("RUSTRUNNER_SHUTDOWN_GRACE_SECS", "0"),
+        ]);
+        let mut cli = SettingsOverrides::default();
+        cli.set_from_flag("--port", "17000").expect("cli flag");
+
+        let settings = resolve_settings(Some(&path), &cli, |name| {
+            env.get(name).map(|value| value.to_string())
+        })
+        .expect("settings");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(settings.entry_port, 17000);
+        assert_eq!(settings.services_dir, PathBuf::from("catalog"));
+        assert_eq!(settings.health_poll_interval_secs, 30);
+        assert_eq!(settings.max_stored_log_lines, 50);
+        assert_eq!(settings.alert_queue.as_deref(), Some("ops.alerts"));
+        assert_eq!(settings.alert_webhook_url, None);
+        assert_eq!(settings.shutdown_grace_secs, 0);
+        assert_eq!(settings.shutdown_drain_secs, DEFAULT_SHUTDOWN_DRAIN_SECS);
+        assert_eq!(
+            settings.schedule_request_timeout_secs,
+            DEFAULT_SCHEDULE_REQUEST_TIMEOUT_SECS
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_invalid_values() {
+        let path = temp_config("unknown", "entry_prot = 1\n");
+        let error =
+            resolve_settings(Some(&path), &SettingsOverrides::default(), |_| None).unwrap_err();
+        let _ = fs::remove_file(&path);
+        assert!(format!("{error:#}").contains("unknown field"));
+
+        // An explicit empty file keeps a ./rustrunner.toml out of the checks.
+        let empty = temp_config("empty", "");
+        let mut cli = SettingsOverrides::default();
+        assert!(cli.set_from_flag("--health-interval", "soon").is_err());
+
+        cli.set_from_flag("--health-interval", "0").expect("parse");
+        let error = resolve_settings(Some(&empty), &cli, |_| None).unwrap_err();
+        assert!(error.to_string().contains("health_poll_interval_secs"));
+
+        let mut cli = SettingsOverrides::default();
+        cli.set_from_flag("--alert-webhook", "ftp://ops.example.com")
+            .expect("parse");
+        let error = resolve_settings(Some(&empty), &cli, |_| None).unwrap_err();
+        assert!(error.to_string().contains("alert_webhook_url"));
+
+        let error = resolve_settings(Some(&empty), &SettingsOverrides::default(), |name| {
+            (name == "RUSTRUNNER_ENTRY_PORT").then(|| "99999".to_string())
+        })
+        .unwrap_err();
+        let _ = fs::remove_file(&empty);
+        assert!(format!("{error:#}").contains("RUSTRUNNER_ENTRY_PORT"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::process::stop_all_modules;
+use crate::queue::{with_queue_registry, QueueRegistry};
+use crate::server::RunnerState;
+use crate::startup::cancel_all_startups;
+
+const DRAIN_POLL_MILLIS: u64 = 50;
+
+#[derive(Default)]
+pub struct Shutdown {
+    requested: AtomicBool,
+    in_flight: AtomicUsize,
+    // Held for the whole stop sequence, so whoever comes second waits for it
+    // to finish instead of letting the process exit halfway.
+    stopped: Mutex<bool>,
+}
+
+pub type SharedShutdown = Arc<Shutdown>;
+
+pub struct InFlightRequest<'a> {
+    shutdown: &'a Shutdown,
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        self.shutdown.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Shutdown {
+    // Returns false when a shutdown was already under way.
+    pub fn request(&self) -> bool {
+        !self.requested.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub fn track_request(&self) -> InFlightRequest<'_> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlightRequest { shutdown: self }
+    }
+
+    fn wait_for_requests(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.in_flight.load(Ordering::SeqCst) > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(DRAIN_POLL_MILLIS));
+        }
+        true
+    }
+}
+
+pub fn initialize_shutdown() -> SharedShutdown {
+    Arc::new(Shutdown::default())
+}
+
+// SIGINT and SIGTERM only flag the shutdown: the server loop notices it, stops
+// accepting connections and calls `stop_runner`. When a proxied request is
+// still running after the drain period, the handler stops the runner itself.
+pub fn install_signal_handler(state: &RunnerState) -> Result<()> {
+    let state = state.clone();
+    ctrlc::set_handler(move || {
+        if !state.shutdown.request() {
+            println!("Shutdown already in progress");
+            return;
+        }
+        println!("Shutting down, no longer accepting requests");
+        let drain = Duration::from_secs(state.settings.shutdown_drain_secs);
+        if state.shutdown.wait_for_requests(drain) {
+            return;
+        }
+        eprintln!(
+            "Requests still in flight after {}s, stopping anyway",
+            drain.as_secs()
+        );
+        stop_runner(&state);
+        std::process::exit(1);
+    })
+    .context("failed to install the shutdown signal handler")
+}
+
+// Scheduled runs get the drain period to finish; modules then get SIGTERM and
+// the grace period before they are killed.
+pub fn stop_runner(state: &RunnerState) {
+    let Ok(mut stopped) = state.shutdown.stopped.lock() else {
+        return;
+    };
+    if *stopped {
+        return;
+    }
+
+    let settings = &state.settings;
+    cancel_all_startups(&state.startup);
+    if !state
+        .schedules
+        .shutdown(Duration::from_secs(settings.shutdown_drain_secs))
+    {
+        eprintln!(
+            "Scheduled webhooks still running after {}s, stopping anyway",
+            settings.shutdown_drain_secs
+        );
+    }
+    if let Err(error) = with_queue_registry(&state.queues, QueueRegistry::close) {
+        eprintln!("Failed to close the queues: {error:#}");
+    }
+    stop_all_modules(
+        &state.modules,
+        Duration::from_secs(settings.shutdown_grace_secs),
+    );
+
+    *stopped = true;
+    println!("Runner stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_tracked_requests_until_the_deadline() {
+        let shutdown = initialize_shutdown();
+        assert!(shutdown.request());
+        assert!(!shutdown.request());
+        assert!(shutdown.is_requested());
+
+        let request = shutdown.track_request();
+        assert!(!shutdown.wait_for_requests(Duration::from_millis(60)));
+        drop(request);
+        assert!(shutdown.wait_for_requests(Duration::ZERO));
+    }
+}
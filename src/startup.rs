@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::catalog::current_catalog;
+use crate::config::{dependency_order, Service};
+use crate::logs::record_log_line;
+use crate::ports::ensure_ports_available;
+use crate::process::{
+    all_instances_ready, expect_service_modules, register_service_modules, start_service_modules,
+    stop_module_handles, stop_service_modules,
+};
+use crate::secrets::SecretStore;
+use crate::server::RunnerState;
+
+const STARTUP_POLL_MILLIS: u64 = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPhase {
+    Waiting,
+    Starting,
+    Ready,
+    Failed,
+}
+
+impl StartupPhase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            StartupPhase::Waiting => "Esperando dependencias",
+            StartupPhase::Starting => "Arrancando",
+            StartupPhase::Ready => "Listo",
+            StartupPhase::Failed => "Fallido",
+        }
+    }
+
+    fn is_settled(&self) -> bool {
+        matches!(self, StartupPhase::Ready | StartupPhase::Failed)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StartupStatus {
+    pub phase: StartupPhase,
+    pub order: usize,
+    pub waiting_on: Vec<String>,
+    pub detail: Option<String>,
+    pub since: Instant,
+    generation: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StartupSnapshot {
+    pub service: String,
+    pub phase: StartupPhase,
+    pub order: usize,
+    pub waiting_on: Vec<String>,
+    pub detail: Option<String>,
+    pub elapsed_secs: u64,
+}
+
+pub type SharedStartupMap = Arc<Mutex<HashMap<String, StartupStatus>>>;
+
+static NEXT_STARTUP_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+pub fn initialize_startup_map() -> SharedStartupMap {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+pub fn startup_snapshot(startup: &SharedStartupMap) -> Vec<StartupSnapshot> {
+    let Ok(map) = startup.lock() else {
+        return Vec::new();
+    };
+
+    let mut snapshot: Vec<StartupSnapshot> = map
+        .iter()
+        .map(|(name, status)| StartupSnapshot {
+            service: name.clone(),
+            phase: status.phase,
+            order: status.order,
+            waiting_on: status.waiting_on.clone(),
+            detail: status.detail.clone(),
+            elapsed_secs: status.since.elapsed().as_secs(),
+        })
+        .collect();
+    snapshot.sort_by(|a, b| {
+        a.order
+            .cmp(&b.order)
+            .then_with(|| a.service.cmp(&b.service))
+    });
+    snapshot
+}
+
+// Forgetting an entry makes the thread that owns it give up on its next check,
+// so a reload can replace or drop a service that is still starting.
+pub fn cancel_startup(startup: &SharedStartupMap, service_name: &str) {
+    if let Ok(mut map) = startup.lock() {
+        map.remove(service_name);
+    }
+}
+
+pub fn cancel_all_startups(startup: &SharedStartupMap) {
+    if let Ok(mut map) = startup.lock() {
+        map.clear();
+    }
+}
+
+// Every service gets its own thread that waits for the services it depends on
+// to settle, launches its modules and then waits until every copy is ready
+// (port open and `/health` answering). Services without a dependency between
+// them start in parallel.
+pub fn launch_services(state: &RunnerState, services: &[Service], secrets: &Arc<SecretStore>) {
+    let catalog = current_catalog(&state.catalog);
+    let order: Vec<String> = dependency_order(catalog.services())
+        .map(|ordered| ordered.iter().map(|service| service.name.clone()).collect())
+        .unwrap_or_default();
+    let position = |name: &str| {
+        order
+            .iter()
+            .position(|candidate| candidate == name)
+            .unwrap_or(order.len())
+    };
+
+    let mut launches = Vec::new();
+    if let Ok(mut map) = state.startup.lock() {
+        for service in services {
+            let generation = NEXT_STARTUP_GENERATION.fetch_add(1, Ordering::Relaxed);
+            map.insert(
+                service.name.clone(),
+                StartupStatus {
+                    phase: StartupPhase::Waiting,
+                    order: position(&service.name),
+                    waiting_on: service.depends_on.clone(),
+                    detail: None,
+                    since: Instant::now(),
+                    generation,
+                },
+            );
+            expect_service_modules(&state.modules, &service.name);
+            launches.push((service.clone(), generation));
+        }
+    }
+
+    launches.sort_by_key(|(service, _)| position(&service.name));
+    for (service, generation) in launches {
+        let state = state.clone();
+        let secrets = Arc::clone(secrets);
+        let spawned = thread::Builder::new()
+            .name(format!("startup-{}", service.name))
+            .spawn(move || start_service(&state, &service, generation, &secrets));
+        if let Err(error) = spawned {
+            eprintln!("failed to spawn startup thread: {error}");
+        }
+    }
+}
+
+fn start_service(state: &RunnerState, service: &Service, generation: u64, secrets: &SecretStore) {
+    let Some(unavailable) = wait_for_dependencies(&state.startup, service, generation) else {
+        return;
+    };
+
+    let detail = (!unavailable.is_empty()).then(|| {
+        format!(
+            "Arrancado sin que respondieran sus dependencias: {}",
+            unavailable.join(", ")
+        )
+    });
+    if let Some(message) = &detail {
+        record_log_line(
+            &service.name,
+            &format!("[WARN] {message}"),
+            "warn",
+            &state.logs,
+        );
+    }
+    if !update_status(&state.startup, &service.name, generation, |status| {
+        status.phase = StartupPhase::Starting;
+        status.detail = detail;
+    }) {
+        return;
+    }
+
+    let settings = &state.settings;
+    let handles = match ensure_ports_available(service).and_then(|()| {
+        start_service_modules(
+            std::slice::from_ref(service),
+            &settings.services_dir,
+            secrets,
+            &state.logs,
+            &state.memory,
+        )
+    }) {
+        Ok(handles) => handles,
+        Err(error) => {
+            eprintln!("Failed to start service '{}': {error:#}", service.name);
+            // Nothing of ours is running, so routing falls back to the
+            // configured URLs instead of waiting for instances that never come.
+            if update_status(&state.startup, &service.name, generation, |status| {
+                status.phase = StartupPhase::Failed;
+                status.detail = Some(format!("{error:#}"));
+            }) {
+                stop_service_modules(&state.modules, &service.name, Duration::ZERO);
+            }
+            return;
+        }
+    };
+
+    // Registering under the startup lock keeps a concurrent reload from
+    // missing these handles when it stops the service.
+    let mut handles = Some(handles);
+    if let Ok(map) = state.startup.lock() {
+        if map
+            .get(&service.name)
+            .is_some_and(|status| status.generation == generation)
+        {
+            if let Some(handles) = handles.take() {
+                register_service_modules(&state.modules, handles);
+            }
+        }
+    }
+    if let Some(stale) = handles {
+        stop_module_handles(stale, Duration::from_secs(settings.shutdown_grace_secs));
+        return;
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(settings.startup_timeout_secs);
+    loop {
+        if !is_current(&state.startup, &service.name, generation) {
+            return;
+        }
+
+        if all_instances_ready(&state.modules, &service.name) {
+            update_status(&state.startup, &service.name, generation, |status| {
+                status.phase = StartupPhase::Ready;
+            });
+            println!("Service '{}' is ready", service.name);
+            return;
+        }
+
+        if Instant::now() >= deadline {
+            let message = format!(
+                "No quedó lista (puerto y /health) tras {} segundos",
+                settings.startup_timeout_secs
+            );
+            record_log_line(
+                &service.name,
+                &format!("[ERROR] {message}"),
+                "error",
+                &state.logs,
+            );
+            update_status(&state.startup, &service.name, generation, |status| {
+                status.phase = StartupPhase::Failed;
+                status.detail = Some(message);
+            });
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(STARTUP_POLL_MILLIS));
+    }
+}
+
+// Returns the dependencies that failed to come up, or `None` once this launch
+// has been superseded.
+fn wait_for_dependencies(
+    startup: &SharedStartupMap,
+    service: &Service,
+    generation: u64,
+) -> Option<Vec<String>> {
+    loop {
+        {
+            let mut map = startup.lock().ok()?;
+            let (pending, failed) = dependency_progress(&map, service);
+            let status = map
+                .get_mut(&service.name)
+                .filter(|status| status.generation == generation)?;
+
+            if pending.is_empty() {
+                status.waiting_on.clear();
+                return Some(failed);
+            }
+            status.waiting_on = pending;
+        }
+
+        thread::sleep(Duration::from_millis(STARTUP_POLL_MILLIS));
+    }
+}
+
+// Dependencies without an entry are not being started by the runner, so there
+// is nothing to wait for.
+fn dependency_progress(
+    map: &HashMap<String, StartupStatus>,
+    service: &Service,
+) -> (Vec<String>, Vec<String>) {
+    let mut pending = Vec::new();
+    let mut failed = Vec::new();
+
+    for dependency in &service.depends_on {
+        match map.get(dependency).map(|status| status.phase) {
+            Some(StartupPhase::Failed) => failed.push(dependency.clone()),
+            Some(phase) if !phase.is_settled() => pending.push(dependency.clone()),
+            _ => {}
+        }
+    }
+
+    (pending, failed)
+}
+
+fn is_current(startup: &SharedStartupMap, service_name: &str, generation: u64) -> bool {
+    startup
+        .lock()
+        .map(|map| {
+            map.get(service_name)
+                .is_some_and(|status| status.generation == generation)
+        })
+        .unwrap_or(false)
+}
+
+fn update_status<F>(
+    startup: &SharedStartupMap,
+    service_name: &str,
+    generation: u64,
+    update: F,
+) -> bool
+where
+    F: FnOnce(&mut StartupStatus),
+{
+    let Ok(mut map) = startup.lock() else {
+        return false;
+    };
+    match map.get_mut(service_name) {
+        Some(status) if status.generation == generation => {
+            update(status);
+            status.since = Instant::now();
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, depends_on: &[&str]) -> Service {
+        Service {
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
+            ..Service::for_test(name)
+        }
+    }
+
+    fn status(phase: StartupPhase) -> StartupStatus {
+        StartupStatus {
+            phase,
+            order: 0,
+            waiting_on: Vec::new(),
+            detail: None,
+            since: Instant::now(),
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn waits_only_on_dependencies_that_have_not_settled() {
+        let mut map = HashMap::new();
+        map.insert("ready".to_string(), status(StartupPhase::Ready));
+        map.insert("booting".to_string(), status(StartupPhase::Starting));
+        map.insert("broken".to_string(), status(StartupPhase::Failed));
+
+        let bff = service("bff", &["ready", "booting", "broken", "unmanaged"]);
+        let (pending, failed) = dependency_progress(&map, &bff);
+        assert_eq!(pending, vec!["booting"]);
+        assert_eq!(failed, vec!["broken"]);
+
+        map.insert("booting".to_string(), status(StartupPhase::Ready));
+        let (pending, _) = dependency_progress(&map, &bff);
+        assert!(pending.is_empty());
+    }
+}
@@ -8,9 +8,11 @@ const MAX_MINUTES: u64 = 60;
 
 pub type SharedStats = Arc<Mutex<StatsStore>>;
 
+type MinuteBuckets = BTreeMap<u64, HashMap<u16, u32>>;
+
 #[derive(Debug, Default)]
 pub struct StatsStore {
-    data: HashMap<String, HashMap<String, BTreeMap<u64, HashMap<u16, u32>>>>,
+    data: HashMap<String, HashMap<String, MinuteBuckets>>,
 }
 
 #[derive(Debug, Default, Serialize)]
@@ -47,16 +49,11 @@ impl StatsStore {
 
         let minute = elapsed.as_secs() / 60;
 
-        let service_entry = self
-            .data
-            .entry(service.to_string())
-            .or_insert_with(HashMap::new);
+        let service_entry = self.data.entry(service.to_string()).or_default();
 
-        let endpoint_entry = service_entry
-            .entry(endpoint.to_string())
-            .or_insert_with(BTreeMap::new);
+        let endpoint_entry = service_entry.entry(endpoint.to_string()).or_default();
 
-        let bucket = endpoint_entry.entry(minute).or_insert_with(HashMap::new);
+        let bucket = endpoint_entry.entry(minute).or_default();
         *bucket.entry(status).or_insert(0) += 1;
 
         let cutoff = minute.saturating_sub(MAX_MINUTES - 1);
@@ -0,0 +1,489 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::catalog::{find_prefix_conflicts, PrefixConflict};
+use crate::config::{self, Service};
+use crate::ports::{find_port_conflicts, join_ports, PortConflict};
+use crate::runtime::module_runtime;
+use crate::secrets::{load_secret_store, SecretStore};
+use crate::settings::RunnerSettings;
+use crate::webhook::schedule_contract_violations;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub service: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub services_dir: String,
+    pub services_checked: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Default)]
+struct IssueCollector {
+    issues: Vec<ValidationIssue>,
+}
+
+impl IssueCollector {
+    fn error(&mut self, code: &'static str, service: Option<&str>, message: String) {
+        self.push(Severity::Error, code, service, message);
+    }
+
+    fn warning(&mut self, code: &'static str, service: Option<&str>, message: String) {
+        self.push(Severity::Warning, code, service, message);
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        code: &'static str,
+        service: Option<&str>,
+        message: String,
+    ) {
+        self.issues.push(ValidationIssue {
+            severity,
+            code,
+            service: service.map(str::to_string),
+            message,
+        });
+    }
+}
+
+pub fn run_validation(settings: &RunnerSettings) -> Result<bool> {
+    let report = validate_services(settings);
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(report.valid)
+}
+
+pub fn validate_services(settings: &RunnerSettings) -> ValidationReport {
+    let services_dir = settings.services_dir.as_path();
+    let mut collector = IssueCollector::default();
+    let mut services_checked = 0;
+    let mut services = Vec::new();
+
+    let secrets = match load_secret_store(&settings.secrets_file) {
+        Ok(store) => store,
+        Err(error) => {
+            collector.error("invalid_secrets_file", None, format!("{error:#}"));
+            SecretStore::default()
+        }
+    };
+
+    if !services_dir.is_dir() {
+        collector.error(
+            "missing_services_dir",
+            None,
+            format!(
+                "services directory '{}' does not exist",
+                services_dir.display()
+            ),
+        );
+    } else {
+        match config::list_service_names(services_dir) {
+            Ok(names) => {
+                for name in names {
+                    services_checked += 1;
+                    if let Some(service) =
+                        inspect_service(services_dir, &name, &secrets, &mut collector)
+                    {
+                        services.push(service);
+                    }
+                }
+            }
+            Err(error) => collector.error("unreadable_services_dir", None, format!("{error:#}")),
+        }
+    }
+
+    check_prefix_collisions(&services, &mut collector);
+    check_port_overlaps(&services, settings.entry_port, &mut collector);
+    check_unpublished_queues(&services, &mut collector);
+    check_dependencies(&services, &mut collector);
+
+    build_report(services_dir, services_checked, collector.issues)
+}
+
+fn inspect_service(
+    services_dir: &Path,
+    name: &str,
+    secrets: &SecretStore,
+    collector: &mut IssueCollector,
+) -> Option<Service> {
+    let config_path = config::config_path(services_dir, name);
+    if !config_path.exists() {
+        collector.warning(
+            "missing_config",
+            Some(name),
+            format!(
+                "directory is skipped because {} does not exist",
+                config_path.display()
+            ),
+        );
+        return None;
+    }
+
+    for (code, path) in [
+        (
+            "missing_manifest",
+            config::service_manifest_path(services_dir, name),
+        ),
+        ("missing_openapi", config::openapi_path(services_dir, name)),
+    ] {
+        if !path.exists() {
+            collector.error(
+                code,
+                Some(name),
+                format!("{} does not exist", path.display()),
+            );
+        }
+    }
+
+    let service = match config::load_service(services_dir, name) {
+        Ok(service) => service,
+        Err(error) => {
+            collector.error("invalid_config", Some(name), format!("{error:#}"));
+            return None;
+        }
+    };
+
+    // What has to exist depends on the runtime, so it is checked once the
+    // configuration parses.
+    if let Err(error) = module_runtime(&service.runtime).entry_point(services_dir, name) {
+        collector.error("missing_module", Some(name), format!("{error:#}"));
+    }
+    if service.memory_limit_mb.is_some() && !service.runtime.enforces_memory_limit() {
+        collector.warning(
+            "memory_limit_unenforced",
+            Some(name),
+            format!(
+                "memory_limit_mb is not enforced for the {} runtime",
+                service.runtime.as_str()
+            ),
+        );
+    }
+
+    for schedule in service.undeclared_schedules() {
+        collector.error(
+            "undeclared_schedule",
+            Some(name),
+            format!(
+                "schedule endpoint '/{}' is not declared as a {} operation in its OpenAPI document",
+                schedule.endpoint, schedule.method
+            ),
+        );
+    }
+
+    for schedule in &service.schedules {
+        for violation in schedule_contract_violations(&service, schedule) {
+            collector.error(
+                "invalid_schedule_request",
+                Some(name),
+                format!(
+                    "schedule request '{} /{}' does not match its OpenAPI operation at {}: {}",
+                    schedule.method, schedule.endpoint, violation.location, violation.message
+                ),
+            );
+        }
+    }
+
+    for (variable, key) in &service.secrets {
+        if !secrets.contains(key) {
+            collector.error(
+                "missing_secret",
+                Some(name),
+                format!("secret '{key}' for {variable} is not defined in the secrets file"),
+            );
+        }
+    }
+
+    Some(service)
+}
+
+fn check_prefix_collisions(services: &[Service], collector: &mut IssueCollector) {
+    for conflict in find_prefix_conflicts(services) {
+        match &conflict {
+            PrefixConflict::Duplicate { .. } => {
+                collector.error("prefix_collision", None, conflict.to_string())
+            }
+            PrefixConflict::Shadowing { service, .. } => {
+                collector.error("prefix_shadowing", Some(service), conflict.to_string())
+            }
+        }
+    }
+}
+
+fn check_port_overlaps(services: &[Service], entry_port: u16, collector: &mut IssueCollector) {
+    for conflict in find_port_conflicts(services, entry_port) {
+        match conflict {
+            PortConflict::EntryPort { service, port } => collector.error(
+                "port_overlap",
+                Some(&service),
+                format!("runner ports include the runner entry port {port}"),
+            ),
+            PortConflict::Overlap {
+                service,
+                other,
+                ports,
+            } => collector.error(
+                "port_overlap",
+                Some(&service),
+                format!(
+                    "runner ports overlap with service '{}' on {}",
+                    other,
+                    join_ports(&ports)
+                ),
+            ),
+        }
+    }
+}
+
+fn check_unpublished_queues(services: &[Service], collector: &mut IssueCollector) {
+    let published: BTreeSet<&str> = services
+        .iter()
+        .flat_map(|service| {
+            let scheduled = service
+                .schedules
+                .iter()
+                .filter_map(|schedule| schedule.queue.as_deref());
+            service
+                .publishes
+                .iter()
+                .map(String::as_str)
+                .chain(scheduled)
+        })
+        .collect();
+
+    for service in services {
+        for listener in &service.queue_listeners {
+            if !published.contains(listener.queue.as_str()) {
+                collector.error(
+                    "unpublished_queue",
+                    Some(&service.name),
+                    format!(
+                        "listens to queue '{}' but no service declares it in 'publishes' or schedules it",
+                        listener.queue
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn check_dependencies(services: &[Service], collector: &mut IssueCollector) {
+    for service in services {
+        for dependency in &service.depends_on {
+            if !services
+                .iter()
+                .any(|candidate| &candidate.name == dependency)
+            {
+                collector.error(
+                    "unknown_dependency",
+                    Some(&service.name),
+                    format!("depends on '{dependency}', which is not a loaded service"),
+                );
+            }
+        }
+    }
+
+    if let Some(cycle) = config::dependency_cycle(services) {
+        collector.error(
+            "dependency_cycle",
+            Some(&cycle[0]),
+            format!("dependency cycle between services: {}", cycle.join(" -> ")),
+        );
+    }
+}
+
+fn build_report(
+    services_dir: &Path,
+    services_checked: usize,
+    issues: Vec<ValidationIssue>,
+) -> ValidationReport {
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+
+    ValidationReport {
+        valid: errors == 0,
+        services_dir: services_dir.display().to_string(),
+        services_checked,
+        errors,
+        warnings,
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    struct Fixture {
+        root: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir()
+                .join(format!("rustrunner-validate-{}-{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).expect("create fixture root");
+            Self { root }
+        }
+
+        fn service(&self, name: &str, config: &str, openapi: &str, with_module: bool) {
+            let dir = self.root.join(name);
+            fs::create_dir_all(dir.join("config")).expect("create service dir");
+            fs::write(dir.join("config").join("service.json"), config).expect("write config");
+            fs::write(dir.join("openapi.json"), openapi).expect("write openapi");
+            fs::write(dir.join("Cargo.toml"), "[package]\n").expect("write manifest");
+            if with_module {
+                fs::write(dir.join(format!("{name}.wasm")), b"\0asm").expect("write module");
+            }
+        }
+
+        fn validate(&self) -> ValidationReport {
+            let settings = RunnerSettings {
+                services_dir: self.root.clone(),
+                secrets_file: self.root.join("secrets.toml"),
+                ..RunnerSettings::default()
+            };
+            validate_services(&settings)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    const PING_OPENAPI: &str = r#"{ "paths": { "/ping": { "get": {} } } }"#;
+
+    fn codes(report: &ValidationReport) -> Vec<(&str, Option<&str>)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.code, issue.service.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn gathers_problems_across_every_service() {
+        let fixture = Fixture::new("problems");
+        fixture.service(
+            "alpha",
+            r#"{ "prefix": "shared", "url": "http://127.0.0.1:15001", "runners": 2,
+                 "domain": "demo", "type": "bff",
+                 "schedules": [["/missing", 10], ["/other", 5],
+                   { "endpoint": "/sync", "method": "POST", "interval_secs": 60,
+                     "body": { "until": "{{fired_at}}" } }],
+                 "secrets": { "API_TOKEN": "alpha.token" },
+                 "depends_on": ["beta", "ghost"],
+                 "listeners": [{ "orders.created": "/queues/orders" }] }"#,
+            r#"{ "paths": { "/ping": { "get": {} }, "/sync": { "post": { "requestBody": {
+                 "required": true, "content": { "application/json": {
+                 "schema": { "type": "object", "required": ["since"] } } } } } } } }"#,
+            false,
+        );
+        fixture.service(
+            "beta",
+            r#"{ "prefix": "/shared/", "url": "http://127.0.0.1:15002",
+                 "domain": "demo", "type": "adapter", "depends_on": ["alpha"] }"#,
+            PING_OPENAPI,
+            true,
+        );
+        fixture.service(
+            "gamma",
+            r#"{ "prefix": "gamma", "url": "http://127.0.0.1:15010", "domain": "demo" }"#,
+            PING_OPENAPI,
+            true,
+        );
+        fixture.service(
+            "delta",
+            r#"{ "prefix": "shared/extra", "url": "http://127.0.0.1:15020",
+                 "domain": "demo", "type": "business" }"#,
+            PING_OPENAPI,
+            true,
+        );
+        fs::create_dir_all(fixture.root.join("notes")).expect("create stray dir");
+
+        let report = fixture.validate();
+
+        assert!(!report.valid);
+        assert_eq!(report.services_checked, 5);
+        assert_eq!(report.warnings, 1);
+        assert_eq!(
+            codes(&report),
+            vec![
+                ("missing_module", Some("alpha")),
+                ("undeclared_schedule", Some("alpha")),
+                ("undeclared_schedule", Some("alpha")),
+                ("invalid_schedule_request", Some("alpha")),
+                ("missing_secret", Some("alpha")),
+                ("invalid_config", Some("gamma")),
+                ("missing_config", Some("notes")),
+                ("prefix_collision", None),
+                ("prefix_shadowing", Some("alpha")),
+                ("prefix_shadowing", Some("beta")),
+                ("port_overlap", Some("alpha")),
+                ("unpublished_queue", Some("alpha")),
+                ("unknown_dependency", Some("alpha")),
+                ("dependency_cycle", Some("alpha")),
+            ]
+        );
+        assert!(report.issues[3].message.contains("'POST /sync'"));
+        assert!(report.issues[8]
+            .message
+            .contains("shadows prefix 'shared/extra'"));
+        assert!(report.issues[10].message.contains("15002"));
+        assert!(report.issues[13].message.contains("alpha -> beta -> alpha"));
+    }
+
+    #[test]
+    fn accepts_consistent_catalogs() {
+        let fixture = Fixture::new("valid");
+        fixture.service(
+            "producer",
+            r#"{ "prefix": "producer", "url": "http://127.0.0.1:15001",
+                 "domain": "demo", "type": "business",
+                 "publishes": ["orders.created"],
+                 "schedules": [["/ping", 10]] }"#,
+            PING_OPENAPI,
+            true,
+        );
+        fixture.service(
+            "consumer",
+            r#"{ "prefix": "consumer", "url": "http://127.0.0.1:15002",
+                 "domain": "demo", "type": "adapter", "depends_on": ["producer"],
+                 "listeners": [{ "orders.created": "/queues/orders" }] }"#,
+            PING_OPENAPI,
+            true,
+        );
+
+        let report = fixture.validate();
+        assert!(report.valid, "{:?}", report.issues);
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.services_checked, 2);
+    }
+}
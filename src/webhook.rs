@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::config::{Service, ServiceSchedule};
+use crate::openapi::RequestParts;
+use crate::schema::SchemaViolation;
+
+const PLACEHOLDERS: [&str; 5] = [
+    "service",
+    "fired_at",
+    "fired_at_unix",
+    "previous_run",
+    "previous_run_unix",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BodyTemplate {
+    Json(Value),
+    Text(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct FireContext<'a> {
+    pub service: &'a str,
+    pub fired_at: DateTime<Utc>,
+    pub previous_run: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedMessage {
+    pub content_type: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl BodyTemplate {
+    pub fn from_value(value: Value) -> Self {
+        match value {
+            Value::String(text) => BodyTemplate::Text(text),
+            other => BodyTemplate::Json(other),
+        }
+    }
+
+    fn default_content_type(&self) -> &'static str {
+        match self {
+            BodyTemplate::Json(_) => "application/json",
+            BodyTemplate::Text(_) => "text/plain; charset=utf-8",
+        }
+    }
+
+    fn render(&self, context: &FireContext) -> Vec<u8> {
+        match self {
+            BodyTemplate::Json(value) => render_json(value, context).to_string().into_bytes(),
+            BodyTemplate::Text(text) => render_text(text, context).into_bytes(),
+        }
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        let mut texts = Vec::new();
+        match self {
+            BodyTemplate::Json(value) => collect_json_texts(value, &mut texts),
+            BodyTemplate::Text(text) => texts.push(text),
+        }
+        texts
+    }
+}
+
+impl FireContext<'_> {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let timestamp = |at: DateTime<Utc>| at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        match placeholder {
+            "service" => Some(self.service.to_string()),
+            "fired_at" => Some(timestamp(self.fired_at)),
+            "fired_at_unix" => Some(self.fired_at.timestamp().to_string()),
+            // Empty on the very first run, so templates can tell there is no cursor yet.
+            "previous_run" => Some(self.previous_run.map(timestamp).unwrap_or_default()),
+            "previous_run_unix" => Some(
+                self.previous_run
+                    .map(|at| at.timestamp().to_string())
+                    .unwrap_or_default(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+pub fn render_text(template: &str, context: &FireContext) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rendered.push_str(&rest[start..]);
+            return rendered;
+        };
+        match context.value(after[..end].trim()) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+fn render_json(value: &Value, context: &FireContext) -> Value {
+    match value {
+        Value::String(text) => Value::String(render_text(text, context)),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_json(item, context))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), render_json(item, context)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn collect_json_texts<'v>(value: &'v Value, texts: &mut Vec<&'v str>) {
+    match value {
+        Value::String(text) => texts.push(text),
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_json_texts(item, texts)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|item| collect_json_texts(item, texts)),
+        _ => {}
+    }
+}
+
+// Typos in a placeholder would otherwise be sent verbatim to the service.
+pub fn check_placeholders(
+    headers: &BTreeMap<String, String>,
+    body: Option<&BodyTemplate>,
+) -> Result<()> {
+    let texts = headers
+        .values()
+        .map(String::as_str)
+        .chain(body.map(BodyTemplate::texts).unwrap_or_default());
+
+    for text in texts {
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            let name = after[..end].trim();
+            if !PLACEHOLDERS.contains(&name) {
+                bail!(
+                    "unknown placeholder '{{{{{name}}}}}', expected one of: {}",
+                    PLACEHOLDERS.join(", ")
+                );
+            }
+            rest = &after[end + 2..];
+        }
+    }
+
+    Ok(())
+}
+
+pub fn render_schedule_request(
+    base_url: &str,
+    schedule: &ServiceSchedule,
+    context: &FireContext,
+) -> RenderedRequest {
+    let url = format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        schedule.endpoint.trim_start_matches('/')
+    );
+
+    let mut content_type = None;
+    let mut headers = Vec::new();
+    for (name, value) in &schedule.headers {
+        let value = render_text(value, context);
+        if name.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value);
+        } else {
+            headers.push((name.clone(), value));
+        }
+    }
+
+    let body = match &schedule.body {
+        Some(template) => {
+            content_type.get_or_insert_with(|| template.default_content_type().to_string());
+            template.render(context)
+        }
+        None => Vec::new(),
+    };
+
+    RenderedRequest {
+        method: schedule.method.clone(),
+        url,
+        headers,
+        content_type,
+        body,
+    }
+}
+
+// Without a body, a queue schedule publishes a small tick event.
+pub fn render_queue_message(schedule: &ServiceSchedule, context: &FireContext) -> RenderedMessage {
+    let template = schedule.body.clone().unwrap_or_else(|| {
+        BodyTemplate::Json(json!({ "service": "{{service}}", "fired_at": "{{fired_at}}" }))
+    });
+    let content_type = schedule
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| render_text(value, context))
+        .unwrap_or_else(|| template.default_content_type().to_string());
+
+    RenderedMessage {
+        content_type,
+        payload: template.render(context),
+    }
+}
+
+// Renders the request as it would look on a regular run and checks it against
+// the OpenAPI operation declared for that method and path.
+pub fn schedule_contract_violations(
+    service: &Service,
+    schedule: &ServiceSchedule,
+) -> Vec<SchemaViolation> {
+    if schedule.queue.is_some() {
+        return Vec::new();
+    }
+    let Some(resolved) = service
+        .operations
+        .find(&schedule.method, &schedule.endpoint)
+    else {
+        return Vec::new();
+    };
+
+    let fired_at = Utc::now();
+    let context = FireContext {
+        service: &service.name,
+        fired_at,
+        previous_run: Some(fired_at - Duration::minutes(1)),
+    };
+    let request = render_schedule_request(&service.base_url, schedule, &context);
+
+    resolved.operation.validate_request(&RequestParts {
+        path_params: &resolved.params,
+        query: None,
+        headers: &request.headers,
+        content_type: request.content_type.as_deref(),
+        body: &request.body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use crate::cron::ScheduleTrigger;
+
+    #[test]
+    fn renders_placeholders_in_headers_and_json_bodies() {
+        let schedule = ServiceSchedule {
+            endpoint: "sync".into(),
+            queue: None,
+            trigger: ScheduleTrigger::Interval { secs: 60 },
+            method: "POST".into(),
+            headers: BTreeMap::from([("X-Fired-At".into(), "{{fired_at_unix}}".into())]),
+            body: Some(BodyTemplate::from_value(json!({
+                "since": "{{ previous_run }}",
+                "until": "{{fired_at}}",
+                "source": "{{service}}",
+                "keep": "{{unknown}}",
+                "limit": 100
+            }))),
+            concurrency: Default::default(),
+            catch_up: Default::default(),
+            jitter_secs: 0,
+            target: Default::default(),
+            retry: Default::default(),
+            alert_after: 3,
+            origin: Default::default(),
+        };
+        let context = FireContext {
+            service: "sap",
+            fired_at: Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap(),
+            previous_run: Some(Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap()),
+        };
+
+        let request = render_schedule_request("http://127.0.0.1:15001/", &schedule, &context);
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, "http://127.0.0.1:15001/sync");
+        assert_eq!(
+            request.headers,
+            vec![("X-Fired-At".to_string(), "1772359200".to_string())]
+        );
+        assert_eq!(request.content_type.as_deref(), Some("application/json"));
+        let body: Value = serde_json::from_slice(&request.body).expect("json body");
+        assert_eq!(
+            body,
+            json!({
+                "since": "2026-03-01T09:00:00Z",
+                "until": "2026-03-01T10:00:00Z",
+                "source": "sap",
+                "keep": "{{unknown}}",
+                "limit": 100
+            })
+        );
+
+        let first = FireContext {
+            previous_run: None,
+            ..context
+        };
+        assert_eq!(render_text("since={{previous_run}}", &first), "since=");
+
+        assert!(check_placeholders(&schedule.headers, None).is_ok());
+        let error = check_placeholders(&schedule.headers, schedule.body.as_ref()).unwrap_err();
+        assert!(error.to_string().contains("'{{unknown}}'"));
+
+        let tick = ServiceSchedule {
+            endpoint: String::new(),
+            queue: Some("orders.tick".into()),
+            headers: BTreeMap::new(),
+            body: None,
+            ..schedule
+        };
+        let message = render_queue_message(&tick, &first);
+        assert_eq!(message.content_type, "application/json");
+        assert_eq!(
+            serde_json::from_slice::<Value>(&message.payload).expect("json payload"),
+            json!({ "service": "sap", "fired_at": "2026-03-01T10:00:00Z" })
+        );
+    }
+}